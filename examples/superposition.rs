@@ -0,0 +1,71 @@
+//! Compares the speed and the results of the Kabsch and QCP methods on
+//! random conformations.
+//!
+//! Run with `cargo run --release --example superposition`.
+
+extern crate biost;
+
+use std::time::Instant;
+
+use biost::superposition::qcp_rmsd;
+use biost::{fitted_rmsd, Vector3d};
+
+const ATOMS: usize = 200;
+const FRAMES: usize = 200;
+
+/// A linear congruential generator, good enough for test coordinates.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> f64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn main() {
+    let mut random = Random(12345);
+    let frames: Vec<Vec<Vector3d<f64>>> = (0..FRAMES)
+        .map(|_| {
+            (0..ATOMS)
+                .map(|_| {
+                    let x = random.next() * 30.0;
+                    let y = random.next() * 30.0;
+                    let z = random.next() * 30.0;
+                    Vector3d::new(x, y, z)
+                })
+                .collect()
+        })
+        .collect();
+
+    let start = Instant::now();
+    let mut kabsch = Vec::with_capacity(FRAMES * FRAMES);
+    for a in frames.iter() {
+        for b in frames.iter() {
+            kabsch.push(fitted_rmsd(a, b, None).unwrap());
+        }
+    }
+    let kabsch_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut qcp = Vec::with_capacity(FRAMES * FRAMES);
+    for a in frames.iter() {
+        for b in frames.iter() {
+            qcp.push(qcp_rmsd(a, b, None).unwrap());
+        }
+    }
+    let qcp_time = start.elapsed();
+
+    let max_difference = kabsch
+        .iter()
+        .zip(qcp.iter())
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f64::max);
+    println!("{} pairs of {} atoms", FRAMES * FRAMES, ATOMS);
+    println!("kabsch: {:?}", kabsch_time);
+    println!("qcp:    {:?}", qcp_time);
+    println!("maximum difference of RMSD: {:e}", max_difference);
+}
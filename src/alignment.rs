@@ -0,0 +1,462 @@
+//! Sequence-independent structural alignment of protein chains in the
+//! manner of TM-align.
+//!
+//! Two chains are compared by their CA atoms only. Initial alignments are
+//! built from
+//!
+//! * gapless threading, i.e. every shift of one chain along the other, and
+//! * the secondary structures assigned from the CA geometry,
+//!
+//! and each is refined by repeating superposition maximizing the TM-score
+//! and dynamic programming on the similarity `1 / (1 + (d / d0)^2)` of all
+//! pairs of residues at distance `d` after the superposition.
+
+use float::Float;
+use rmsd::fitted_rmsd;
+use similarity::{tm_score, tm_score_d0, tm_score_search};
+use superposition::kabsch;
+use transform::Transform;
+use Vector3d;
+
+/// The gap opening penalty of the refinement; extending gaps is free.
+const GAP_OPEN: f32 = -0.6;
+
+/// The gap opening penalty of the alignment of secondary structures.
+const SECONDARY_GAP_OPEN: f32 = -1.0;
+
+/// The maximum number of dynamic programming steps of a refinement.
+const MAX_REFINEMENTS: usize = 30;
+
+/// Only every this-th fragment seeds a superposition during refinement.
+const SEARCH_STEP: usize = 40;
+
+/// The distance in Angstroms within which pairs count as aligned.
+const ALIGNED_CUTOFF: f32 = 5.0;
+
+/// The result of a structural alignment of two chains.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructuralAlignment<T: Float = f32> {
+    /// The indices of aligned residues of the first and second chains in
+    /// ascending order.
+    pub pairs: Vec<(usize, usize)>,
+    /// The transformation superposing the first chain onto the second.
+    pub transform: Transform<T>,
+    /// The distance between each aligned pair after superposition.
+    pub distances: Vec<T>,
+    /// The TM-score normalized by the length of the first chain.
+    pub tm_score1: T,
+    /// The TM-score normalized by the length of the second chain.
+    pub tm_score2: T,
+    /// The number of aligned pairs within 5 Angstroms after superposition.
+    pub aligned_length: usize,
+    /// The RMSD of the aligned pairs within 5 Angstroms.
+    pub rmsd: T,
+}
+
+/// Aligns two chains given by their CA positions and superposes the first
+/// onto the second.
+///
+/// Returns `None` if either chain is empty.
+///
+/// # Example
+///
+/// ```
+/// use biost::alignment::align;
+/// use biost::{Rotation, Vector3d};
+/// let chain2: Vec<Vector3d> = (0..40)
+///     .map(|i| {
+///         let t = (i as f32 * 100.0).to_radians();
+///         let bend = if i < 20 { 0.0 } else { (i - 20) as f32 * 2.0 };
+///         Vector3d::new(2.3 * t.cos() + bend, 2.3 * t.sin(), 1.5 * i as f32)
+///     })
+///     .collect();
+/// let rotation = Rotation::from_axis_angle(&Vector3d::new(1.0, 0.0, 0.0), 2.0).unwrap();
+/// // The first chain lacks the first five residues.
+/// let chain1: Vec<Vector3d> = chain2[5..].iter().map(|v| rotation.rotate(v)).collect();
+///
+/// let alignment = align(&chain1, &chain2).unwrap();
+/// assert_eq!((0, 5), alignment.pairs[0]);
+/// assert_eq!(35, alignment.aligned_length);
+/// assert!(alignment.tm_score1 > 0.99);
+/// assert!(alignment.rmsd < 1e-3);
+/// ```
+pub fn align<T: Float>(
+    chain1: &[Vector3d<T>],
+    chain2: &[Vector3d<T>],
+) -> Option<StructuralAlignment<T>> {
+    if chain1.is_empty() || chain2.is_empty() {
+        return None;
+    }
+    let length = chain1.len().min(chain2.len());
+    let d0 = tm_score_d0(length);
+
+    let initials = [
+        threading(chain1, chain2, length, d0),
+        secondary_structure_alignment(chain1, chain2),
+    ];
+    let mut best: Option<(T, Vec<(usize, usize)>)> = None;
+    for initial in initials.iter() {
+        if let Some((score, pairs)) = refine(chain1, chain2, initial.clone(), length, d0) {
+            if best.as_ref().is_none_or(|&(max, _)| score > max) {
+                best = Some((score, pairs));
+            }
+        }
+    }
+    let (_, pairs) = best?;
+
+    let (mobile, reference) = aligned(chain1, chain2, &pairs);
+    let score1 = tm_score(&mobile, &reference, chain1.len())?;
+    let score2 = tm_score(&mobile, &reference, chain2.len())?;
+    let cutoff = T::from_f32(ALIGNED_CUTOFF);
+    let close: Vec<usize> = (0..pairs.len())
+        .filter(|&k| score2.distances[k] < cutoff)
+        .collect();
+    let close_mobile: Vec<Vector3d<T>> = close.iter().map(|&k| mobile[k]).collect();
+    let close_reference: Vec<Vector3d<T>> = close.iter().map(|&k| reference[k]).collect();
+    Some(StructuralAlignment {
+        pairs,
+        transform: score2.transform,
+        distances: score2.distances,
+        tm_score1: score1.score,
+        tm_score2: score2.score,
+        aligned_length: close.len(),
+        rmsd: fitted_rmsd(&close_mobile, &close_reference, None).unwrap_or(T::zero()),
+    })
+}
+
+/// Returns the positions of aligned pairs of the first and second chains.
+fn aligned<T: Float>(
+    chain1: &[Vector3d<T>],
+    chain2: &[Vector3d<T>],
+    pairs: &[(usize, usize)],
+) -> (Vec<Vector3d<T>>, Vec<Vector3d<T>>) {
+    pairs.iter().map(|&(i, j)| (chain1[i], chain2[j])).unzip()
+}
+
+/// Repeats superposition and dynamic programming from an initial alignment
+/// until the alignment converges, and returns the best alignment with its
+/// approximate TM-score.
+fn refine<T: Float>(
+    chain1: &[Vector3d<T>],
+    chain2: &[Vector3d<T>],
+    mut pairs: Vec<(usize, usize)>,
+    length: usize,
+    d0: T,
+) -> Option<(T, Vec<(usize, usize)>)> {
+    let d0_squared = d0 * d0;
+    let mut best: Option<(T, Vec<(usize, usize)>)> = None;
+    for _ in 0..MAX_REFINEMENTS {
+        if pairs.is_empty() {
+            break;
+        }
+        let (mobile, reference) = aligned(chain1, chain2, &pairs);
+        let (score, transform) = tm_score_search(&mobile, &reference, length, d0, SEARCH_STEP)?;
+        if best.as_ref().is_none_or(|&(max, _)| score > max) {
+            best = Some((score, pairs.clone()));
+        }
+
+        let moved: Vec<Vector3d<T>> = chain1.iter().map(|v| transform.apply(v)).collect();
+        let next =
+            dynamic_programming(chain1.len(), chain2.len(), T::from_f32(GAP_OPEN), |i, j| {
+                T::one() / (T::one() + moved[i].distance_squared(&chain2[j]) / d0_squared)
+            });
+        if next == pairs {
+            break;
+        }
+        pairs = next;
+    }
+    best
+}
+
+/// Returns the gapless alignment with the highest approximate TM-score
+/// among those overlapping by at least half of the shorter chain.
+fn threading<T: Float>(
+    chain1: &[Vector3d<T>],
+    chain2: &[Vector3d<T>],
+    length: usize,
+    d0: T,
+) -> Vec<(usize, usize)> {
+    let (len1, len2) = (chain1.len() as isize, chain2.len() as isize);
+    let min_overlap = (length as isize / 2).max(1);
+    let mut best: Option<(T, Vec<(usize, usize)>)> = None;
+    for shift in (1 - len1)..len2 {
+        let pairs: Vec<(usize, usize)> = (0..len1)
+            .filter(|&i| 0 <= i + shift && i + shift < len2)
+            .map(|i| (i as usize, (i + shift) as usize))
+            .collect();
+        if (pairs.len() as isize) < min_overlap {
+            continue;
+        }
+        let (mobile, reference) = aligned(chain1, chain2, &pairs);
+        let score = fast_tm_score(&mobile, &reference, length, d0);
+        if best.as_ref().is_none_or(|&(max, _)| score > max) {
+            best = Some((score, pairs));
+        }
+    }
+    best.map_or_else(Vec::new, |(_, pairs)| pairs)
+}
+
+/// Returns a rough TM-score from the superposition of all pairs refined
+/// once onto the close pairs.
+fn fast_tm_score<T: Float>(
+    mobile: &[Vector3d<T>],
+    reference: &[Vector3d<T>],
+    length: usize,
+    d0: T,
+) -> T {
+    let d0_squared = d0 * d0;
+    let cutoff = d0.max(T::from_f32(4.5)).min(T::from_f32(8.0));
+    let mut weights = vec![T::one(); mobile.len()];
+    let mut best = T::zero();
+    for _ in 0..2 {
+        let transform = match kabsch(mobile, reference, Some(&weights)) {
+            Some(transform) => transform,
+            None => break,
+        };
+        let mut sum = T::zero();
+        for ((p, q), weight) in mobile.iter().zip(reference).zip(weights.iter_mut()) {
+            let d_squared = transform.apply(p).distance_squared(q);
+            sum += T::one() / (T::one() + d_squared / d0_squared);
+            *weight = if d_squared < cutoff * cutoff {
+                T::one()
+            } else {
+                T::zero()
+            };
+        }
+        best = best.max(sum / T::from_f64(length as f64));
+    }
+    best
+}
+
+/// The secondary structure of a residue assigned from CA positions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Secondary {
+    Helix,
+    Strand,
+    Turn,
+    Coil,
+}
+
+/// Assigns secondary structures by comparing the distances between CA
+/// atoms within five residues with those of ideal helices and strands.
+fn secondary_structures<T: Float>(chain: &[Vector3d<T>]) -> Vec<Secondary> {
+    let matches = |distances: &[f64; 6], ideal: &[f64; 6], tolerance: f64| {
+        distances
+            .iter()
+            .zip(ideal.iter())
+            .all(|(d, i)| (d - i).abs() < tolerance)
+    };
+    (0..chain.len())
+        .map(|i| {
+            if i < 2 || i + 2 >= chain.len() {
+                return Secondary::Coil;
+            }
+            let d = |a: usize, b: usize| chain[a].distance(&chain[b]).to_f64();
+            // The distances 1-3, 1-4, 1-5, 2-4, 2-5 and 3-5 within the
+            // five residues centered at i
+            let distances = [
+                d(i - 2, i),
+                d(i - 2, i + 1),
+                d(i - 2, i + 2),
+                d(i - 1, i + 1),
+                d(i - 1, i + 2),
+                d(i, i + 2),
+            ];
+            if matches(&distances, &[5.45, 5.18, 6.37, 5.45, 5.18, 5.45], 2.1) {
+                Secondary::Helix
+            } else if matches(&distances, &[6.1, 10.4, 13.0, 6.1, 10.4, 6.1], 1.42) {
+                Secondary::Strand
+            } else if distances[2] < 8.0 {
+                Secondary::Turn
+            } else {
+                Secondary::Coil
+            }
+        })
+        .collect()
+}
+
+/// Returns the alignment maximizing the number of residues in the same
+/// secondary structure.
+fn secondary_structure_alignment<T: Float>(
+    chain1: &[Vector3d<T>],
+    chain2: &[Vector3d<T>],
+) -> Vec<(usize, usize)> {
+    let secondary1 = secondary_structures(chain1);
+    let secondary2 = secondary_structures(chain2);
+    dynamic_programming(
+        chain1.len(),
+        chain2.len(),
+        T::from_f32(SECONDARY_GAP_OPEN),
+        |i, j| {
+            if secondary1[i] == secondary2[j] {
+                T::one()
+            } else {
+                T::zero()
+            }
+        },
+    )
+}
+
+/// Returns the global alignment maximizing the sum of the scores of the
+/// aligned pairs plus the penalty for opening each gap.
+///
+/// Gaps are free to extend and at the ends of the chains.
+fn dynamic_programming<T: Float, F: Fn(usize, usize) -> T>(
+    len1: usize,
+    len2: usize,
+    gap_open: T,
+    score: F,
+) -> Vec<(usize, usize)> {
+    // values[i][j] is the best score of the first i and j residues, and
+    // diagonal[i][j] tells whether it ends with the pair (i - 1, j - 1).
+    let mut values = vec![vec![T::zero(); len2 + 1]; len1 + 1];
+    let mut diagonal = vec![vec![false; len2 + 1]; len1 + 1];
+    // Returns the score of skipping a residue after the first i and j
+    // residues, which is free after the end of either chain.
+    let gap = |values: &[Vec<T>], diagonal: &[Vec<bool>], i: usize, j: usize| {
+        if diagonal[i][j] && i < len1 && j < len2 {
+            values[i][j] + gap_open
+        } else {
+            values[i][j]
+        }
+    };
+    for i in 1..=len1 {
+        for j in 1..=len2 {
+            let matched = values[i - 1][j - 1] + score(i - 1, j - 1);
+            let skip1 = gap(&values, &diagonal, i - 1, j);
+            let skip2 = gap(&values, &diagonal, i, j - 1);
+            if matched >= skip1 && matched >= skip2 {
+                values[i][j] = matched;
+                diagonal[i][j] = true;
+            } else {
+                values[i][j] = skip1.max(skip2);
+            }
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (len1, len2);
+    while i > 0 && j > 0 {
+        if diagonal[i][j] {
+            pairs.push((i - 1, j - 1));
+            i -= 1;
+            j -= 1;
+        } else if gap(&values, &diagonal, i, j - 1) >= gap(&values, &diagonal, i - 1, j) {
+            j -= 1;
+        } else {
+            i -= 1;
+        }
+    }
+    pairs.reverse();
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rotation::{EulerConvention, Rotation};
+
+    /// Returns a chain of a helix, a strand and another helix.
+    fn sample() -> Vec<Vector3d<f64>> {
+        let mut chain = Vec::new();
+        for i in 0..14 {
+            let t = (i as f64 * 100.0).to_radians();
+            chain.push(Vector3d::new(2.3 * t.cos(), 2.3 * t.sin(), 1.5 * i as f64));
+        }
+        let start = chain[13];
+        for i in 1..9 {
+            let zigzag = if i % 2 == 0 { 0.9 } else { -0.9 };
+            chain.push(start + Vector3d::new(3.3 * i as f64, zigzag, 0.0));
+        }
+        let start = chain[21] + Vector3d::new(3.0, 0.0, -3.0);
+        for i in 0..12 {
+            let t = (i as f64 * 100.0).to_radians();
+            let offset = Vector3d::new(2.3 * t.cos(), -1.5 * i as f64, 2.3 * t.sin());
+            chain.push(start + offset);
+        }
+        chain
+    }
+
+    /// Displaces positions irregularly to break the screw symmetry of
+    /// helices.
+    fn perturbed(chain: &[Vector3d<f64>]) -> Vec<Vector3d<f64>> {
+        chain
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let t = i as f64;
+                *v + Vector3d::new((1.7 * t).sin(), (2.3 * t).cos(), (0.9 * t).sin()) * 0.6
+            })
+            .collect()
+    }
+
+    fn moved(chain: &[Vector3d<f64>]) -> Vec<Vector3d<f64>> {
+        let transform = Transform::new(
+            Rotation::from_euler(EulerConvention::ZYZ, 1.0, 0.5, -2.0),
+            Vector3d::new(10.0, -5.0, 3.0),
+        );
+        chain.iter().map(|v| transform.apply(v)).collect()
+    }
+
+    #[test]
+    fn test_secondary_structures() {
+        let secondary = secondary_structures(&sample());
+        assert!(secondary[2..12].iter().all(|&s| s == Secondary::Helix));
+        assert!(secondary[16..20].iter().all(|&s| s == Secondary::Strand));
+        assert_eq!(Secondary::Coil, secondary[0]);
+    }
+
+    #[test]
+    fn test_dynamic_programming() {
+        let scores = [[1.0, 0.0, 0.0], [0.0, 0.0, 1.0]];
+        let pairs = dynamic_programming(2, 3, -0.6, |i, j| scores[i][j]);
+        assert_eq!(vec![(0, 0), (1, 2)], pairs);
+        // Opening a gap costs more than the gain.
+        let scores = [[1.0, 0.0, 0.0], [0.0, 0.5, 1.0]];
+        let pairs = dynamic_programming(2, 3, -2.0, |i, j| scores[i][j]);
+        assert_eq!(vec![(0, 0), (1, 1)], pairs);
+        assert!(dynamic_programming(0, 3, -0.6, |_, _| 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_align_identical() {
+        let chain2 = perturbed(&sample());
+        let chain1 = moved(&chain2);
+        let alignment = align(&chain1, &chain2).unwrap();
+        let expected: Vec<_> = (0..chain2.len()).map(|i| (i, i)).collect();
+        assert_eq!(expected, alignment.pairs);
+        assert!((alignment.tm_score1 - 1.0).abs() < 1e-9);
+        assert!((alignment.tm_score2 - 1.0).abs() < 1e-9);
+        assert_eq!(chain2.len(), alignment.aligned_length);
+        assert!(alignment.rmsd < 1e-6);
+        assert!((alignment.transform.apply(&chain1[7]) - chain2[7]).norm() < 1e-6);
+    }
+
+    #[test]
+    fn test_align_deletion() {
+        let chain2 = perturbed(&sample());
+        // Delete four residues of the strand.
+        let mut chain1 = chain2[..16].to_vec();
+        chain1.extend_from_slice(&chain2[20..]);
+        let chain1 = moved(&chain1);
+
+        let alignment = align(&chain1, &chain2).unwrap();
+        for &(i, j) in alignment.pairs.iter() {
+            if i < 15 {
+                assert_eq!(i, j);
+            } else if i > 16 {
+                assert_eq!(i + 4, j);
+            }
+        }
+        assert!(alignment.pairs.len() >= 28);
+        assert!(alignment.tm_score1 > 0.9);
+        assert!(alignment.tm_score1 > alignment.tm_score2);
+    }
+
+    #[test]
+    fn test_empty() {
+        let chain = sample();
+        assert!(align(&chain, &[]).is_none());
+        assert!(align(&[], &chain).is_none());
+    }
+}
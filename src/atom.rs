@@ -0,0 +1,62 @@
+use Vector3d;
+
+/// An atom as described by a coordinate record of a structure file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Atom {
+    /// The atom serial number.
+    pub serial: u32,
+    /// The atom name without surrounding spaces, e.g. `CA`.
+    pub name: String,
+    /// The alternate location indicator.
+    pub alt_loc: Option<char>,
+    /// The residue name, e.g. `ALA`.
+    pub res_name: String,
+    /// The chain identifier, which may be empty.
+    pub chain_id: String,
+    /// The residue sequence number.
+    pub res_seq: i32,
+    /// The insertion code of the residue.
+    pub i_code: Option<char>,
+    /// The Cartesian coordinates in Angstroms.
+    pub position: Vector3d,
+    pub occupancy: f32,
+    /// The isotropic temperature factor.
+    pub temp_factor: f32,
+    /// The element symbol as written in the file, which may be empty.
+    pub element: String,
+    /// The formal charge.
+    pub charge: i8,
+    /// Whether the atom comes from a `HETATM` record.
+    pub hetero: bool,
+}
+
+impl Atom {
+    /// Returns an atom with given name and position, and default values
+    /// for all other fields.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{Atom, Vector3d};
+    /// let atom = Atom::new("CA", Vector3d::new(1.0, 2.0, 3.0));
+    /// assert_eq!("CA", atom.name);
+    /// assert_eq!(1.0, atom.occupancy);
+    /// ```
+    pub fn new(name: &str, position: Vector3d) -> Self {
+        Atom {
+            serial: 0,
+            name: name.to_string(),
+            alt_loc: None,
+            res_name: String::new(),
+            chain_id: String::new(),
+            res_seq: 0,
+            i_code: None,
+            position,
+            occupancy: 1.0,
+            temp_factor: 0.0,
+            element: String::new(),
+            charge: 0,
+            hetero: false,
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::lexer::{tokenize, Token};
+use super::{Error, ErrorKind};
+
+/// A value of a data item.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// The value is unknown, written as `?`.
+    Unknown,
+    /// The item does not apply, written as `.`.
+    Inapplicable,
+    Text(String),
+}
+
+impl Value {
+    /// Returns the text, or `None` for unknown or inapplicable values.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::Text(ref text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+/// The data items of one category, e.g. `_atom_site`.
+///
+/// Items given in a `loop_` form the columns of a table with one row per
+/// loop packet; items given as single key-value pairs form a table with a
+/// single row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Table {
+    /// The tags of the columns, e.g. `_atom_site.id`.
+    pub tags: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    /// The line number of the first value of each row.
+    pub lines: Vec<usize>,
+}
+
+impl Table {
+    /// Returns the index of the column with given tag, compared
+    /// case-insensitively.
+    pub fn column(&self, tag: &str) -> Option<usize> {
+        self.tags.iter().position(|t| t.eq_ignore_ascii_case(tag))
+    }
+
+    /// Returns the value in a row of the column with given tag.
+    pub fn get(&self, row: usize, tag: &str) -> Option<&Value> {
+        self.column(tag).and_then(|j| self.rows[row].get(j))
+    }
+}
+
+/// A data block, which starts with `data_` followed by its name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataBlock {
+    pub name: String,
+    pub tables: Vec<Table>,
+}
+
+impl DataBlock {
+    /// Returns the table of a category, e.g. `_atom_site`, compared
+    /// case-insensitively.
+    pub fn table(&self, category: &str) -> Option<&Table> {
+        self.tables
+            .iter()
+            .find(|table| self::category(&table.tags[0]).eq_ignore_ascii_case(category))
+    }
+
+    /// Returns the value of a non-looped item.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::cif::Value;
+    /// let text = "data_1ABC\n_entry.id 1ABC\n_cell.length_a ?\n";
+    /// let blocks = biost::cif::parse(text.as_bytes()).unwrap();
+    /// assert_eq!(Some(&Value::Text("1ABC".to_string())), blocks[0].get("_entry.id"));
+    /// assert_eq!(Some(&Value::Unknown), blocks[0].get("_cell.length_a"));
+    /// assert_eq!(None, blocks[0].get("_cell.length_b"));
+    /// ```
+    pub fn get(&self, tag: &str) -> Option<&Value> {
+        let table = self.table(category(tag))?;
+        if table.rows.len() != 1 {
+            return None;
+        }
+        table.get(0, tag)
+    }
+}
+
+/// Returns the category part of a tag, i.e. the part before the first `.`.
+fn category(tag: &str) -> &str {
+    tag.split('.').next().unwrap_or(tag)
+}
+
+/// Reads all data blocks of CIF formatted text.
+pub fn parse<R: BufRead>(reader: R) -> Result<Vec<DataBlock>, Error> {
+    let tokens = tokenize(reader)?;
+    let mut blocks: Vec<DataBlock> = Vec::new();
+    // Indices of the tables of non-looped items in the current block.
+    let mut item_tables: Vec<usize> = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
+
+    while let Some((lineno, token)) = tokens.next() {
+        if let Token::DataBlock(name) = token {
+            blocks.push(DataBlock {
+                name,
+                tables: Vec::new(),
+            });
+            item_tables.clear();
+            continue;
+        }
+        let block = match blocks.last_mut() {
+            Some(block) => block,
+            None => return Err(Error::new(lineno, ErrorKind::MissingDataBlock)),
+        };
+        match token {
+            Token::Loop => {
+                let mut tags = Vec::new();
+                while let Some(&(_, Token::Tag(_))) = tokens.peek() {
+                    if let Some((_, Token::Tag(tag))) = tokens.next() {
+                        tags.push(tag);
+                    }
+                }
+                if tags.is_empty() {
+                    return Err(Error::new(lineno, ErrorKind::EmptyLoop));
+                }
+                let mut table = Table {
+                    tags,
+                    rows: Vec::new(),
+                    lines: Vec::new(),
+                };
+                let mut row = Vec::new();
+                let mut last = lineno;
+                while let Some(&(_, Token::Value(_))) = tokens.peek() {
+                    if let Some((line, Token::Value(value))) = tokens.next() {
+                        if row.is_empty() {
+                            table.lines.push(line);
+                        }
+                        row.push(value);
+                        last = line;
+                    }
+                    if row.len() == table.tags.len() {
+                        table.rows.push(row);
+                        row = Vec::new();
+                    }
+                }
+                if !row.is_empty() {
+                    return Err(Error::new(last, ErrorKind::IncompleteLoop));
+                }
+                block.tables.push(table);
+            }
+            Token::Tag(tag) => {
+                let value = match tokens.next() {
+                    Some((_, Token::Value(value))) => value,
+                    _ => return Err(Error::new(lineno, ErrorKind::MissingValue(tag))),
+                };
+                let name = category(&tag).to_string();
+                let existing = item_tables
+                    .iter()
+                    .cloned()
+                    .find(|&i| category(&block.tables[i].tags[0]).eq_ignore_ascii_case(&name));
+                match existing {
+                    Some(i) => {
+                        let table = &mut block.tables[i];
+                        table.tags.push(tag);
+                        table.rows[0].push(value);
+                    }
+                    None => {
+                        item_tables.push(block.tables.len());
+                        block.tables.push(Table {
+                            tags: vec![tag],
+                            rows: vec![vec![value]],
+                            lines: vec![lineno],
+                        });
+                    }
+                }
+            }
+            Token::Value(_) => return Err(Error::new(lineno, ErrorKind::UnexpectedValue)),
+            Token::DataBlock(_) => unreachable!(),
+        }
+    }
+
+    Ok(blocks)
+}
+
+/// Reads all data blocks of a CIF file.
+///
+/// An error in opening the file is reported at line zero.
+pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<Vec<DataBlock>, Error> {
+    let file = File::open(path).map_err(|err| Error::new(0, ErrorKind::Io(err)))?;
+    parse(BufReader::new(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> Value {
+        Value::Text(value.to_string())
+    }
+
+    #[test]
+    fn test_parse() {
+        let input = "\
+data_first
+_cell.length_a 10.0
+_cell.length_b 20.0
+loop_
+_atom_type.symbol
+_atom_type.radius
+C 1.7
+N ?
+data_second
+_entry.id X
+";
+        let blocks = parse(input.as_bytes()).unwrap();
+        assert_eq!(2, blocks.len());
+        assert_eq!("first", blocks[0].name);
+        assert_eq!(2, blocks[0].tables.len());
+
+        let cell = blocks[0].table("_cell").unwrap();
+        assert_eq!(vec!["_cell.length_a", "_cell.length_b"], cell.tags);
+        assert_eq!(vec![vec![text("10.0"), text("20.0")]], cell.rows);
+        assert_eq!(Some(&text("20.0")), blocks[0].get("_CELL.LENGTH_B"));
+
+        let atom_type = blocks[0].table("_atom_type").unwrap();
+        assert_eq!(2, atom_type.rows.len());
+        assert_eq!(vec![7, 8], atom_type.lines);
+        assert_eq!(Some(&text("N")), atom_type.get(1, "_atom_type.symbol"));
+        assert_eq!(Some(&Value::Unknown), atom_type.get(1, "_atom_type.radius"));
+        assert_eq!(None, blocks[0].get("_atom_type.symbol"));
+
+        assert_eq!("second", blocks[1].name);
+        assert_eq!(
+            Some("X"),
+            blocks[1].get("_entry.id").and_then(Value::as_str)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = parse("_entry.id X\n".as_bytes()).unwrap_err();
+        match *err.kind() {
+            ErrorKind::MissingDataBlock => {}
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+
+        let err = parse("data_x\nloop_\n_a.b\n_a.c\n1 2\n3\n".as_bytes()).unwrap_err();
+        assert_eq!(6, err.line());
+        match *err.kind() {
+            ErrorKind::IncompleteLoop => {}
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+
+        let err = parse("data_x\n_a.b\n_a.c 1\n".as_bytes()).unwrap_err();
+        assert_eq!(2, err.line());
+        match *err.kind() {
+            ErrorKind::MissingValue(ref tag) => assert_eq!("_a.b", tag),
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+
+        let err = parse("data_x\n_a.b 1 2\n".as_bytes()).unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnexpectedValue => {}
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+
+        let err = parse("data_x\nloop_\n1\n".as_bytes()).unwrap_err();
+        match *err.kind() {
+            ErrorKind::EmptyLoop => {}
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+    }
+}
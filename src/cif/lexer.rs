@@ -0,0 +1,169 @@
+use std::io::BufRead;
+
+use super::{Error, ErrorKind, Value};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    DataBlock(String),
+    Loop,
+    Tag(String),
+    Value(Value),
+}
+
+/// Splits CIF text into tokens paired with their line numbers.
+pub fn tokenize<R: BufRead>(reader: R) -> Result<Vec<(usize, Token)>, Error> {
+    let mut tokens = Vec::new();
+    // The first line and the content of an open text field.
+    let mut text_field: Option<(usize, String)> = None;
+    let mut lineno = 0;
+
+    for line in reader.lines() {
+        lineno += 1;
+        let line = line.map_err(|err| Error::new(lineno, ErrorKind::Io(err)))?;
+        let rest = match text_field.take() {
+            Some((start, mut text)) => match line.strip_prefix(';') {
+                Some(rest) => {
+                    tokens.push((start, Token::Value(Value::Text(text))));
+                    rest
+                }
+                None => {
+                    text.push('\n');
+                    text.push_str(&line);
+                    text_field = Some((start, text));
+                    continue;
+                }
+            },
+            None => {
+                if let Some(rest) = line.strip_prefix(';') {
+                    text_field = Some((lineno, rest.to_string()));
+                    continue;
+                }
+                &line[..]
+            }
+        };
+        tokenize_line(rest, lineno, &mut tokens)?;
+    }
+
+    if let Some((start, _)) = text_field {
+        return Err(Error::new(start, ErrorKind::UnterminatedTextField));
+    }
+    Ok(tokens)
+}
+
+fn tokenize_line(line: &str, lineno: usize, tokens: &mut Vec<(usize, Token)>) -> Result<(), Error> {
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        if rest.starts_with('#') {
+            break;
+        }
+        let (token, remainder) = if rest.starts_with('\'') || rest.starts_with('"') {
+            let (value, remainder) =
+                quoted(rest).ok_or_else(|| Error::new(lineno, ErrorKind::UnterminatedQuote))?;
+            (Token::Value(Value::Text(value.to_string())), remainder)
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let (word, remainder) = rest.split_at(end);
+            (classify(word), remainder)
+        };
+        tokens.push((lineno, token));
+        rest = remainder.trim_start();
+    }
+    Ok(())
+}
+
+/// Returns the content of a quoted value at the beginning of `text` and the
+/// text following it. The closing quote must be followed by whitespace or
+/// the end of the line.
+fn quoted(text: &str) -> Option<(&str, &str)> {
+    let quote = text.chars().next()?;
+    let body = &text[1..];
+    let mut search = 0;
+    while let Some(offset) = body[search..].find(quote) {
+        let end = search + offset;
+        let after = &body[end + 1..];
+        if after.is_empty() || after.starts_with(char::is_whitespace) {
+            return Some((&body[..end], after));
+        }
+        search = end + 1;
+    }
+    None
+}
+
+fn classify(word: &str) -> Token {
+    let lower = word.to_ascii_lowercase();
+    if lower.starts_with("data_") {
+        Token::DataBlock(word[5..].to_string())
+    } else if lower == "loop_" {
+        Token::Loop
+    } else if word.starts_with('_') {
+        Token::Tag(word.to_string())
+    } else if word == "?" {
+        Token::Value(Value::Unknown)
+    } else if word == "." {
+        Token::Value(Value::Inapplicable)
+    } else {
+        Token::Value(Value::Text(word.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> Token {
+        Token::Value(Value::Text(value.to_string()))
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let input = "\
+data_1ABC # comment
+_cell.length_a 10.5
+loop_
+_a.b _a.c
+'it's quoted' \"double # not a comment\"
+? .
+;first line
+second line
+;
+";
+        let tokens = tokenize(input.as_bytes()).unwrap();
+        let expected = vec![
+            (1, Token::DataBlock("1ABC".to_string())),
+            (2, Token::Tag("_cell.length_a".to_string())),
+            (2, text("10.5")),
+            (3, Token::Loop),
+            (4, Token::Tag("_a.b".to_string())),
+            (4, Token::Tag("_a.c".to_string())),
+            (5, text("it's quoted")),
+            (5, text("double # not a comment")),
+            (6, Token::Value(Value::Unknown)),
+            (6, Token::Value(Value::Inapplicable)),
+            (7, text("first line\nsecond line")),
+        ];
+        assert_eq!(expected, tokens);
+    }
+
+    #[test]
+    fn test_quoted_special_values() {
+        let tokens = tokenize("'?' '.' ''\n".as_bytes()).unwrap();
+        assert_eq!(vec![(1, text("?")), (1, text(".")), (1, text(""))], tokens);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = tokenize("data_x\n_a 'unterminated\n".as_bytes()).unwrap_err();
+        assert_eq!(2, err.line());
+        match *err.kind() {
+            ErrorKind::UnterminatedQuote => {}
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+
+        let err = tokenize("data_x\n_a\n;text\n".as_bytes()).unwrap_err();
+        assert_eq!(3, err.line());
+        match *err.kind() {
+            ErrorKind::UnterminatedTextField => {}
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! Reading and writing CIF files, and structures in the PDBx/mmCIF format.
+
+use std::error;
+use std::fmt;
+use std::io;
+
+mod block;
+mod lexer;
+mod reader;
+mod writer;
+
+pub use self::block::{parse, parse_file, DataBlock, Table, Value};
+pub use self::reader::{read, read_file, structure_from_block};
+pub use self::writer::{write, write_file};
+
+/// An error occurred while reading a CIF file.
+#[derive(Debug)]
+pub struct Error {
+    line: usize,
+    kind: ErrorKind,
+}
+
+/// The reason why a CIF file could not be read.
+#[derive(Debug)]
+pub enum ErrorKind {
+    Io(io::Error),
+    /// A quoted value is not closed on its line.
+    UnterminatedQuote,
+    /// A text field starting with `;` is not closed.
+    UnterminatedTextField,
+    /// Data appear before any `data_` block header, or there is no data
+    /// block at all.
+    MissingDataBlock,
+    /// A `loop_` is not followed by any tag.
+    EmptyLoop,
+    /// The number of values in a loop is not a multiple of the number of
+    /// its tags.
+    IncompleteLoop,
+    /// A tag of a non-looped item is not followed by a value.
+    MissingValue(String),
+    /// A value appears without a preceding tag.
+    UnexpectedValue,
+    /// An item necessary to build a structure is missing.
+    MissingItem(String),
+    /// A value of an item could not be parsed.
+    InvalidValue(String, String),
+}
+
+impl Error {
+    pub(crate) fn new(line: usize, kind: ErrorKind) -> Self {
+        Error { line, kind }
+    }
+
+    /// Returns the line number, starting from one, where the error occurred.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match self.kind {
+            ErrorKind::Io(ref err) => write!(f, "{}", err),
+            ErrorKind::UnterminatedQuote => write!(f, "unterminated quoted value"),
+            ErrorKind::UnterminatedTextField => write!(f, "unterminated text field"),
+            ErrorKind::MissingDataBlock => write!(f, "missing data block"),
+            ErrorKind::EmptyLoop => write!(f, "loop without tags"),
+            ErrorKind::IncompleteLoop => write!(f, "incomplete loop packet"),
+            ErrorKind::MissingValue(ref tag) => write!(f, "missing value of {}", tag),
+            ErrorKind::UnexpectedValue => write!(f, "value without tag"),
+            ErrorKind::MissingItem(ref tag) => write!(f, "missing {}", tag),
+            ErrorKind::InvalidValue(ref tag, ref value) => {
+                write!(f, "invalid {}: {:?}", tag, value)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.kind {
+            ErrorKind::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
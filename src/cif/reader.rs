@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::str::FromStr;
+
+use super::{parse, DataBlock, Error, ErrorKind, Table, Value};
+use atom::Atom;
+use structure::{Model, Structure};
+use Vector3d;
+
+/// Reads a structure from the `_atom_site` category of the first data
+/// block of PDBx/mmCIF formatted text.
+///
+/// See `structure_from_block` for how the items are interpreted.
+///
+/// # Example
+///
+/// ```
+/// let text = "\
+/// data_example
+/// loop_
+/// _atom_site.group_PDB
+/// _atom_site.id
+/// _atom_site.type_symbol
+/// _atom_site.label_atom_id
+/// _atom_site.label_comp_id
+/// _atom_site.label_asym_id
+/// _atom_site.label_seq_id
+/// _atom_site.Cartn_x
+/// _atom_site.Cartn_y
+/// _atom_site.Cartn_z
+/// ATOM 1 N N  ALA A 1 11.104 6.134 -6.504
+/// ATOM 2 C CA ALA A 1 11.639 6.071 -5.147
+/// ";
+/// let structure = biost::cif::read(text.as_bytes()).unwrap();
+/// let atoms = &structure.models[0].atoms;
+/// assert_eq!(2, atoms.len());
+/// assert_eq!("CA", atoms[1].name);
+/// assert_eq!(11.639, atoms[1].position.x);
+/// ```
+pub fn read<R: BufRead>(reader: R) -> Result<Structure, Error> {
+    let blocks = parse(reader)?;
+    match blocks.first() {
+        Some(block) => structure_from_block(block),
+        None => Err(Error::new(0, ErrorKind::MissingDataBlock)),
+    }
+}
+
+/// Reads a structure from a PDBx/mmCIF file.
+///
+/// An error in opening the file is reported at line zero.
+pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Structure, Error> {
+    let file = File::open(path).map_err(|err| Error::new(0, ErrorKind::Io(err)))?;
+    read(BufReader::new(file))
+}
+
+/// Builds a structure from the `_atom_site` category of a data block.
+///
+/// The author-defined `auth_*` items are preferred over the corresponding
+/// `label_*` items, so that names and numbers agree with those of PDB
+/// files. Atoms are split into models by `pdbx_PDB_model_num`, in the order
+/// of the first appearance of each number, even if the rows of a model are
+/// not contiguous. A chain end is placed wherever `label_asym_id` changes
+/// within a model, so that waters and ligands sharing the author chain
+/// identifier of a polymer are split from it as by `TER` records of PDB
+/// files. Bonds are not read.
+pub fn structure_from_block(block: &DataBlock) -> Result<Structure, Error> {
+    let table = block
+        .table("_atom_site")
+        .ok_or_else(|| Error::new(0, ErrorKind::MissingItem("_atom_site".to_string())))?;
+    let mut structure = Structure::default();
+    // The indices of models by their numbers.
+    let mut models = HashMap::new();
+    // The label_asym_id of the last atom of each model.
+    let mut labels = Vec::new();
+    for row in 0..table.rows.len() {
+        let line = table.lines[row];
+        let site = Site { table, row };
+        let model_num = site
+            .parse("_atom_site.pdbx_PDB_model_num")
+            .map_err(|kind| Error::new(line, kind))?
+            .unwrap_or(1);
+        let atom = site.atom().map_err(|kind| Error::new(line, kind))?;
+        let label = site.text(&["_atom_site.label_asym_id"]);
+        let index = match models.get(&model_num) {
+            Some(&index) => index,
+            None => {
+                structure.models.push(Model::new(model_num));
+                labels.push(label);
+                models.insert(model_num, structure.models.len() - 1);
+                structure.models.len() - 1
+            }
+        };
+        let model = &mut structure.models[index];
+        if label != labels[index] {
+            if !model.atoms.is_empty() {
+                model.chain_ends.push(model.atoms.len());
+            }
+            labels[index] = label;
+        }
+        model.atoms.push(atom);
+    }
+    Ok(structure)
+}
+
+/// A row of the `_atom_site` table.
+struct Site<'a> {
+    table: &'a Table,
+    row: usize,
+}
+
+impl<'a> Site<'a> {
+    fn atom(&self) -> Result<Atom, ErrorKind> {
+        let x = self.required("_atom_site.Cartn_x")?;
+        let y = self.required("_atom_site.Cartn_y")?;
+        let z = self.required("_atom_site.Cartn_z")?;
+        let name = self.text(&["_atom_site.auth_atom_id", "_atom_site.label_atom_id"]);
+        let mut atom = Atom::new(
+            name.ok_or_else(|| ErrorKind::MissingItem("_atom_site.label_atom_id".to_string()))?,
+            Vector3d::new(x, y, z),
+        );
+        atom.serial = self.required("_atom_site.id")?;
+        atom.alt_loc = self
+            .text(&["_atom_site.label_alt_id"])
+            .and_then(|alt_id| alt_id.chars().next());
+        atom.res_name = self
+            .text(&["_atom_site.auth_comp_id", "_atom_site.label_comp_id"])
+            .unwrap_or("")
+            .to_string();
+        atom.chain_id = self
+            .text(&["_atom_site.auth_asym_id", "_atom_site.label_asym_id"])
+            .unwrap_or("")
+            .to_string();
+        atom.res_seq = match self.parse("_atom_site.auth_seq_id")? {
+            Some(res_seq) => res_seq,
+            None => self.parse("_atom_site.label_seq_id")?.unwrap_or(0),
+        };
+        atom.i_code = self
+            .text(&["_atom_site.pdbx_PDB_ins_code"])
+            .and_then(|i_code| i_code.chars().next());
+        atom.occupancy = self.parse("_atom_site.occupancy")?.unwrap_or(1.0);
+        atom.temp_factor = self.parse("_atom_site.B_iso_or_equiv")?.unwrap_or(0.0);
+        atom.element = self
+            .text(&["_atom_site.type_symbol"])
+            .unwrap_or("")
+            .to_string();
+        atom.charge = self.parse("_atom_site.pdbx_formal_charge")?.unwrap_or(0);
+        atom.hetero = self
+            .text(&["_atom_site.group_PDB"])
+            .is_some_and(|group| group.eq_ignore_ascii_case("HETATM"));
+        Ok(atom)
+    }
+
+    /// Returns the text of the first of `tags` with a known value.
+    fn text(&self, tags: &[&str]) -> Option<&'a str> {
+        tags.iter()
+            .filter_map(|tag| self.table.get(self.row, tag))
+            .filter_map(Value::as_str)
+            .next()
+    }
+
+    /// Parses a value, ignoring a standard uncertainty in parentheses such
+    /// as `1.234(5)`. Returns `None` for a missing, unknown or inapplicable
+    /// value.
+    fn parse<T: FromStr>(&self, tag: &str) -> Result<Option<T>, ErrorKind> {
+        let text = match self.text(&[tag]) {
+            Some(text) => text,
+            None => return Ok(None),
+        };
+        let number = text.split('(').next().unwrap_or(text);
+        number
+            .parse()
+            .map(Some)
+            .map_err(|_| ErrorKind::InvalidValue(tag.to_string(), text.to_string()))
+    }
+
+    fn required<T: FromStr>(&self, tag: &str) -> Result<T, ErrorKind> {
+        self.parse(tag)?
+            .ok_or_else(|| ErrorKind::MissingItem(tag.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "\
+data_1ABC
+#
+_entry.id 1ABC
+#
+loop_
+_atom_site.group_PDB
+_atom_site.id
+_atom_site.type_symbol
+_atom_site.label_atom_id
+_atom_site.label_alt_id
+_atom_site.label_comp_id
+_atom_site.label_asym_id
+_atom_site.label_entity_id
+_atom_site.label_seq_id
+_atom_site.pdbx_PDB_ins_code
+_atom_site.Cartn_x
+_atom_site.Cartn_y
+_atom_site.Cartn_z
+_atom_site.occupancy
+_atom_site.B_iso_or_equiv
+_atom_site.pdbx_formal_charge
+_atom_site.auth_seq_id
+_atom_site.auth_comp_id
+_atom_site.auth_asym_id
+_atom_site.auth_atom_id
+_atom_site.pdbx_PDB_model_num
+ATOM   1 N  N     . THR A  1 1 ? 17.047 14.099 3.625 1.00 13.79 ? 1   THR A N     1
+ATOM   2 C  CA    A THR A  1 1 ? 16.967 12.784 4.338 0.50 10.80 ? 1   THR A CA    1
+ATOM   3 O  \"O5'\" . DA  AA 2 1 B 1.0    2.0    3.0   1.00 0.0   ? 10  DA  AA \"O5'\" 1
+HETATM 4 FE FE    . HEM C  3 . ? 10.000 -1.500 0.250 1.00 20.00 3 200 HEM B FE    1
+ATOM   5 N  N     . THR A  1 1 ? 17.000 14.000 3.000 1.00 13.79 ? 1   THR A N     2
+";
+
+    #[test]
+    fn test_read() {
+        let structure = read(INPUT.as_bytes()).unwrap();
+        assert_eq!(2, structure.models.len());
+        assert_eq!(1, structure.models[0].serial);
+        assert_eq!(2, structure.models[1].serial);
+        assert_eq!(4, structure.models[0].atoms.len());
+
+        let atom = &structure.models[0].atoms[1];
+        assert_eq!(2, atom.serial);
+        assert_eq!("CA", atom.name);
+        assert_eq!(Some('A'), atom.alt_loc);
+        assert_eq!("THR", atom.res_name);
+        assert_eq!("A", atom.chain_id);
+        assert_eq!(1, atom.res_seq);
+        assert_eq!(None, atom.i_code);
+        assert_eq!(Vector3d::new(16.967, 12.784, 4.338), atom.position);
+        assert_eq!(0.5, atom.occupancy);
+        assert_eq!(10.8, atom.temp_factor);
+        assert_eq!("C", atom.element);
+        assert_eq!(0, atom.charge);
+        assert!(!atom.hetero);
+
+        let atom = &structure.models[0].atoms[2];
+        assert_eq!("O5'", atom.name);
+        assert_eq!("AA", atom.chain_id);
+        assert_eq!(10, atom.res_seq);
+        assert_eq!(Some('B'), atom.i_code);
+
+        // auth_asym_id takes precedence over label_asym_id
+        let iron = &structure.models[0].atoms[3];
+        assert!(iron.hetero);
+        assert_eq!("B", iron.chain_id);
+        assert_eq!(200, iron.res_seq);
+        assert_eq!(3, iron.charge);
+
+        assert_eq!(17.0, structure.models[1].atoms[0].position.x);
+        assert_eq!(vec![2, 3], structure.models[0].chain_ends);
+        assert!(structure.models[1].chain_ends.is_empty());
+    }
+
+    #[test]
+    fn test_chain_ends() {
+        let input = "\
+data_x
+loop_
+_atom_site.group_PDB
+_atom_site.id
+_atom_site.label_atom_id
+_atom_site.label_comp_id
+_atom_site.label_asym_id
+_atom_site.Cartn_x
+_atom_site.Cartn_y
+_atom_site.Cartn_z
+_atom_site.auth_seq_id
+_atom_site.auth_asym_id
+ATOM   1 N  ALA A 0.0 0.0 0.0 1   A
+ATOM   2 CA ALA A 1.0 0.0 0.0 1   A
+HETATM 3 C1 NAG B 2.0 0.0 0.0 101 A
+HETATM 4 O  HOH C 3.0 0.0 0.0 201 A
+HETATM 5 O  HOH C 4.0 0.0 0.0 202 A
+";
+        let structure = read(input.as_bytes()).unwrap();
+        let model = &structure.models[0];
+        assert_eq!(vec![2, 3], model.chain_ends);
+        let chains: Vec<usize> = model.chains().map(|chain| chain.atoms().len()).collect();
+        assert_eq!(vec![2, 1, 2], chains);
+    }
+
+    #[test]
+    fn test_errors() {
+        let input = INPUT.replace("16.967", "16.9x7");
+        let err = read(input.as_bytes()).unwrap_err();
+        assert_eq!(28, err.line());
+        match *err.kind() {
+            ErrorKind::InvalidValue(ref tag, ref value) => {
+                assert_eq!("_atom_site.Cartn_x", tag);
+                assert_eq!("16.9x7", value);
+            }
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+
+        let err = read("data_x\n_entry.id x\n".as_bytes()).unwrap_err();
+        match *err.kind() {
+            ErrorKind::MissingItem(ref tag) => assert_eq!("_atom_site", tag),
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+
+        let err = read("".as_bytes()).unwrap_err();
+        match *err.kind() {
+            ErrorKind::MissingDataBlock => {}
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_interleaved_models() {
+        let input = "\
+data_x
+loop_
+_atom_site.id
+_atom_site.label_atom_id
+_atom_site.Cartn_x
+_atom_site.Cartn_y
+_atom_site.Cartn_z
+_atom_site.pdbx_PDB_model_num
+1 N  1.0 0.0 0.0 2
+2 N  2.0 0.0 0.0 1
+3 CA 3.0 0.0 0.0 2
+4 CA 4.0 0.0 0.0 1
+";
+        let structure = read(input.as_bytes()).unwrap();
+        let models: Vec<(i32, Vec<u32>)> = structure
+            .models
+            .iter()
+            .map(|model| {
+                (
+                    model.serial,
+                    model.atoms.iter().map(|atom| atom.serial).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(vec![(2, vec![1, 3]), (1, vec![2, 4])], models);
+    }
+
+    #[test]
+    fn test_uncertainty() {
+        let input = "\
+data_x
+_atom_site.id 1
+_atom_site.label_atom_id C1
+_atom_site.Cartn_x 1.25(3)
+_atom_site.Cartn_y 2.5
+_atom_site.Cartn_z -3
+";
+        let structure = read(input.as_bytes()).unwrap();
+        let atom = &structure.models[0].atoms[0];
+        assert_eq!(Vector3d::new(1.25, 2.5, -3.0), atom.position);
+        assert_eq!(0, atom.res_seq);
+    }
+}
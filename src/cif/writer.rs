@@ -0,0 +1,288 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use atom::Atom;
+use structure::Structure;
+
+const ATOM_SITE_TAGS: [&str; 21] = [
+    "group_PDB",
+    "id",
+    "type_symbol",
+    "label_atom_id",
+    "label_alt_id",
+    "label_comp_id",
+    "label_asym_id",
+    "label_seq_id",
+    "pdbx_PDB_ins_code",
+    "Cartn_x",
+    "Cartn_y",
+    "Cartn_z",
+    "occupancy",
+    "B_iso_or_equiv",
+    "pdbx_formal_charge",
+    "auth_seq_id",
+    "auth_comp_id",
+    "auth_asym_id",
+    "auth_atom_id",
+    "pdbx_PDB_model_num",
+    "label_entity_id",
+];
+
+/// Writes a structure as a PDBx/mmCIF data block with given name.
+///
+/// All atoms of all models are written to a single `_atom_site` loop.
+/// Unlike the PDB format, there is no limit on the number of atoms or on
+/// the length of chain identifiers. The chain identifier is written as
+/// both `label_asym_id` and `auth_asym_id`, so chain ends other than those
+/// between different chains are not written. Bonds are not written either,
+/// and `label_seq_id` and `label_entity_id` are inapplicable and unknown
+/// respectively, since the residue numbers of atoms are those of authors.
+///
+/// Returns an error of `InvalidInput` if the name of the data block is
+/// empty or contains whitespace, or if a value contains a line starting
+/// with `;`, which cannot be written in CIF.
+///
+/// # Example
+///
+/// ```
+/// use biost::{Atom, Model, Structure, Vector3d};
+/// let mut model = Model::new(1);
+/// let mut atom = Atom::new("CA", Vector3d::new(1.0, 2.0, 3.0));
+/// atom.chain_id = "AAA".to_string();
+/// model.atoms.push(atom);
+/// let structure = Structure { models: vec![model], bonds: vec![] };
+///
+/// let mut buffer = Vec::new();
+/// biost::cif::write(&mut buffer, &structure, "example").unwrap();
+/// let read = biost::cif::read(&buffer[..]).unwrap();
+/// assert_eq!("AAA", read.models[0].atoms[0].chain_id);
+/// ```
+pub fn write<W: Write>(mut writer: W, structure: &Structure, name: &str) -> io::Result<()> {
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid data block name {:?}", name),
+        ));
+    }
+    writeln!(writer, "data_{}", name)?;
+    writeln!(writer, "#")?;
+    writeln!(writer, "loop_")?;
+    for tag in ATOM_SITE_TAGS.iter() {
+        writeln!(writer, "_atom_site.{}", tag)?;
+    }
+    for model in structure.models.iter() {
+        for atom in model.atoms.iter() {
+            write_atom_site(&mut writer, atom, model.serial)?;
+        }
+    }
+    writeln!(writer, "#")
+}
+
+/// Writes a structure to a PDBx/mmCIF file.
+pub fn write_file<P: AsRef<Path>>(path: P, structure: &Structure, name: &str) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write(&mut writer, structure, name)?;
+    writer.flush()
+}
+
+fn write_atom_site<W: Write>(writer: &mut W, atom: &Atom, model: i32) -> io::Result<()> {
+    let name = quote(&atom.name)?;
+    let res_name = quote(&atom.res_name)?;
+    let chain_id = quote(&atom.chain_id)?;
+    let optional = |c: Option<char>, missing: &str| match c {
+        Some(c) => quote(&c.to_string()),
+        None => Ok(missing.to_string()),
+    };
+    writeln!(
+        writer,
+        "{} {} {} {} {} {} {} . {} {:.3} {:.3} {:.3} {:.2} {:.2} {} {} {} {} {} {} ?",
+        if atom.hetero { "HETATM" } else { "ATOM" },
+        atom.serial,
+        quote(&atom.element)?,
+        name,
+        optional(atom.alt_loc, ".")?,
+        res_name,
+        chain_id,
+        optional(atom.i_code, "?")?,
+        atom.position.x,
+        atom.position.y,
+        atom.position.z,
+        atom.occupancy,
+        atom.temp_factor,
+        atom.charge,
+        atom.res_seq,
+        res_name,
+        chain_id,
+        name,
+        model,
+    )
+}
+
+/// Returns a value formatted as a CIF token, quoting it if necessary.
+/// Empty values are written as unknown.
+///
+/// Returns an error of `InvalidInput` if the value contains a line
+/// starting with `;`, which would end a text field early.
+fn quote(value: &str) -> io::Result<String> {
+    if value.is_empty() {
+        return Ok("?".to_string());
+    }
+    if value.contains("\n;") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("value with a line starting with ';': {:?}", value),
+        ));
+    }
+    let lower = value.to_ascii_lowercase();
+    let reserved = ["data_", "save_", "loop_", "global_", "stop_"]
+        .iter()
+        .any(|word| lower.starts_with(word));
+    let needs_quote = reserved
+        || value == "?"
+        || value == "."
+        || value.starts_with(|c| "_#$'\"[];".contains(c))
+        || value.contains(char::is_whitespace);
+    Ok(if value.contains('\n') {
+        format!("\n;{}\n;", value)
+    } else if !needs_quote {
+        value.to_string()
+    } else if !value.contains('\'') {
+        format!("'{}'", value)
+    } else if !closes(value, '"') {
+        format!("\"{}\"", value)
+    } else if !closes(value, '\'') {
+        format!("'{}'", value)
+    } else {
+        format!("\n;{}\n;", value)
+    })
+}
+
+/// Returns whether `quote` followed by whitespace occurs in `value`, which
+/// would end a value delimited by `quote` early.
+fn closes(value: &str, quote: char) -> bool {
+    value
+        .char_indices()
+        .any(|(i, c)| c == quote && value[i + 1..].starts_with(char::is_whitespace))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::read;
+    use super::*;
+    use structure::Model;
+    use Vector3d;
+
+    fn atom(serial: u32, chain_id: &str) -> Atom {
+        let mut atom = Atom::new("CA", Vector3d::new(-1.5, 20.25, 300.125));
+        atom.serial = serial;
+        atom.res_name = "ALA".to_string();
+        atom.chain_id = chain_id.to_string();
+        atom.res_seq = 10;
+        atom.element = "C".to_string();
+        atom
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!("CA", quote("CA").unwrap());
+        assert_eq!("O5'", quote("O5'").unwrap());
+        assert_eq!("?", quote("").unwrap());
+        assert_eq!("'?'", quote("?").unwrap());
+        assert_eq!("'.'", quote(".").unwrap());
+        assert_eq!("'_a'", quote("_a").unwrap());
+        assert_eq!("'data_x'", quote("data_x").unwrap());
+        assert_eq!("'a b'", quote("a b").unwrap());
+        assert_eq!("\"it' s\"", quote("it' s").unwrap());
+        assert_eq!("\"'a\"", quote("'a").unwrap());
+        assert_eq!("\n;a\nb\n;", quote("a\nb").unwrap());
+        assert_eq!("'a'b\" c'", quote("a'b\" c").unwrap());
+        assert_eq!("\n;a' b\" c\n;", quote("a' b\" c").unwrap());
+        assert_eq!("\n;;a\nb\n;", quote(";a\nb").unwrap());
+        let err = quote("a\n;b").unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
+
+    #[test]
+    fn test_round_trip_quotes() {
+        let mut model = Model::new(1);
+        for (serial, name) in ["a'b\" c", "a' b\" c", "x'", "it' s"].iter().enumerate() {
+            let mut quoted = atom(serial as u32 + 1, "A");
+            quoted.res_name = name.to_string();
+            model.atoms.push(quoted);
+        }
+        let structure = Structure {
+            models: vec![model],
+            bonds: vec![],
+        };
+
+        let mut buffer = Vec::new();
+        write(&mut buffer, &structure, "quotes").unwrap();
+        let read = read(&buffer[..]).unwrap();
+        assert_eq!(structure, read);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut model1 = Model::new(1);
+        model1.atoms.push(atom(1, "A"));
+        let mut special = atom(2, "LONG");
+        special.name = "O5'".to_string();
+        special.res_name = "'DA".to_string();
+        special.alt_loc = Some('B');
+        special.i_code = Some('A');
+        special.occupancy = 0.5;
+        special.temp_factor = 12.25;
+        special.charge = -1;
+        special.hetero = true;
+        model1.atoms.push(special);
+        // The change of the chain is read back as a chain end.
+        model1.chain_ends.push(1);
+        let mut model2 = Model::new(2);
+        model2.atoms.push(atom(1, "A"));
+        let structure = Structure {
+            models: vec![model1, model2],
+            bonds: vec![],
+        };
+
+        let mut buffer = Vec::new();
+        write(&mut buffer, &structure, "test").unwrap();
+        let read = read(&buffer[..]).unwrap();
+        assert_eq!(structure, read);
+    }
+
+    #[test]
+    fn test_many_atoms() {
+        let mut model = Model::new(1);
+        for serial in 1..100_002 {
+            model.atoms.push(atom(serial, "A"));
+        }
+        let structure = Structure {
+            models: vec![model],
+            bonds: vec![],
+        };
+        let mut buffer = Vec::new();
+        write(&mut buffer, &structure, "big").unwrap();
+        let read = read(&buffer[..]).unwrap();
+        assert_eq!(100_001, read.models[0].atoms.len());
+        assert_eq!(100_001, read.models[0].atoms[100_000].serial);
+    }
+
+    #[test]
+    fn test_invalid_name() {
+        let structure = Structure::default();
+        let err = write(Vec::new(), &structure, "a b").unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+
+        let mut model = Model::new(1);
+        let mut invalid = atom(1, "A");
+        invalid.res_name = "a\n;b".to_string();
+        model.atoms.push(invalid);
+        let structure = Structure {
+            models: vec![model],
+            bonds: vec![],
+        };
+        let err = write(Vec::new(), &structure, "x").unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
+}
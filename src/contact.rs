@@ -0,0 +1,555 @@
+//! Residue contacts and distance matrices.
+//!
+//! The distance between residues is measured between their CA atoms, their
+//! CB atoms (CA for glycine) or their closest heavy atoms. Distances and
+//! contacts of all pairs of residues are given densely by `DistanceMatrix`
+//! and `ContactMap`, and those within a cutoff sparsely by `contacts`.
+
+use std::collections::BTreeMap;
+use std::ops;
+
+use element::Element;
+use neighbor::Grid;
+use structure::{Chain, Model, Residue};
+use Vector3d;
+
+/// The ways to measure the distance between residues.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Measure {
+    /// The distance between CA atoms.
+    Alpha,
+    /// The distance between CB atoms, or CA atoms for glycines.
+    Beta,
+    /// The minimum distance between heavy atoms.
+    ClosestHeavyAtom,
+}
+
+impl Measure {
+    /// Returns the position representing a residue if the measure is
+    /// between single atoms, and `None` if the residue lacks the atom.
+    fn representative(&self, residue: &Residue) -> Option<Vector3d> {
+        let name = match self {
+            Measure::Beta if residue.name() != "GLY" => "CB",
+            _ => "CA",
+        };
+        residue.atom(name).map(|atom| atom.position)
+    }
+
+    /// Returns the distance between residues, or `None` if either lacks the
+    /// atoms to measure.
+    fn distance(&self, first: &Residue, second: &Residue) -> Option<f32> {
+        match self {
+            Measure::ClosestHeavyAtom => {
+                let heavy = |residue: &Residue| -> Vec<Vector3d> {
+                    residue
+                        .atoms()
+                        .filter(|atom| !Element::is_hydrogen_atom(atom))
+                        .map(|atom| atom.position)
+                        .collect()
+                };
+                let second = heavy(second);
+                heavy(first)
+                    .iter()
+                    .flat_map(|a| second.iter().map(move |b| a.distance_squared(b)))
+                    .min_by(|a, b| a.total_cmp(b))
+                    .map(f32::sqrt)
+            }
+            _ => {
+                let a = self.representative(first)?;
+                let b = self.representative(second)?;
+                Some(a.distance(&b))
+            }
+        }
+    }
+}
+
+/// A dense symmetric matrix of the distances between residues.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistanceMatrix {
+    len: usize,
+    /// The distances in row-major order, `NaN` for undefined ones.
+    values: Vec<f32>,
+}
+
+impl DistanceMatrix {
+    /// Computes the distances between all pairs of residues. Distances
+    /// involving residues without the atoms to measure are undefined.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::contact::{DistanceMatrix, Measure};
+    /// let text = "\
+    /// ATOM      1  CA  GLY A   1       0.000   0.000   0.000  1.00  0.00           C
+    /// ATOM      2  CA  ALA A   2       3.800   0.000   0.000  1.00  0.00           C
+    /// ATOM      3  CB  ALA A   2       3.800   1.500   0.000  1.00  0.00           C
+    /// ATOM      4  N   SER A   3       7.600   0.000   0.000  1.00  0.00           N
+    /// ";
+    /// let structure = biost::pdb::read(text.as_bytes()).unwrap();
+    /// let residues: Vec<_> = structure.models[0].residues().collect();
+    ///
+    /// let matrix = DistanceMatrix::new(&residues, Measure::Alpha);
+    /// assert_eq!(3, matrix.len());
+    /// assert_eq!(Some(3.8), matrix.get(0, 1));
+    /// assert_eq!(None, matrix.get(0, 2));
+    /// let matrix = DistanceMatrix::new(&residues, Measure::ClosestHeavyAtom);
+    /// assert_eq!(Some(3.8), matrix.get(1, 2));
+    /// ```
+    pub fn new(residues: &[Residue], measure: Measure) -> Self {
+        let len = residues.len();
+        let mut values = vec![f32::NAN; len * len];
+        for i in 0..len {
+            for j in i..len {
+                if let Some(distance) = measure.distance(&residues[i], &residues[j]) {
+                    values[i * len + j] = distance;
+                    values[j * len + i] = distance;
+                }
+            }
+        }
+        DistanceMatrix { len, values }
+    }
+
+    /// Returns the number of residues.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the distance between the `i`-th and `j`-th residues, or
+    /// `None` if it is undefined or either index is out of range.
+    pub fn get(&self, i: usize, j: usize) -> Option<f32> {
+        if i >= self.len || j >= self.len {
+            return None;
+        }
+        Some(self.values[i * self.len + j]).filter(|distance| !distance.is_nan())
+    }
+
+    /// Returns the distances in row-major order, `NaN` for undefined ones.
+    pub fn as_slice(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// Returns the rows of the matrix, `NaN` for undefined distances.
+    pub fn rows(&self) -> impl Iterator<Item = &[f32]> {
+        self.values.chunks(self.len.max(1))
+    }
+
+    /// Returns the map of residues within `cutoff` of each other. Residues
+    /// with undefined distances are not in contact.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::contact::{DistanceMatrix, Measure};
+    /// let text = "\
+    /// ATOM      1  CA  GLY A   1       0.000   0.000   0.000  1.00  0.00           C
+    /// ATOM      2  CA  GLY A   2       3.800   0.000   0.000  1.00  0.00           C
+    /// ATOM      3  CA  GLY A   3      12.000   0.000   0.000  1.00  0.00           C
+    /// ";
+    /// let structure = biost::pdb::read(text.as_bytes()).unwrap();
+    /// let residues: Vec<_> = structure.models[0].residues().collect();
+    /// let map = DistanceMatrix::new(&residues, Measure::Beta).contact_map(8.0);
+    /// assert!(map.get(0, 1) && !map.get(0, 2));
+    /// assert_eq!(vec![(0, 1)], map.pairs());
+    /// ```
+    pub fn contact_map(&self, cutoff: f32) -> ContactMap {
+        ContactMap {
+            len: self.len,
+            values: self
+                .values
+                .iter()
+                .map(|&distance| distance <= cutoff)
+                .collect(),
+        }
+    }
+}
+
+impl ops::Index<(usize, usize)> for DistanceMatrix {
+    type Output = f32;
+
+    /// Returns the distance between the `i`-th and `j`-th residues, `NaN`
+    /// if it is undefined.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of range.
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        assert!(i < self.len && j < self.len, "index out of range");
+        &self.values[i * self.len + j]
+    }
+}
+
+/// A dense symmetric boolean matrix of residues in contact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContactMap {
+    len: usize,
+    values: Vec<bool>,
+}
+
+impl ContactMap {
+    /// Returns the number of residues.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns whether the `i`-th and `j`-th residues are in contact, which
+    /// is `false` if either index is out of range.
+    pub fn get(&self, i: usize, j: usize) -> bool {
+        i < self.len && j < self.len && self.values[i * self.len + j]
+    }
+
+    /// Returns the contacts in row-major order.
+    pub fn as_slice(&self) -> &[bool] {
+        &self.values
+    }
+
+    /// Returns the pairs of different residues in contact, `(i, j)` with
+    /// `i < j` in lexicographic order.
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        (0..self.len)
+            .flat_map(|i| (i + 1..self.len).map(move |j| (i, j)))
+            .filter(|&(i, j)| self.values[i * self.len + j])
+            .collect()
+    }
+}
+
+/// Returns the pairs of different residues within `cutoff` of each other
+/// with their distances, `(i, j, distance)` with `i < j` in lexicographic
+/// order.
+///
+/// Unlike `DistanceMatrix`, only the pairs within the cutoff are computed,
+/// by searching the neighbors of atoms.
+///
+/// # Example
+///
+/// ```
+/// use biost::contact::{contacts, Measure};
+/// let text = "\
+/// ATOM      1  CA  GLY A   1       0.000   0.000   0.000  1.00  0.00           C
+/// ATOM      2  CA  ALA A   2       3.800   0.000   0.000  1.00  0.00           C
+/// ATOM      3  CB  ALA A   2       3.800   1.500   0.000  1.00  0.00           C
+/// ATOM      4  CA  GLY A   3      12.000   0.000   0.000  1.00  0.00           C
+/// ";
+/// let structure = biost::pdb::read(text.as_bytes()).unwrap();
+/// let residues: Vec<_> = structure.models[0].residues().collect();
+/// let pairs = contacts(&residues, Measure::Alpha, 8.0);
+/// assert_eq!(vec![(0, 1, 3.8)], pairs);
+/// ```
+pub fn contacts(residues: &[Residue], measure: Measure, cutoff: f32) -> Vec<(usize, usize, f32)> {
+    // The positions of atoms and the indices of their residues. Atoms at
+    // non-finite positions are left out of the grid, which would otherwise
+    // not be built at all.
+    let mut positions = Vec::new();
+    let mut owners = Vec::new();
+    let mut push = |position: Vector3d, owner: usize| {
+        if position.x.is_finite() && position.y.is_finite() && position.z.is_finite() {
+            positions.push(position);
+            owners.push(owner);
+        }
+    };
+    for (i, residue) in residues.iter().enumerate() {
+        match measure {
+            Measure::ClosestHeavyAtom => {
+                for atom in residue
+                    .atoms()
+                    .filter(|atom| !Element::is_hydrogen_atom(atom))
+                {
+                    push(atom.position, i);
+                }
+            }
+            _ => {
+                if let Some(position) = measure.representative(residue) {
+                    push(position, i);
+                }
+            }
+        }
+    }
+    let grid = match Grid::new(&positions, cutoff) {
+        Some(grid) => grid,
+        None => return Vec::new(),
+    };
+    let mut closest = BTreeMap::new();
+    for (a, b, distance) in grid.pairs(cutoff) {
+        let (i, j) = (owners[a].min(owners[b]), owners[a].max(owners[b]));
+        if i == j {
+            continue;
+        }
+        let current = closest.entry((i, j)).or_insert(distance);
+        if distance < *current {
+            *current = distance;
+        }
+    }
+    closest
+        .into_iter()
+        .map(|((i, j), distance)| (i, j, distance))
+        .collect()
+}
+
+/// The residues at the interface of two chains.
+#[derive(Debug, Clone)]
+pub struct Interface<'a> {
+    pub first: Chain<'a>,
+    pub second: Chain<'a>,
+    /// The residues of each chain with a heavy atom within the cutoff of
+    /// one of the other chain, in the order of the chain.
+    pub first_residues: Vec<Residue<'a>>,
+    pub second_residues: Vec<Residue<'a>>,
+}
+
+impl<'a> Interface<'a> {
+    /// Returns whether the chains have no residues in contact.
+    pub fn is_empty(&self) -> bool {
+        self.first_residues.is_empty()
+    }
+}
+
+/// Returns the residues of two chains with heavy atoms within `cutoff` of
+/// each other.
+///
+/// # Example
+///
+/// ```
+/// use biost::contact::interface;
+/// let text = "\
+/// ATOM      1  CA  GLY A   1       0.000   0.000   0.000  1.00  0.00           C
+/// ATOM      2  CA  GLY A   2       3.800   0.000   0.000  1.00  0.00           C
+/// ATOM      3  CA  GLY B   1       1.900   4.000   0.000  1.00  0.00           C
+/// ATOM      4  CA  GLY B   2       0.000  20.000   0.000  1.00  0.00           C
+/// ";
+/// let structure = biost::pdb::read(text.as_bytes()).unwrap();
+/// let model = &structure.models[0];
+/// let interface = interface(&model.chain("A").unwrap(), &model.chain("B").unwrap(), 5.0);
+/// let res_seqs = |residues: &[biost::Residue]| -> Vec<i32> {
+///     residues.iter().map(|residue| residue.res_seq()).collect()
+/// };
+/// assert_eq!(vec![1, 2], res_seqs(&interface.first_residues));
+/// assert_eq!(vec![1], res_seqs(&interface.second_residues));
+/// ```
+pub fn interface<'a>(first: &Chain<'a>, second: &Chain<'a>, cutoff: f32) -> Interface<'a> {
+    let first_residues: Vec<Residue<'a>> = first.residues().collect();
+    let second_residues: Vec<Residue<'a>> = second.residues().collect();
+    let split = first_residues.len();
+    let residues: Vec<Residue<'a>> = first_residues
+        .iter()
+        .chain(second_residues.iter())
+        .cloned()
+        .collect();
+    let mut in_first = vec![false; first_residues.len()];
+    let mut in_second = vec![false; second_residues.len()];
+    for (i, j, _) in contacts(&residues, Measure::ClosestHeavyAtom, cutoff) {
+        if i < split && j >= split {
+            in_first[i] = true;
+            in_second[j - split] = true;
+        }
+    }
+    let select = |residues: Vec<Residue<'a>>, selected: &[bool]| -> Vec<Residue<'a>> {
+        residues
+            .into_iter()
+            .zip(selected)
+            .filter(|&(_, &selected)| selected)
+            .map(|(residue, _)| residue)
+            .collect()
+    };
+    Interface {
+        first: first.clone(),
+        second: second.clone(),
+        first_residues: select(first_residues, &in_first),
+        second_residues: select(second_residues, &in_second),
+    }
+}
+
+/// Returns the non-empty interfaces between all pairs of chains of a model
+/// with different identifiers, e.g. not between a polymer and the waters
+/// listed after its `TER` record.
+///
+/// # Example
+///
+/// ```
+/// let text = "\
+/// ATOM      1  CA  GLY A   1       0.000   0.000   0.000  1.00  0.00           C
+/// ATOM      2  CA  GLY B   1       0.000   4.000   0.000  1.00  0.00           C
+/// ATOM      3  CA  GLY C   1       0.000  20.000   0.000  1.00  0.00           C
+/// ";
+/// let structure = biost::pdb::read(text.as_bytes()).unwrap();
+/// let interfaces = biost::contact::interfaces(&structure.models[0], 5.0);
+/// assert_eq!(1, interfaces.len());
+/// assert_eq!(("A", "B"), (interfaces[0].first.id(), interfaces[0].second.id()));
+/// ```
+pub fn interfaces(model: &Model, cutoff: f32) -> Vec<Interface<'_>> {
+    let chains: Vec<Chain> = model.chains().collect();
+    let mut interfaces = Vec::new();
+    for (i, first) in chains.iter().enumerate() {
+        for second in chains[i + 1..].iter() {
+            if first.id() == second.id() {
+                continue;
+            }
+            let interface = interface(first, second, cutoff);
+            if !interface.is_empty() {
+                interfaces.push(interface);
+            }
+        }
+    }
+    interfaces
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atom::Atom;
+
+    fn atom(name: &str, res_name: &str, chain_id: &str, res_seq: i32, x: f32, y: f32) -> Atom {
+        let mut atom = Atom::new(name, Vector3d::new(x, y, 0.0));
+        atom.res_name = res_name.to_string();
+        atom.chain_id = chain_id.to_string();
+        atom.res_seq = res_seq;
+        atom
+    }
+
+    /// Returns two chains of three residues each, A along the x axis and B
+    /// 6 Angstroms above it, with hydrogens pointing at each other.
+    fn model() -> Model {
+        let mut model = Model::new(1);
+        for (i, res_name) in ["GLY", "ALA", "SER"].iter().enumerate() {
+            let x = 3.8 * i as f32;
+            model
+                .atoms
+                .push(atom("CA", res_name, "A", i as i32 + 1, x, 0.0));
+            if *res_name != "GLY" {
+                model
+                    .atoms
+                    .push(atom("CB", res_name, "A", i as i32 + 1, x, 1.5));
+            }
+        }
+        model.atoms.push(atom("H", "SER", "A", 3, 7.6, 2.5));
+        model.chain_ends.push(model.atoms.len());
+        for i in 0..3 {
+            let x = 3.8 * i as f32;
+            model.atoms.push(atom("CA", "ALA", "B", i + 1, x, 6.0));
+            model.atoms.push(atom("CB", "ALA", "B", i + 1, x, 4.5));
+            if i == 0 {
+                model.atoms.push(atom("H", "ALA", "B", 1, 0.0, 3.5));
+            }
+        }
+        model
+    }
+
+    #[test]
+    fn test_distance_matrix() {
+        let model = model();
+        let residues: Vec<Residue> = model.residues().collect();
+        assert_eq!(6, residues.len());
+
+        let alpha = DistanceMatrix::new(&residues, Measure::Alpha);
+        assert_eq!(6, alpha.len());
+        assert_eq!(Some(0.0), alpha.get(4, 4));
+        assert!((alpha[(0, 2)] - 7.6).abs() < 1e-5);
+        assert_eq!(alpha.get(0, 3), alpha.get(3, 0));
+        assert_eq!(Some(6.0), alpha.get(0, 3));
+        assert_eq!(None, alpha.get(0, 6));
+
+        let beta = DistanceMatrix::new(&residues, Measure::Beta);
+        // The CA of the glycine and the CB of the alanine below it.
+        assert_eq!(Some(4.5), beta.get(0, 3));
+        assert_eq!(Some(3.0), beta.get(1, 4));
+
+        let closest = DistanceMatrix::new(&residues, Measure::ClosestHeavyAtom);
+        assert_eq!(Some(3.0), closest.get(1, 4));
+        assert_eq!(Some(0.0), closest.get(1, 1));
+        // Hydrogens are ignored.
+        assert_eq!(Some(3.0), closest.get(2, 5));
+
+        let rows: Vec<&[f32]> = beta.rows().collect();
+        assert_eq!(6, rows.len());
+        assert_eq!(beta.as_slice()[6..12], *rows[1]);
+    }
+
+    #[test]
+    fn test_distance_matrix_undefined() {
+        let mut model = Model::new(1);
+        model.atoms.push(atom("CA", "ALA", "A", 1, 0.0, 0.0));
+        model.atoms.push(atom("CA", "ALA", "A", 2, 3.8, 0.0));
+        model.atoms.push(atom("CB", "ALA", "A", 2, 3.8, 1.5));
+        let residues: Vec<Residue> = model.residues().collect();
+        let beta = DistanceMatrix::new(&residues, Measure::Beta);
+        assert_eq!(None, beta.get(0, 0));
+        assert_eq!(None, beta.get(0, 1));
+        assert_eq!(Some(0.0), beta.get(1, 1));
+        assert!(beta[(0, 1)].is_nan());
+        assert!(!beta.contact_map(100.0).get(0, 1));
+
+        let empty = DistanceMatrix::new(&[], Measure::Alpha);
+        assert!(empty.is_empty());
+        assert_eq!(0, empty.rows().count());
+        assert!(empty.contact_map(8.0).pairs().is_empty());
+    }
+
+    #[test]
+    fn test_contacts() {
+        let model = model();
+        let residues: Vec<Residue> = model.residues().collect();
+        for &measure in [Measure::Alpha, Measure::Beta, Measure::ClosestHeavyAtom].iter() {
+            for &cutoff in [3.0, 4.0, 5.0, 8.0].iter() {
+                let map = DistanceMatrix::new(&residues, measure).contact_map(cutoff);
+                let sparse = contacts(&residues, measure, cutoff);
+                let pairs: Vec<(usize, usize)> = sparse.iter().map(|&(i, j, _)| (i, j)).collect();
+                assert_eq!(map.pairs(), pairs, "{:?} {}", measure, cutoff);
+                let matrix = DistanceMatrix::new(&residues, measure);
+                for &(i, j, distance) in sparse.iter() {
+                    assert!((matrix[(i, j)] - distance).abs() < 1e-5);
+                }
+            }
+        }
+        assert_eq!(
+            vec![(1, 4, 3.0), (2, 5, 3.0)],
+            contacts(&residues, Measure::ClosestHeavyAtom, 3.0)
+        );
+        assert!(contacts(&residues, Measure::Alpha, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_contacts_non_finite_position() {
+        let mut model = Model::new(1);
+        model.atoms.push(atom("CA", "ALA", "A", 1, 0.0, 0.0));
+        model.atoms.push(atom("CA", "ALA", "A", 2, 3.8, 0.0));
+        model.atoms.push(atom("CA", "ALA", "A", 3, f32::NAN, 0.0));
+        let residues: Vec<Residue> = model.residues().collect();
+        let map = DistanceMatrix::new(&residues, Measure::Alpha).contact_map(8.0);
+        assert_eq!(vec![(0, 1)], map.pairs());
+        assert_eq!(vec![(0, 1, 3.8)], contacts(&residues, Measure::Alpha, 8.0));
+    }
+
+    #[test]
+    fn test_interfaces() {
+        let model = model();
+        let chains: Vec<Chain> = model.chains().collect();
+        let res_seqs = |residues: &[Residue]| -> Vec<i32> {
+            residues.iter().map(|residue| residue.res_seq()).collect()
+        };
+
+        let contact = interface(&chains[0], &chains[1], 3.0);
+        assert_eq!(vec![2, 3], res_seqs(&contact.first_residues));
+        assert_eq!(vec![2, 3], res_seqs(&contact.second_residues));
+        let contact = interface(&chains[0], &chains[1], 4.5);
+        assert_eq!(vec![1, 2, 3], res_seqs(&contact.first_residues));
+        assert!(interface(&chains[0], &chains[1], 2.0).is_empty());
+
+        let all = interfaces(&model, 3.0);
+        assert_eq!(1, all.len());
+        assert_eq!(("A", "B"), (all[0].first.id(), all[0].second.id()));
+        assert!(interfaces(&model, 2.0).is_empty());
+
+        // Chains with the same identifier are not paired.
+        let mut same = model.clone();
+        for atom in same.atoms.iter_mut() {
+            atom.chain_id = "A".to_string();
+        }
+        assert!(interfaces(&same, 3.0).is_empty());
+    }
+}
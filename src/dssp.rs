@@ -0,0 +1,726 @@
+//! Secondary structure assignment following DSSP.
+//!
+//! Hydrogen bonds between backbone N-H and C=O groups are identified by the
+//! electrostatic energy of Kabsch and Sander (1983), after placing the
+//! amide hydrogens, and the secondary structure of each residue is assigned
+//! from the patterns of the bonds as in DSSP 4, which prefers pi helices to
+//! alpha helices and assigns polyproline II helices to the residues left
+//! without any other structure, including turns and bends.
+
+use std::fmt;
+
+use neighbor::Grid;
+use structure::{Model, Residue};
+use torsion::dihedral;
+use Vector3d;
+
+/// The maximum distance in Angstroms between the C atom of a residue and
+/// the N atom of the next for them to be bonded.
+const MAX_PEPTIDE_BOND: f32 = 2.5;
+
+/// The maximum distance between CA atoms of residues to be hydrogen bonded.
+const MAX_CA_DISTANCE: f32 = 9.0;
+
+/// The product of the partial charges of the C=O and N-H groups, 0.42e and
+/// 0.20e, and the conversion factor 332 to kcal/mol.
+const COUPLING_CONSTANT: f32 = 27.888;
+
+/// The energy in kcal/mol below which a hydrogen bond exists.
+const MAX_HBOND_ENERGY: f32 = -0.5;
+
+/// The minimum energy, which is assigned to atoms too close to each other.
+const MIN_HBOND_ENERGY: f32 = -9.9;
+
+/// The distance between atoms below which the energy is `MIN_HBOND_ENERGY`.
+const MIN_DISTANCE: f32 = 0.5;
+
+/// The minimum angle in degrees between CA(i-2)-CA(i) and CA(i)-CA(i+2) of
+/// a bend.
+const MIN_BEND_ANGLE: f32 = 70.0;
+
+/// The minimum number of consecutive residues in a polyproline II helix,
+/// and the ranges of its phi and psi in degrees.
+const MIN_PP_STRETCH: usize = 3;
+const PP_PHI: (f32, f32) = (-75.0, 29.0);
+const PP_PSI: (f32, f32) = (145.0, 29.0);
+
+/// The secondary structures of DSSP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SecondaryStructure {
+    /// An alpha helix, `H`.
+    AlphaHelix,
+    /// An isolated beta bridge, `B`.
+    Bridge,
+    /// An extended strand in a beta ladder, `E`.
+    Strand,
+    /// A 3-10 helix, `G`.
+    Helix310,
+    /// A pi helix, `I`.
+    PiHelix,
+    /// A polyproline II helix, `P`.
+    PolyProline,
+    /// A hydrogen bonded turn, `T`.
+    Turn,
+    /// A bend, `S`.
+    Bend,
+    /// None of the above, written as a space.
+    Loop,
+}
+
+impl SecondaryStructure {
+    /// Returns the one-letter code used by DSSP.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::dssp::SecondaryStructure;
+    /// assert_eq!('H', SecondaryStructure::AlphaHelix.code());
+    /// assert_eq!(' ', SecondaryStructure::Loop.code());
+    /// ```
+    pub fn code(&self) -> char {
+        match self {
+            SecondaryStructure::AlphaHelix => 'H',
+            SecondaryStructure::Bridge => 'B',
+            SecondaryStructure::Strand => 'E',
+            SecondaryStructure::Helix310 => 'G',
+            SecondaryStructure::PiHelix => 'I',
+            SecondaryStructure::PolyProline => 'P',
+            SecondaryStructure::Turn => 'T',
+            SecondaryStructure::Bend => 'S',
+            SecondaryStructure::Loop => ' ',
+        }
+    }
+}
+
+impl fmt::Display for SecondaryStructure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// Returns the position of the amide hydrogen of a residue, which is 1
+/// Angstrom from N opposite to the C=O group of the previous residue.
+///
+/// # Example
+///
+/// ```
+/// use biost::dssp::hydrogen;
+/// use biost::Vector3d;
+/// let n = Vector3d::new(1.0, 1.0, 0.0);
+/// let c = Vector3d::new(0.0, 1.0, 0.0);
+/// let o = Vector3d::new(0.0, 2.2, 0.0);
+/// assert_eq!(Vector3d::new(1.0, 0.0, 0.0), hydrogen(&n, &c, &o));
+/// ```
+pub fn hydrogen(n: &Vector3d, previous_c: &Vector3d, previous_o: &Vector3d) -> Vector3d {
+    *n + (*previous_c - *previous_o).normalize()
+}
+
+/// Returns the electrostatic energy in kcal/mol of a hydrogen bond between
+/// the N-H group of a donor and the C=O group of an acceptor.
+///
+/// The energy is capped at -9.9 kcal/mol, and a bond exists if it is below
+/// -0.5 kcal/mol.
+///
+/// # Example
+///
+/// ```
+/// use biost::dssp::hbond_energy;
+/// use biost::Vector3d;
+/// let n = Vector3d::new(0.0, 0.0, 0.0);
+/// let h = Vector3d::new(1.0, 0.0, 0.0);
+/// let o = Vector3d::new(2.9, 0.0, 0.0);
+/// let c = Vector3d::new(4.13, 0.0, 0.0);
+/// let energy = hbond_energy(&n, &h, &c, &o);
+/// assert!((energy + 2.904).abs() < 1e-3);
+/// ```
+pub fn hbond_energy(n: &Vector3d, h: &Vector3d, c: &Vector3d, o: &Vector3d) -> f32 {
+    let distances = [h.distance(o), h.distance(c), n.distance(c), n.distance(o)];
+    if distances.iter().any(|&distance| distance < MIN_DISTANCE) {
+        return MIN_HBOND_ENERGY;
+    }
+    let [ho, hc, nc, no] = distances;
+    let energy = COUPLING_CONSTANT * (1.0 / no + 1.0 / hc - 1.0 / ho - 1.0 / nc);
+    // Rounded as DSSP does, so that borderline bonds agree.
+    ((energy * 1000.0).round() / 1000.0).max(MIN_HBOND_ENERGY)
+}
+
+/// The assignment of a residue.
+#[derive(Debug, Clone)]
+pub struct Assignment<'a> {
+    pub residue: Residue<'a>,
+    pub structure: SecondaryStructure,
+}
+
+/// The backbone atoms of a residue.
+struct Backbone {
+    n: Vector3d,
+    ca: Vector3d,
+    c: Vector3d,
+    o: Vector3d,
+    proline: bool,
+}
+
+/// The two lowest energy partners of a residue in hydrogen bonds.
+type Partners = [(Option<usize>, f32); 2];
+
+/// Assigns the secondary structure of each residue of a model with the
+/// backbone atoms N, CA, C and O; other residues are omitted.
+///
+/// # Example
+///
+/// ```
+/// use biost::dssp::{assign, SecondaryStructure};
+/// use biost::{Atom, Model, Vector3d};
+/// let mut model = Model::new(1);
+/// for &(name, x) in [("N", 0.0), ("CA", 1.5), ("C", 3.0), ("O", 4.2)].iter() {
+///     model.atoms.push(Atom::new(name, Vector3d::new(x, 0.0, 0.0)));
+/// }
+/// let assignments = assign(&model);
+/// assert_eq!(1, assignments.len());
+/// assert_eq!(SecondaryStructure::Loop, assignments[0].structure);
+/// ```
+pub fn assign(model: &Model) -> Vec<Assignment<'_>> {
+    let mut residues = Vec::new();
+    let mut backbones = Vec::new();
+    for residue in model.residues() {
+        let position = |name: &str| residue.atom(name).map(|atom| atom.position);
+        if let (Some(n), Some(ca), Some(c), Some(o)) =
+            (position("N"), position("CA"), position("C"), position("O"))
+        {
+            backbones.push(Backbone {
+                n,
+                ca,
+                c,
+                o,
+                proline: residue.name() == "PRO",
+            });
+            residues.push(residue);
+        }
+    }
+    let structures = Dssp::new(&backbones).assign();
+    residues
+        .into_iter()
+        .zip(structures)
+        .map(|(residue, structure)| Assignment { residue, structure })
+        .collect()
+}
+
+struct Dssp {
+    len: usize,
+    /// Whether each residue is bonded to the previous one.
+    bonded: Vec<bool>,
+    ca: Vec<Vector3d>,
+    /// The phi and psi angles in degrees.
+    angles: Vec<(Option<f32>, Option<f32>)>,
+    /// The acceptors of the N-H group of each residue.
+    acceptors: Vec<Partners>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BridgeType {
+    Parallel,
+    Antiparallel,
+}
+
+/// A ladder of consecutive bridges between residues `i` and `j`, in which
+/// `i` are increasing, as are `j` in parallel ladders.
+struct Ladder {
+    kind: BridgeType,
+    i: Vec<usize>,
+    j: Vec<usize>,
+}
+
+impl Dssp {
+    fn new(backbones: &[Backbone]) -> Self {
+        let len = backbones.len();
+        let bonded: Vec<bool> = (0..len)
+            .map(|k| k > 0 && backbones[k - 1].c.distance(&backbones[k].n) <= MAX_PEPTIDE_BOND)
+            .collect();
+        let hydrogens: Vec<Option<Vector3d>> = (0..len)
+            .map(|k| {
+                if bonded[k] && !backbones[k].proline {
+                    let previous = &backbones[k - 1];
+                    Some(hydrogen(&backbones[k].n, &previous.c, &previous.o))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        let angles = (0..len)
+            .map(|k| {
+                let b = &backbones[k];
+                let phi = if bonded[k] {
+                    Some(dihedral(&backbones[k - 1].c, &b.n, &b.ca, &b.c).to_degrees())
+                } else {
+                    None
+                };
+                let psi = if k + 1 < len && bonded[k + 1] {
+                    Some(dihedral(&b.n, &b.ca, &b.c, &backbones[k + 1].n).to_degrees())
+                } else {
+                    None
+                };
+                (phi, psi)
+            })
+            .collect();
+
+        let mut acceptors = vec![[(None, 0.0); 2]; len];
+        let ca: Vec<Vector3d> = backbones.iter().map(|b| b.ca).collect();
+        let mut energy = |donor: usize, acceptor: usize| {
+            if let Some(h) = hydrogens[donor] {
+                let (d, a) = (&backbones[donor], &backbones[acceptor]);
+                let energy = hbond_energy(&d.n, &h, &a.c, &a.o);
+                insert(&mut acceptors[donor], acceptor, energy);
+            }
+        };
+        // Residues with a non-finite CA are left out of the grid, which
+        // would otherwise not be built at all.
+        let finite: Vec<usize> = (0..len)
+            .filter(|&k| ca[k].x.is_finite() && ca[k].y.is_finite() && ca[k].z.is_finite())
+            .collect();
+        let positions: Vec<Vector3d> = finite.iter().map(|&k| ca[k]).collect();
+        if let Some(grid) = Grid::new(&positions, MAX_CA_DISTANCE) {
+            for (i, j, distance) in grid.pairs(MAX_CA_DISTANCE) {
+                let (i, j) = (finite[i], finite[j]);
+                if distance < MAX_CA_DISTANCE {
+                    energy(i, j);
+                    if j != i + 1 {
+                        energy(j, i);
+                    }
+                }
+            }
+        }
+        Dssp {
+            len,
+            bonded,
+            ca,
+            angles,
+            acceptors,
+        }
+    }
+
+    /// Returns whether the N-H group of `donor` is hydrogen bonded to the
+    /// C=O group of `acceptor`.
+    fn bond(&self, donor: usize, acceptor: usize) -> bool {
+        donor < self.len
+            && self.acceptors[donor]
+                .iter()
+                .any(|&(partner, energy)| partner == Some(acceptor) && energy < MAX_HBOND_ENERGY)
+    }
+
+    /// Returns whether residues from `i` to `j` are consecutively bonded.
+    fn no_break(&self, i: usize, j: usize) -> bool {
+        j < self.len && (i + 1..=j).all(|k| self.bonded[k])
+    }
+
+    /// Returns whether there is an n-turn at `i`, i.e. a bond from the C=O
+    /// of `i` to the N-H of `i + n`.
+    fn turn(&self, i: usize, n: usize) -> bool {
+        self.no_break(i, i + n) && self.bond(i + n, i)
+    }
+
+    fn bridge(&self, i: usize, j: usize) -> Option<BridgeType> {
+        if !(self.no_break(i - 1, i + 1) && self.no_break(j - 1, j + 1)) {
+            return None;
+        }
+        if (self.bond(i + 1, j) && self.bond(j, i - 1))
+            || (self.bond(j + 1, i) && self.bond(i, j - 1))
+        {
+            Some(BridgeType::Parallel)
+        } else if (self.bond(i + 1, j - 1) && self.bond(j + 1, i - 1))
+            || (self.bond(j, i) && self.bond(i, j))
+        {
+            Some(BridgeType::Antiparallel)
+        } else {
+            None
+        }
+    }
+
+    fn assign(&self) -> Vec<SecondaryStructure> {
+        let mut structures = vec![SecondaryStructure::Loop; self.len];
+        self.assign_sheets(&mut structures);
+        self.assign_helices(&mut structures);
+        self.assign_turns_and_bends(&mut structures);
+        self.assign_polyproline(&mut structures);
+        structures
+    }
+
+    fn assign_sheets(&self, structures: &mut [SecondaryStructure]) {
+        let mut ladders: Vec<Ladder> = Vec::new();
+        for i in 1..self.len.saturating_sub(4) {
+            for j in i + 3..self.len - 1 {
+                let kind = match self.bridge(i, j) {
+                    Some(kind) => kind,
+                    None => continue,
+                };
+                let extended = ladders.iter_mut().any(|ladder| {
+                    if ladder.kind != kind || ladder.i.last() != Some(&(i - 1)) {
+                        return false;
+                    }
+                    match kind {
+                        BridgeType::Parallel if ladder.j.last() == Some(&(j - 1)) => {
+                            ladder.i.push(i);
+                            ladder.j.push(j);
+                            true
+                        }
+                        BridgeType::Antiparallel if ladder.j.first() == Some(&(j + 1)) => {
+                            ladder.i.push(i);
+                            ladder.j.insert(0, j);
+                            true
+                        }
+                        _ => false,
+                    }
+                });
+                if !extended {
+                    ladders.push(Ladder {
+                        kind,
+                        i: vec![i],
+                        j: vec![j],
+                    });
+                }
+            }
+        }
+
+        // Link ladders separated by a beta bulge.
+        ladders.sort_by_key(|ladder| (ladder.i[0], ladder.j[0]));
+        let mut a = 0;
+        while a < ladders.len() {
+            let mut b = a + 1;
+            while b < ladders.len() {
+                if self.bulge(&ladders[a], &ladders[b]) {
+                    let linked = ladders.remove(b);
+                    let ladder = &mut ladders[a];
+                    ladder.i.extend(linked.i);
+                    match ladder.kind {
+                        BridgeType::Parallel => ladder.j.extend(linked.j),
+                        BridgeType::Antiparallel => {
+                            let mut j = linked.j;
+                            j.append(&mut ladder.j);
+                            ladder.j = j;
+                        }
+                    }
+                } else {
+                    b += 1;
+                }
+            }
+            a += 1;
+        }
+
+        for ladder in ladders.iter() {
+            let structure = if ladder.i.len() > 1 {
+                SecondaryStructure::Strand
+            } else {
+                SecondaryStructure::Bridge
+            };
+            for range in [&ladder.i, &ladder.j].iter() {
+                let (first, last) = (range[0], range[range.len() - 1]);
+                for assigned in structures[first.min(last)..=first.max(last)].iter_mut() {
+                    if *assigned != SecondaryStructure::Strand {
+                        *assigned = structure;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns whether ladder `b` following ladder `a` is linked to it by a
+    /// bulge of at most 4 residues on one strand and 1 on the other.
+    fn bulge(&self, a: &Ladder, b: &Ladder) -> bool {
+        let (ibi, iei) = (a.i[0], a.i[a.i.len() - 1]);
+        let (jbi, jei) = (a.j[0], a.j[a.j.len() - 1]);
+        let (ibj, iej) = (b.i[0], b.i[b.i.len() - 1]);
+        let (jbj, jej) = (b.j[0], b.j[b.j.len() - 1]);
+        // Differences of indices which are negative are regarded as large.
+        let gap = |from: usize, to: usize| to.checked_sub(from).unwrap_or(usize::MAX);
+        if a.kind != b.kind
+            || !self.no_break(ibi.min(ibj), iei.max(iej))
+            || !self.no_break(jbi.min(jbj), jei.max(jej))
+            || gap(iei, ibj) >= 6
+            || (iei >= ibj && ibi <= iej)
+        {
+            return false;
+        }
+        match a.kind {
+            BridgeType::Parallel => (gap(jei, jbj) < 6 && gap(iei, ibj) < 3) || gap(jei, jbj) < 3,
+            BridgeType::Antiparallel => {
+                (gap(jej, jbi) < 6 && gap(iei, ibj) < 3) || gap(jej, jbi) < 3
+            }
+        }
+    }
+
+    fn assign_helices(&self, structures: &mut [SecondaryStructure]) {
+        use self::SecondaryStructure::*;
+        let start = |i: usize, n: usize| i >= 1 && self.turn(i - 1, n) && self.turn(i, n);
+        for i in 1..self.len {
+            if start(i, 4) {
+                for structure in structures[i..i + 4].iter_mut() {
+                    *structure = AlphaHelix;
+                }
+            }
+        }
+        for i in 1..self.len {
+            if start(i, 3)
+                && structures[i..i + 3]
+                    .iter()
+                    .all(|&s| s == Loop || s == Helix310)
+            {
+                for structure in structures[i..i + 3].iter_mut() {
+                    *structure = Helix310;
+                }
+            }
+        }
+        for i in 1..self.len {
+            if start(i, 5)
+                && structures[i..i + 5]
+                    .iter()
+                    .all(|&s| s == Loop || s == PiHelix || s == AlphaHelix)
+            {
+                for structure in structures[i..i + 5].iter_mut() {
+                    *structure = PiHelix;
+                }
+            }
+        }
+    }
+
+    fn assign_polyproline(&self, structures: &mut [SecondaryStructure]) {
+        let within = |angle: Option<f32>, (center, width): (f32, f32)| {
+            angle.is_some_and(|angle| (angle - center).abs() <= width)
+        };
+        let pp = |k: usize| within(self.angles[k].0, PP_PHI) && within(self.angles[k].1, PP_PSI);
+        for i in 0..(self.len + 1).saturating_sub(MIN_PP_STRETCH) {
+            if (i..i + MIN_PP_STRETCH).all(pp) {
+                for structure in structures[i..i + MIN_PP_STRETCH].iter_mut() {
+                    if *structure == SecondaryStructure::Loop {
+                        *structure = SecondaryStructure::PolyProline;
+                    }
+                }
+            }
+        }
+    }
+
+    fn assign_turns_and_bends(&self, structures: &mut [SecondaryStructure]) {
+        let len = structures.len();
+        for (i, structure) in structures
+            .iter_mut()
+            .enumerate()
+            .take(len.saturating_sub(1))
+            .skip(1)
+        {
+            if *structure != SecondaryStructure::Loop {
+                continue;
+            }
+            let turn = (3..=5).any(|n| (1..n).any(|k| i >= k && self.turn(i - k, n)));
+            if turn {
+                *structure = SecondaryStructure::Turn;
+            } else if self.bend(i) {
+                *structure = SecondaryStructure::Bend;
+            }
+        }
+    }
+
+    fn bend(&self, i: usize) -> bool {
+        if i < 2 || !self.no_break(i - 2, i + 2) {
+            return false;
+        }
+        let before = self.ca[i] - self.ca[i - 2];
+        let after = self.ca[i + 2] - self.ca[i];
+        before.angle(&after).to_degrees() > MIN_BEND_ANGLE
+    }
+}
+
+/// Keeps the two lowest energy partners.
+fn insert(partners: &mut Partners, partner: usize, energy: f32) {
+    if energy < partners[0].1 {
+        partners[1] = partners[0];
+        partners[0] = (Some(partner), energy);
+    } else if energy < partners[1].1 {
+        partners[1] = (Some(partner), energy);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atom::Atom;
+    use zmatrix::place;
+
+    /// Builds a peptide of given residues with the same backbone angles.
+    fn peptide(names: &[&str], phi: f32, psi: f32) -> Model {
+        let mut model = Model::new(1);
+        let mut n = Vector3d::new(-0.7, -1.2, -0.5);
+        let mut ca = Vector3d::zero();
+        let mut c = place(
+            &Vector3d::new(0.0, -1.0, 1.0),
+            &n,
+            &ca,
+            1.52,
+            111.0f32.to_radians(),
+            phi.to_radians(),
+        );
+        for (k, name) in names.iter().enumerate() {
+            let next_n = place(&n, &ca, &c, 1.33, 116.2f32.to_radians(), psi.to_radians());
+            let o = place(
+                &next_n,
+                &ca,
+                &c,
+                1.23,
+                120.5f32.to_radians(),
+                180.0f32.to_radians(),
+            );
+            for &(atom_name, position) in [("N", n), ("CA", ca), ("C", c), ("O", o)].iter() {
+                let mut atom = Atom::new(atom_name, position);
+                atom.res_name = name.to_string();
+                atom.res_seq = k as i32 + 1;
+                atom.chain_id = "A".to_string();
+                model.atoms.push(atom);
+            }
+            let next_ca = place(
+                &ca,
+                &c,
+                &next_n,
+                1.46,
+                121.7f32.to_radians(),
+                std::f32::consts::PI,
+            );
+            let next_c = place(
+                &c,
+                &next_n,
+                &next_ca,
+                1.52,
+                111.2f32.to_radians(),
+                phi.to_radians(),
+            );
+            n = next_n;
+            ca = next_ca;
+            c = next_c;
+        }
+        model
+    }
+
+    fn codes(model: &Model) -> String {
+        assign(model).iter().map(|a| a.structure.code()).collect()
+    }
+
+    #[test]
+    fn test_hbond_energy() {
+        let n = Vector3d::zero();
+        let h = Vector3d::new(1.0, 0.0, 0.0);
+        assert_eq!(MIN_HBOND_ENERGY, hbond_energy(&n, &h, &h, &h));
+        let far = hbond_energy(
+            &n,
+            &h,
+            &Vector3d::new(9.0, 0.0, 0.0),
+            &Vector3d::new(8.0, 0.0, 0.0),
+        );
+        assert!(far < 0.0 && far > MAX_HBOND_ENERGY);
+    }
+
+    #[test]
+    fn test_alpha_helix() {
+        let model = peptide(&["ALA"; 12], -57.0, -47.0);
+        assert_eq!(" HHHHHHHHHH ", codes(&model));
+    }
+
+    #[test]
+    fn test_non_finite_position() {
+        let mut model = peptide(&["ALA"; 13], -57.0, -47.0);
+        for atom in &mut model.atoms[48..] {
+            atom.position = Vector3d::new(f32::NAN, 0.0, 0.0);
+        }
+        assert_eq!(" HHHHHHHHHH  ", codes(&model));
+    }
+
+    #[test]
+    fn test_310_helix() {
+        let model = peptide(&["ALA"; 10], -49.0, -26.0);
+        let codes = codes(&model);
+        assert!(codes.contains("GGG"), "{:?}", codes);
+        assert!(!codes.contains('H'), "{:?}", codes);
+    }
+
+    #[test]
+    fn test_polyproline() {
+        let model = peptide(&["PRO"; 6], -75.0, 145.0);
+        assert_eq!(" PPPP ", codes(&model));
+    }
+
+    /// Returns DSSP for residues with hydrogen bonds given as pairs of the
+    /// donor and the acceptor.
+    fn from_bonds(len: usize, bonds: &[(usize, usize)]) -> Vec<SecondaryStructure> {
+        dssp_from_bonds(len, bonds).assign()
+    }
+
+    fn dssp_from_bonds(len: usize, bonds: &[(usize, usize)]) -> Dssp {
+        let mut acceptors = vec![[(None, 0.0); 2]; len];
+        for &(donor, acceptor) in bonds {
+            insert(&mut acceptors[donor], acceptor, -2.0);
+        }
+        let ca = (0..len)
+            .map(|k| Vector3d::new(k as f32 * 3.8, 0.0, 0.0))
+            .collect();
+        Dssp {
+            len,
+            bonded: (0..len).map(|k| k > 0).collect(),
+            ca,
+            angles: vec![(None, None); len],
+            acceptors,
+        }
+    }
+
+    fn to_codes(structures: &[SecondaryStructure]) -> String {
+        structures.iter().map(SecondaryStructure::code).collect()
+    }
+
+    #[test]
+    fn test_antiparallel() {
+        // A hairpin of residues 1-4 and 9-12 paired 2-11 and 4-9.
+        let bonds = [(2, 11), (11, 2), (4, 9), (9, 4)];
+        let codes = to_codes(&from_bonds(14, &bonds));
+        // The C=O of 4 and the N-H of 9 also form a 5-turn.
+        assert_eq!("  EEETTTTEEE  ", &codes[..]);
+
+        // A single pair is an isolated bridge.
+        let codes = to_codes(&from_bonds(14, &bonds[..2]));
+        assert_eq!("  B        B  ", &codes[..]);
+    }
+
+    #[test]
+    fn test_parallel() {
+        // Strands 1-5 and 10-14 with bridges 2-11, 3-12 and 4-13.
+        let bonds = [(11, 1), (3, 11), (12, 2), (4, 12), (13, 3), (5, 13)];
+        let codes = to_codes(&from_bonds(16, &bonds));
+        assert_eq!("  EEE      EEE  ", &codes[..]);
+    }
+
+    #[test]
+    fn test_bulge() {
+        // Antiparallel bridges 2-20, 3-19, 4-18 and 7-17, in which residues
+        // 5 and 6 form a bulge.
+        let bonds = [(2, 20), (20, 2), (4, 18), (18, 4), (7, 17), (17, 7)];
+        let structures = from_bonds(23, &bonds);
+        let codes = to_codes(&structures);
+        assert_eq!("  EEEEEE         EEEE  ", &codes[..]);
+    }
+
+    #[test]
+    fn test_polyproline_priority() {
+        // A 3-turn from 2 to 5 within a stretch of polyproline angles: the
+        // turn takes precedence over the polyproline helix as in DSSP 4.
+        let mut dssp = dssp_from_bonds(9, &[(5, 2)]);
+        dssp.angles = vec![(Some(-75.0), Some(145.0)); 9];
+        assert_eq!("PPPTTPPPP", to_codes(&dssp.assign()));
+
+        // So does a bend at residue 4, where the chain turns by 90 degrees.
+        let mut dssp = dssp_from_bonds(9, &[]);
+        dssp.angles = vec![(Some(-75.0), Some(145.0)); 9];
+        for k in 5..9 {
+            dssp.ca[k] = Vector3d::new(15.2, (k - 4) as f32 * 3.8, 0.0);
+        }
+        assert!(dssp.bend(4));
+        assert_eq!("PPPPSPPPP", to_codes(&dssp.assign()));
+
+        // A helix is not a polyproline helix either.
+        let mut dssp = dssp_from_bonds(12, &[(5, 1), (6, 2), (7, 3)]);
+        dssp.angles = vec![(Some(-75.0), Some(145.0)); 12];
+        assert_eq!("PPHHHHHPPPPP", to_codes(&dssp.assign()));
+    }
+}
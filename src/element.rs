@@ -0,0 +1,451 @@
+//! Chemical elements and their atomic properties.
+//!
+//! The properties are
+//!
+//! * standard atomic weights of IUPAC, or the mass numbers of the most
+//!   stable isotopes for elements without stable ones,
+//! * covalent radii of Cordero et al. (2008), with those of low-spin Mn,
+//!   Fe and Co and sp3 carbon,
+//! * van der Waals radii of Bondi (1964), supplemented by Mantina et al.
+//!   (2009) for the other main-group elements, and
+//! * Pauling electronegativities.
+//!
+//! Masses are in daltons and radii in Angstroms.
+
+use std::fmt;
+
+use atom::Atom;
+
+/// The symbol, name, mass, covalent radius, van der Waals radius and
+/// electronegativity of an element.
+type Properties = (
+    &'static str,
+    &'static str,
+    f32,
+    Option<f32>,
+    Option<f32>,
+    Option<f32>,
+);
+
+macro_rules! elements {
+    ($($variant:ident $symbol:expr, $name:expr, $mass:expr, $covalent:expr, $vdw:expr, $en:expr;)*) => {
+        /// A chemical element.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum Element {
+            $($variant,)*
+        }
+
+        /// The elements in the order of atomic numbers.
+        const ELEMENTS: &[Element] = &[$(Element::$variant,)*];
+
+        /// The properties of each element.
+        const PROPERTIES: &[Properties] = &[$(($symbol, $name, $mass, $covalent, $vdw, $en),)*];
+    };
+}
+
+elements! {
+    H "H", "hydrogen", 1.008, Some(0.31), Some(1.20), Some(2.20);
+    He "He", "helium", 4.0026, Some(0.28), Some(1.40), None;
+    Li "Li", "lithium", 6.94, Some(1.28), Some(1.82), Some(0.98);
+    Be "Be", "beryllium", 9.0122, Some(0.96), Some(1.53), Some(1.57);
+    B "B", "boron", 10.81, Some(0.84), Some(1.92), Some(2.04);
+    C "C", "carbon", 12.011, Some(0.76), Some(1.70), Some(2.55);
+    N "N", "nitrogen", 14.007, Some(0.71), Some(1.55), Some(3.04);
+    O "O", "oxygen", 15.999, Some(0.66), Some(1.52), Some(3.44);
+    F "F", "fluorine", 18.998, Some(0.57), Some(1.47), Some(3.98);
+    Ne "Ne", "neon", 20.180, Some(0.58), Some(1.54), None;
+    Na "Na", "sodium", 22.990, Some(1.66), Some(2.27), Some(0.93);
+    Mg "Mg", "magnesium", 24.305, Some(1.41), Some(1.73), Some(1.31);
+    Al "Al", "aluminium", 26.982, Some(1.21), Some(1.84), Some(1.61);
+    Si "Si", "silicon", 28.085, Some(1.11), Some(2.10), Some(1.90);
+    P "P", "phosphorus", 30.974, Some(1.07), Some(1.80), Some(2.19);
+    S "S", "sulfur", 32.06, Some(1.05), Some(1.80), Some(2.58);
+    Cl "Cl", "chlorine", 35.45, Some(1.02), Some(1.75), Some(3.16);
+    Ar "Ar", "argon", 39.948, Some(1.06), Some(1.88), None;
+    K "K", "potassium", 39.098, Some(2.03), Some(2.75), Some(0.82);
+    Ca "Ca", "calcium", 40.078, Some(1.76), Some(2.31), Some(1.00);
+    Sc "Sc", "scandium", 44.956, Some(1.70), None, Some(1.36);
+    Ti "Ti", "titanium", 47.867, Some(1.60), None, Some(1.54);
+    V "V", "vanadium", 50.942, Some(1.53), None, Some(1.63);
+    Cr "Cr", "chromium", 51.996, Some(1.39), None, Some(1.66);
+    Mn "Mn", "manganese", 54.938, Some(1.39), None, Some(1.55);
+    Fe "Fe", "iron", 55.845, Some(1.32), None, Some(1.83);
+    Co "Co", "cobalt", 58.933, Some(1.26), None, Some(1.88);
+    Ni "Ni", "nickel", 58.693, Some(1.24), Some(1.63), Some(1.91);
+    Cu "Cu", "copper", 63.546, Some(1.32), Some(1.40), Some(1.90);
+    Zn "Zn", "zinc", 65.38, Some(1.22), Some(1.39), Some(1.65);
+    Ga "Ga", "gallium", 69.723, Some(1.22), Some(1.87), Some(1.81);
+    Ge "Ge", "germanium", 72.630, Some(1.20), Some(2.11), Some(2.01);
+    As "As", "arsenic", 74.922, Some(1.19), Some(1.85), Some(2.18);
+    Se "Se", "selenium", 78.971, Some(1.20), Some(1.90), Some(2.55);
+    Br "Br", "bromine", 79.904, Some(1.20), Some(1.85), Some(2.96);
+    Kr "Kr", "krypton", 83.798, Some(1.16), Some(2.02), Some(3.00);
+    Rb "Rb", "rubidium", 85.468, Some(2.20), Some(3.03), Some(0.82);
+    Sr "Sr", "strontium", 87.62, Some(1.95), Some(2.49), Some(0.95);
+    Y "Y", "yttrium", 88.906, Some(1.90), None, Some(1.22);
+    Zr "Zr", "zirconium", 91.224, Some(1.75), None, Some(1.33);
+    Nb "Nb", "niobium", 92.906, Some(1.64), None, Some(1.6);
+    Mo "Mo", "molybdenum", 95.95, Some(1.54), None, Some(2.16);
+    Tc "Tc", "technetium", 98.0, Some(1.47), None, Some(1.9);
+    Ru "Ru", "ruthenium", 101.07, Some(1.46), None, Some(2.2);
+    Rh "Rh", "rhodium", 102.91, Some(1.42), None, Some(2.28);
+    Pd "Pd", "palladium", 106.42, Some(1.39), Some(1.63), Some(2.20);
+    Ag "Ag", "silver", 107.87, Some(1.45), Some(1.72), Some(1.93);
+    Cd "Cd", "cadmium", 112.41, Some(1.44), Some(1.58), Some(1.69);
+    In "In", "indium", 114.82, Some(1.42), Some(1.93), Some(1.78);
+    Sn "Sn", "tin", 118.71, Some(1.39), Some(2.17), Some(1.96);
+    Sb "Sb", "antimony", 121.76, Some(1.39), Some(2.06), Some(2.05);
+    Te "Te", "tellurium", 127.60, Some(1.38), Some(2.06), Some(2.1);
+    I "I", "iodine", 126.90, Some(1.39), Some(1.98), Some(2.66);
+    Xe "Xe", "xenon", 131.29, Some(1.40), Some(2.16), Some(2.60);
+    Cs "Cs", "caesium", 132.91, Some(2.44), Some(3.43), Some(0.79);
+    Ba "Ba", "barium", 137.33, Some(2.15), Some(2.68), Some(0.89);
+    La "La", "lanthanum", 138.91, Some(2.07), None, Some(1.10);
+    Ce "Ce", "cerium", 140.12, Some(2.04), None, Some(1.12);
+    Pr "Pr", "praseodymium", 140.91, Some(2.03), None, Some(1.13);
+    Nd "Nd", "neodymium", 144.24, Some(2.01), None, Some(1.14);
+    Pm "Pm", "promethium", 145.0, Some(1.99), None, Some(1.13);
+    Sm "Sm", "samarium", 150.36, Some(1.98), None, Some(1.17);
+    Eu "Eu", "europium", 151.96, Some(1.98), None, Some(1.2);
+    Gd "Gd", "gadolinium", 157.25, Some(1.96), None, Some(1.20);
+    Tb "Tb", "terbium", 158.93, Some(1.94), None, Some(1.1);
+    Dy "Dy", "dysprosium", 162.50, Some(1.92), None, Some(1.22);
+    Ho "Ho", "holmium", 164.93, Some(1.92), None, Some(1.23);
+    Er "Er", "erbium", 167.26, Some(1.89), None, Some(1.24);
+    Tm "Tm", "thulium", 168.93, Some(1.90), None, Some(1.25);
+    Yb "Yb", "ytterbium", 173.05, Some(1.87), None, Some(1.1);
+    Lu "Lu", "lutetium", 174.97, Some(1.87), None, Some(1.27);
+    Hf "Hf", "hafnium", 178.49, Some(1.75), None, Some(1.3);
+    Ta "Ta", "tantalum", 180.95, Some(1.70), None, Some(1.5);
+    W "W", "tungsten", 183.84, Some(1.62), None, Some(2.36);
+    Re "Re", "rhenium", 186.21, Some(1.51), None, Some(1.9);
+    Os "Os", "osmium", 190.23, Some(1.44), None, Some(2.2);
+    Ir "Ir", "iridium", 192.22, Some(1.41), None, Some(2.20);
+    Pt "Pt", "platinum", 195.08, Some(1.36), Some(1.72), Some(2.28);
+    Au "Au", "gold", 196.97, Some(1.36), Some(1.66), Some(2.54);
+    Hg "Hg", "mercury", 200.59, Some(1.32), Some(1.55), Some(2.00);
+    Tl "Tl", "thallium", 204.38, Some(1.45), Some(1.96), Some(1.62);
+    Pb "Pb", "lead", 207.2, Some(1.46), Some(2.02), Some(2.33);
+    Bi "Bi", "bismuth", 208.98, Some(1.48), Some(2.07), Some(2.02);
+    Po "Po", "polonium", 209.0, Some(1.40), Some(1.97), Some(2.0);
+    At "At", "astatine", 210.0, Some(1.50), Some(2.02), Some(2.2);
+    Rn "Rn", "radon", 222.0, Some(1.50), Some(2.20), Some(2.2);
+    Fr "Fr", "francium", 223.0, Some(2.60), Some(3.48), Some(0.7);
+    Ra "Ra", "radium", 226.0, Some(2.21), Some(2.83), Some(0.9);
+    Ac "Ac", "actinium", 227.0, Some(2.15), None, Some(1.1);
+    Th "Th", "thorium", 232.04, Some(2.06), None, Some(1.3);
+    Pa "Pa", "protactinium", 231.04, Some(2.00), None, Some(1.5);
+    U "U", "uranium", 238.03, Some(1.96), Some(1.86), Some(1.38);
+    Np "Np", "neptunium", 237.0, Some(1.90), None, Some(1.36);
+    Pu "Pu", "plutonium", 244.0, Some(1.87), None, Some(1.28);
+    Am "Am", "americium", 243.0, Some(1.80), None, Some(1.3);
+    Cm "Cm", "curium", 247.0, Some(1.69), None, Some(1.3);
+    Bk "Bk", "berkelium", 247.0, None, None, Some(1.3);
+    Cf "Cf", "californium", 251.0, None, None, Some(1.3);
+    Es "Es", "einsteinium", 252.0, None, None, Some(1.3);
+    Fm "Fm", "fermium", 257.0, None, None, Some(1.3);
+    Md "Md", "mendelevium", 258.0, None, None, Some(1.3);
+    No "No", "nobelium", 259.0, None, None, Some(1.3);
+    Lr "Lr", "lawrencium", 266.0, None, None, Some(1.3);
+    Rf "Rf", "rutherfordium", 267.0, None, None, None;
+    Db "Db", "dubnium", 268.0, None, None, None;
+    Sg "Sg", "seaborgium", 269.0, None, None, None;
+    Bh "Bh", "bohrium", 270.0, None, None, None;
+    Hs "Hs", "hassium", 269.0, None, None, None;
+    Mt "Mt", "meitnerium", 278.0, None, None, None;
+    Ds "Ds", "darmstadtium", 281.0, None, None, None;
+    Rg "Rg", "roentgenium", 282.0, None, None, None;
+    Cn "Cn", "copernicium", 285.0, None, None, None;
+    Nh "Nh", "nihonium", 286.0, None, None, None;
+    Fl "Fl", "flerovium", 289.0, None, None, None;
+    Mc "Mc", "moscovium", 290.0, None, None, None;
+    Lv "Lv", "livermorium", 293.0, None, None, None;
+    Ts "Ts", "tennessine", 294.0, None, None, None;
+    Og "Og", "oganesson", 294.0, None, None, None;
+}
+
+impl Element {
+    /// Returns the element of given atomic number.
+    pub fn from_atomic_number(number: u8) -> Option<Element> {
+        ELEMENTS.get(usize::from(number).checked_sub(1)?).cloned()
+    }
+
+    /// Parses an element symbol, ignoring case and surrounding whitespace.
+    /// The isotopes `D` and `T` are parsed as hydrogen.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::element::Element;
+    /// assert_eq!(Some(Element::Fe), Element::from_symbol("FE"));
+    /// assert_eq!(Some(Element::H), Element::from_symbol(" d"));
+    /// assert_eq!(None, Element::from_symbol("X"));
+    /// ```
+    pub fn from_symbol(symbol: &str) -> Option<Element> {
+        let symbol = symbol.trim();
+        if symbol.eq_ignore_ascii_case("D") || symbol.eq_ignore_ascii_case("T") {
+            return Some(Element::H);
+        }
+        PROPERTIES
+            .iter()
+            .position(|properties| properties.0.eq_ignore_ascii_case(symbol))
+            .map(|i| ELEMENTS[i])
+    }
+
+    /// Guesses the element from an atom name and its residue name, for
+    /// atoms whose element is not given.
+    ///
+    /// The atom name of an ion, a residue consisting of a single atom,
+    /// usually coincides with the residue name and the element symbol,
+    /// e.g. `ZN`. A name consisting of the symbol of a halogen or a metal
+    /// common in ligands followed by digits, e.g. `CL1` or `FE2`, is taken
+    /// as that element; these names do not occur in standard residues. So is
+    /// such a symbol alone outside standard amino acids and nucleotides,
+    /// e.g. `FE` in `HEM` or `SE` in `MSE`.
+    /// Otherwise, the element is taken as the first letter of the name
+    /// after leading digits, e.g. hydrogen for `1HB`, since polymers and
+    /// most ligands consist of elements with one-letter symbols.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::element::Element;
+    /// assert_eq!(Some(Element::C), Element::guess("CA", "ALA"));
+    /// assert_eq!(Some(Element::Ca), Element::guess("CA", "CA"));
+    /// assert_eq!(Some(Element::H), Element::guess("1HB", "ALA"));
+    /// assert_eq!(Some(Element::Cl), Element::guess("CL1", "LIG"));
+    /// ```
+    pub fn guess(name: &str, res_name: &str) -> Option<Element> {
+        /// The two-letter elements recognized in names alone or followed by
+        /// digits.
+        const LIGAND_ELEMENTS: [&str; 9] = ["BR", "CL", "CU", "FE", "MG", "MN", "NI", "SE", "ZN"];
+        /// The residues in which a bare two-letter name is not an element.
+        const STANDARD_RESIDUES: [&str; 32] = [
+            "ALA", "ARG", "ASN", "ASP", "CYS", "GLN", "GLU", "GLY", "HIS", "ILE", "LEU", "LYS",
+            "MET", "PHE", "PRO", "SER", "THR", "TRP", "TYR", "VAL", "A", "C", "G", "U", "I", "T",
+            "DA", "DC", "DG", "DT", "DU", "DI",
+        ];
+        let name = name.trim().trim_start_matches(|c: char| c.is_ascii_digit());
+        if name.eq_ignore_ascii_case(res_name.trim()) {
+            if let Some(element) = Element::from_symbol(name) {
+                return Some(element);
+            }
+        }
+        if let (Some(symbol), Some(rest)) = (name.get(..2), name.get(2..)) {
+            let numbered = !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit());
+            let bare = rest.is_empty()
+                && !STANDARD_RESIDUES
+                    .iter()
+                    .any(|residue| residue.eq_ignore_ascii_case(res_name.trim()));
+            if (numbered || bare)
+                && LIGAND_ELEMENTS
+                    .iter()
+                    .any(|element| element.eq_ignore_ascii_case(symbol))
+            {
+                return Element::from_symbol(symbol);
+            }
+        }
+        name.get(..1).and_then(Element::from_symbol)
+    }
+
+    /// Returns the element of an atom, guessed from its atom and residue
+    /// names by `guess` if the element is blank.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::element::Element;
+    /// use biost::{Atom, Vector3d};
+    /// let mut atom = Atom::new("CA", Vector3d::zero());
+    /// assert_eq!(Some(Element::C), Element::of(&atom));
+    /// atom.element = "CA".to_string();
+    /// assert_eq!(Some(Element::Ca), Element::of(&atom));
+    /// ```
+    pub fn of(atom: &Atom) -> Option<Element> {
+        if atom.element.trim().is_empty() {
+            Element::guess(&atom.name, &atom.res_name)
+        } else {
+            Element::from_symbol(&atom.element)
+        }
+    }
+
+    /// Returns whether an atom is hydrogen or deuterium by `of`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::element::Element;
+    /// use biost::{Atom, Vector3d};
+    /// assert!(Element::is_hydrogen_atom(&Atom::new("HA", Vector3d::zero())));
+    /// assert!(!Element::is_hydrogen_atom(&Atom::new("CA", Vector3d::zero())));
+    /// ```
+    pub fn is_hydrogen_atom(atom: &Atom) -> bool {
+        Element::of(atom).is_some_and(|element| element.is_hydrogen())
+    }
+
+    fn properties(&self) -> &'static Properties {
+        &PROPERTIES[*self as usize]
+    }
+
+    pub fn atomic_number(&self) -> u8 {
+        *self as u8 + 1
+    }
+
+    /// Returns the symbol, e.g. `Fe`.
+    pub fn symbol(&self) -> &'static str {
+        self.properties().0
+    }
+
+    /// Returns the English name, e.g. `iron`.
+    pub fn name(&self) -> &'static str {
+        self.properties().1
+    }
+
+    /// Returns the standard atomic weight in daltons, or the mass number of
+    /// the most stable isotope if the element has no stable isotopes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::element::Element;
+    /// assert_eq!(12.011, Element::C.mass());
+    /// ```
+    pub fn mass(&self) -> f32 {
+        self.properties().2
+    }
+
+    /// Returns the single-bond covalent radius in Angstroms, which is known
+    /// up to curium.
+    pub fn covalent_radius(&self) -> Option<f32> {
+        self.properties().3
+    }
+
+    /// Returns the van der Waals radius in Angstroms, which is unknown for
+    /// most transition metals, lanthanides and actinides.
+    pub fn vdw_radius(&self) -> Option<f32> {
+        self.properties().4
+    }
+
+    /// Returns the Pauling electronegativity, which is unknown for light
+    /// noble gases and superheavy elements.
+    pub fn electronegativity(&self) -> Option<f32> {
+        self.properties().5
+    }
+
+    /// Returns whether the element is hydrogen, which includes deuterium.
+    pub fn is_hydrogen(&self) -> bool {
+        *self == Element::H
+    }
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Vector3d;
+
+    #[test]
+    fn test_table() {
+        assert_eq!(118, ELEMENTS.len());
+        assert_eq!(118, PROPERTIES.len());
+        for (i, &element) in ELEMENTS.iter().enumerate() {
+            assert_eq!(i + 1, element.atomic_number() as usize);
+            assert_eq!(Some(element), Element::from_atomic_number(i as u8 + 1));
+            assert_eq!(Some(element), Element::from_symbol(element.symbol()));
+            assert_eq!(format!("{:?}", element), element.symbol());
+            assert_eq!(element.symbol(), element.to_string());
+            assert!(element.mass() > 0.0);
+        }
+        assert_eq!(None, Element::from_atomic_number(0));
+        assert_eq!(None, Element::from_atomic_number(119));
+        assert_eq!(Element::Og, Element::from_atomic_number(118).unwrap());
+        // Masses increase with atomic numbers except for a few pairs.
+        let inversions: Vec<(Element, Element)> = ELEMENTS
+            .windows(2)
+            .filter(|pair| pair[0].mass() > pair[1].mass())
+            .map(|pair| (pair[0], pair[1]))
+            .collect();
+        assert_eq!(
+            vec![
+                (Element::Ar, Element::K),
+                (Element::Co, Element::Ni),
+                (Element::Te, Element::I),
+                (Element::Th, Element::Pa),
+                (Element::U, Element::Np),
+                (Element::Pu, Element::Am),
+                (Element::Bh, Element::Hs),
+            ],
+            inversions
+        );
+    }
+
+    #[test]
+    fn test_properties() {
+        assert_eq!(26, Element::Fe.atomic_number());
+        assert_eq!("iron", Element::Fe.name());
+        assert_eq!(55.845, Element::Fe.mass());
+        assert_eq!(Some(0.76), Element::C.covalent_radius());
+        assert_eq!(Some(1.52), Element::O.vdw_radius());
+        assert_eq!(None, Element::Fe.vdw_radius());
+        assert_eq!(Some(3.98), Element::F.electronegativity());
+        assert_eq!(None, Element::He.electronegativity());
+        assert_eq!(None, Element::Og.covalent_radius());
+        // The most electronegative element.
+        let max = ELEMENTS
+            .iter()
+            .filter_map(|element| element.electronegativity().map(|en| (element, en)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        assert_eq!(Some(&Element::F), max.map(|(element, _)| element));
+    }
+
+    #[test]
+    fn test_from_symbol() {
+        assert_eq!(Some(Element::Cl), Element::from_symbol("CL"));
+        assert_eq!(Some(Element::Cl), Element::from_symbol("cl"));
+        assert_eq!(Some(Element::N), Element::from_symbol("N "));
+        assert_eq!(Some(Element::H), Element::from_symbol("T"));
+        assert_eq!(None, Element::from_symbol(""));
+        assert_eq!(None, Element::from_symbol("Xx"));
+        assert_eq!(None, Element::from_symbol("CAL"));
+    }
+
+    #[test]
+    fn test_guess() {
+        assert_eq!(Some(Element::N), Element::guess("N", "GLY"));
+        assert_eq!(Some(Element::O), Element::guess("OXT", "GLY"));
+        assert_eq!(Some(Element::H), Element::guess("HG", "SER"));
+        assert_eq!(Some(Element::Hg), Element::guess("HG", "HG"));
+        assert_eq!(Some(Element::Zn), Element::guess("ZN", "ZN"));
+        assert_eq!(Some(Element::H), Element::guess("2HD1", "LEU"));
+        // Common two-letter elements of ligands followed by digits.
+        assert_eq!(Some(Element::Cl), Element::guess("CL1", "LIG"));
+        assert_eq!(Some(Element::Br), Element::guess("Br12", "LIG"));
+        assert_eq!(Some(Element::Fe), Element::guess("FE1", "HEM"));
+        assert_eq!(Some(Element::Mg), Element::guess("MG2", "LIG"));
+        assert_eq!(Some(Element::C), Element::guess("CLA", "LIG"));
+        // And alone outside standard residues.
+        assert_eq!(Some(Element::Fe), Element::guess("FE", "HEM"));
+        assert_eq!(Some(Element::Se), Element::guess("SE", "MSE"));
+        assert_eq!(Some(Element::Mg), Element::guess("MG", "CLA"));
+        assert_eq!(Some(Element::Cl), Element::guess("CL", "LIG"));
+        // Names of standard residues are not taken for two-letter elements.
+        assert_eq!(Some(Element::C), Element::guess("CD1", "LEU"));
+        assert_eq!(Some(Element::H), Element::guess("HG1", "THR"));
+        assert_eq!(Some(Element::N), Element::guess("NE2", "GLN"));
+        assert_eq!(Some(Element::N), Element::guess("NI", "DA"));
+        assert_eq!(None, Element::guess("X", "UNK"));
+        assert_eq!(None, Element::guess("", "UNK"));
+
+        let mut atom = Atom::new("1HB", Vector3d::zero());
+        assert_eq!(Some(Element::H), Element::of(&atom));
+        atom.element = "D".to_string();
+        assert!(Element::of(&atom).unwrap().is_hydrogen());
+        atom.element = "Q".to_string();
+        assert_eq!(None, Element::of(&atom));
+    }
+}
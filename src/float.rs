@@ -0,0 +1,180 @@
+use std::fmt;
+use std::iter;
+use std::ops;
+
+/// A floating point scalar usable as the component type of `Vector3d`.
+///
+/// This trait is implemented for `f32` and `f64`, and only exposes the
+/// operations needed by the geometric types of this crate.
+pub trait Float:
+    Copy
+    + PartialOrd
+    + fmt::Debug
+    + fmt::Display
+    + ops::Add<Output = Self>
+    + ops::Sub<Output = Self>
+    + ops::Mul<Output = Self>
+    + ops::Div<Output = Self>
+    + ops::Neg<Output = Self>
+    + ops::AddAssign
+    + ops::SubAssign
+    + ops::MulAssign
+    + ops::DivAssign
+    + iter::Sum
+{
+    /// Returns `0`.
+    fn zero() -> Self;
+
+    /// Returns `1`.
+    fn one() -> Self;
+
+    /// Returns the machine epsilon.
+    fn epsilon() -> Self;
+
+    /// Returns Archimedes' constant.
+    fn pi() -> Self;
+
+    /// Converts a `f32` value, which is lossless for both implementors.
+    fn from_f32(value: f32) -> Self;
+
+    /// Converts a `f64` value, rounding it if necessary.
+    fn from_f64(value: f64) -> Self;
+
+    /// Converts the value to `f32`, rounding it if necessary.
+    fn to_f32(self) -> f32;
+
+    /// Converts the value to `f64`, which is lossless for both implementors.
+    fn to_f64(self) -> f64;
+
+    fn abs(self) -> Self;
+    fn floor(self) -> Self;
+    fn round(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn asin(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn min(self, other: Self) -> Self;
+    fn max(self, other: Self) -> Self;
+    fn is_nan(self) -> bool;
+    fn is_finite(self) -> bool;
+}
+
+macro_rules! impl_float {
+    ($t:ident) => {
+        impl Float for $t {
+            fn zero() -> Self {
+                0.0
+            }
+
+            fn one() -> Self {
+                1.0
+            }
+
+            fn epsilon() -> Self {
+                $t::EPSILON
+            }
+
+            fn pi() -> Self {
+                ::std::$t::consts::PI
+            }
+
+            fn from_f32(value: f32) -> Self {
+                value as $t
+            }
+
+            fn from_f64(value: f64) -> Self {
+                value as $t
+            }
+
+            fn to_f32(self) -> f32 {
+                self as f32
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn abs(self) -> Self {
+                $t::abs(self)
+            }
+
+            fn floor(self) -> Self {
+                $t::floor(self)
+            }
+
+            fn round(self) -> Self {
+                $t::round(self)
+            }
+
+            fn sqrt(self) -> Self {
+                $t::sqrt(self)
+            }
+
+            fn sin(self) -> Self {
+                $t::sin(self)
+            }
+
+            fn cos(self) -> Self {
+                $t::cos(self)
+            }
+
+            fn tan(self) -> Self {
+                $t::tan(self)
+            }
+
+            fn asin(self) -> Self {
+                $t::asin(self)
+            }
+
+            fn acos(self) -> Self {
+                $t::acos(self)
+            }
+
+            fn atan2(self, other: Self) -> Self {
+                $t::atan2(self, other)
+            }
+
+            fn min(self, other: Self) -> Self {
+                $t::min(self, other)
+            }
+
+            fn max(self, other: Self) -> Self {
+                $t::max(self, other)
+            }
+
+            fn is_nan(self) -> bool {
+                $t::is_nan(self)
+            }
+
+            fn is_finite(self) -> bool {
+                $t::is_finite(self)
+            }
+        }
+    };
+}
+
+impl_float!(f32);
+impl_float!(f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversion() {
+        assert_eq!(0.1f32 as f64, <f64 as Float>::from_f32(0.1));
+        assert_eq!(0.1f32, <f32 as Float>::from_f64(0.1));
+        assert_eq!(0.1f32 as f64, Float::to_f64(0.1f32));
+        assert_eq!(0.1f32, Float::to_f32(0.1f64));
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(0.0, <f32 as Float>::zero());
+        assert_eq!(1.0, <f64 as Float>::one());
+        assert_eq!(::std::f64::consts::PI, <f64 as Float>::pi());
+    }
+}
@@ -0,0 +1,687 @@
+//! Hydrogen bonds by geometric criteria.
+//!
+//! Atoms are typed as donors and acceptors by their residue and atom names
+//! for standard amino acids and nucleotides, and by their elements for
+//! other residues, e.g. waters and ligands, where every nitrogen and oxygen
+//! is taken as both.
+//! A donor D with a hydrogen H and an acceptor A bonded to an antecedent AA
+//! form a hydrogen bond if the D-A distance and the D-H...A and H...A-AA
+//! angles meet the `Criteria`. Hydrogens are those in the residue of the
+//! donor within `MAX_HYDROGEN_BOND_LENGTH` of it; antecedents likewise are the
+//! nearest heavy atoms bonded to the acceptors.
+
+use std::f32::consts::PI;
+use std::fmt;
+use std::io::{self, Write};
+
+use atom::Atom;
+use element::Element;
+use neighbor::Grid;
+use structure::Model;
+use Vector3d;
+
+/// The maximum distance in Angstroms between covalently bonded atoms in a
+/// residue.
+const MAX_COVALENT_BOND: f32 = 1.9;
+
+/// The maximum distance between a donor and its hydrogens.
+const MAX_HYDROGEN_BOND_LENGTH: f32 = 1.3;
+
+/// The side-chain donors and acceptors of standard amino acids.
+const SIDE_CHAIN_ROLES: [(&str, &str, Role); 30] = [
+    ("ARG", "NE", Role::Donor),
+    ("ARG", "NH1", Role::Donor),
+    ("ARG", "NH2", Role::Donor),
+    ("ASN", "ND2", Role::Donor),
+    ("ASN", "OD1", Role::Acceptor),
+    ("ASP", "OD1", Role::Acceptor),
+    ("ASP", "OD2", Role::Acceptor),
+    ("CYS", "SG", Role::Both),
+    ("GLN", "NE2", Role::Donor),
+    ("GLN", "OE1", Role::Acceptor),
+    ("GLU", "OE1", Role::Acceptor),
+    ("GLU", "OE2", Role::Acceptor),
+    ("HIS", "ND1", Role::Both),
+    ("HIS", "NE2", Role::Both),
+    ("LYS", "NZ", Role::Donor),
+    ("MET", "SD", Role::Acceptor),
+    ("SER", "OG", Role::Both),
+    ("THR", "OG1", Role::Both),
+    ("TRP", "NE1", Role::Donor),
+    ("TYR", "OH", Role::Both),
+    // Protonated and deprotonated variants of titratable residues.
+    ("ASH", "OD1", Role::Acceptor),
+    ("ASH", "OD2", Role::Both),
+    ("GLH", "OE1", Role::Acceptor),
+    ("GLH", "OE2", Role::Both),
+    ("HID", "ND1", Role::Donor),
+    ("HID", "NE2", Role::Acceptor),
+    ("HIE", "ND1", Role::Acceptor),
+    ("HIE", "NE2", Role::Donor),
+    ("HIP", "ND1", Role::Donor),
+    ("HIP", "NE2", Role::Donor),
+];
+
+/// The acceptors of the sugar-phosphate backbone of nucleotides, with the
+/// old names of the phosphate oxygens. The 2'-hydroxyl of ribose is both.
+const NUCLEOTIDE_BACKBONE_ROLES: [(&str, Role); 9] = [
+    ("OP1", Role::Acceptor),
+    ("OP2", Role::Acceptor),
+    ("OP3", Role::Acceptor),
+    ("O1P", Role::Acceptor),
+    ("O2P", Role::Acceptor),
+    ("O5'", Role::Acceptor),
+    ("O4'", Role::Acceptor),
+    ("O3'", Role::Acceptor),
+    ("O2'", Role::Both),
+];
+
+/// The donors and acceptors of nucleotide bases by the names of the
+/// ribonucleotides and thymidine. The glycosidic N9 of purines and N1 of
+/// pyrimidines are neither.
+const BASE_ROLES: [(&str, &str, Role); 18] = [
+    ("A", "N1", Role::Acceptor),
+    ("A", "N3", Role::Acceptor),
+    ("A", "N6", Role::Donor),
+    ("A", "N7", Role::Acceptor),
+    ("G", "N1", Role::Donor),
+    ("G", "N2", Role::Donor),
+    ("G", "N3", Role::Acceptor),
+    ("G", "O6", Role::Acceptor),
+    ("G", "N7", Role::Acceptor),
+    ("C", "O2", Role::Acceptor),
+    ("C", "N3", Role::Acceptor),
+    ("C", "N4", Role::Donor),
+    ("U", "O2", Role::Acceptor),
+    ("U", "N3", Role::Donor),
+    ("U", "O4", Role::Acceptor),
+    ("T", "O2", Role::Acceptor),
+    ("T", "N3", Role::Donor),
+    ("T", "O4", Role::Acceptor),
+];
+
+/// The roles of atoms in hydrogen bonds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role {
+    Donor,
+    Acceptor,
+    /// Both a donor and an acceptor, e.g. a hydroxyl oxygen.
+    Both,
+}
+
+impl Role {
+    /// Returns the role of an atom, or `None` if it takes part in no
+    /// hydrogen bond.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::hbond::Role;
+    /// use biost::{Atom, Vector3d};
+    /// let mut atom = Atom::new("OG", Vector3d::zero());
+    /// atom.res_name = "SER".to_string();
+    /// assert_eq!(Some(Role::Both), Role::of(&atom));
+    /// atom.name = "CB".to_string();
+    /// assert_eq!(None, Role::of(&atom));
+    /// ```
+    pub fn of(atom: &Atom) -> Option<Role> {
+        let res_name = atom.res_name.trim();
+        let name = atom.name.trim();
+        if is_amino_acid(res_name) {
+            return match name {
+                "N" if res_name == "PRO" => None,
+                "N" => Some(Role::Donor),
+                "O" | "OXT" => Some(Role::Acceptor),
+                _ => SIDE_CHAIN_ROLES
+                    .iter()
+                    .find(|&&(residue, atom, _)| residue == res_name && atom == name)
+                    .map(|&(_, _, role)| role),
+            };
+        }
+        if let Some(base) = base(res_name) {
+            let backbone = NUCLEOTIDE_BACKBONE_ROLES
+                .iter()
+                .find(|&&(atom, _)| atom == name)
+                .map(|&(_, role)| role);
+            return backbone.or_else(|| {
+                BASE_ROLES
+                    .iter()
+                    .find(|&&(residue, atom, _)| residue == base && atom == name)
+                    .map(|&(_, _, role)| role)
+            });
+        }
+        match Element::of(atom) {
+            Some(Element::N) | Some(Element::O) => Some(Role::Both),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the role is `Donor` or `Both`.
+    pub fn is_donor(&self) -> bool {
+        *self != Role::Acceptor
+    }
+
+    /// Returns whether the role is `Acceptor` or `Both`.
+    pub fn is_acceptor(&self) -> bool {
+        *self != Role::Donor
+    }
+}
+
+/// Returns whether a residue is a standard amino acid or one of the
+/// variants in `SIDE_CHAIN_ROLES`.
+fn is_amino_acid(res_name: &str) -> bool {
+    const AMINO_ACIDS: [&str; 20] = [
+        "ALA", "ARG", "ASN", "ASP", "CYS", "GLN", "GLU", "GLY", "HIS", "ILE", "LEU", "LYS", "MET",
+        "PHE", "PRO", "SER", "THR", "TRP", "TYR", "VAL",
+    ];
+    AMINO_ACIDS.contains(&res_name)
+        || SIDE_CHAIN_ROLES
+            .iter()
+            .any(|&(residue, _, _)| residue == res_name)
+}
+
+/// Returns the name of the base in `BASE_ROLES` of a standard nucleotide,
+/// either a ribonucleotide or a deoxyribonucleotide prefixed by `D`.
+fn base(res_name: &str) -> Option<&'static str> {
+    match res_name {
+        "A" | "DA" => Some("A"),
+        "G" | "DG" => Some("G"),
+        "C" | "DC" => Some("C"),
+        "U" | "DU" => Some("U"),
+        "DT" => Some("T"),
+        _ => None,
+    }
+}
+
+/// The geometric criteria of hydrogen bonds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Criteria {
+    /// The range of the donor-acceptor distance in Angstroms. The minimum
+    /// excludes atoms bonded to a common atom, e.g. the N and O atoms of
+    /// adjacent peptide units.
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// The minimum D-H...A angle in radians.
+    pub min_dha_angle: f32,
+    /// The minimum H...A-AA angle in radians, or D...A-AA angle if the
+    /// donor has no hydrogens.
+    pub min_haa_angle: f32,
+    /// Whether donors without hydrogens are ignored. Otherwise, only the
+    /// distance and the D...A-AA angle are checked for them.
+    pub require_hydrogens: bool,
+}
+
+impl Default for Criteria {
+    /// Returns the criteria of a D-A distance between 2.5 and 3.5
+    /// Angstroms, a D-H...A angle above 120 degrees and an H...A-AA angle
+    /// above 90 degrees.
+    fn default() -> Self {
+        Criteria {
+            min_distance: 2.5,
+            max_distance: 3.5,
+            min_dha_angle: 2.0 * PI / 3.0,
+            min_haa_angle: PI / 2.0,
+            require_hydrogens: false,
+        }
+    }
+}
+
+/// A hydrogen bond given by the indices of atoms of a model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HydrogenBond {
+    pub donor: usize,
+    pub hydrogen: Option<usize>,
+    pub acceptor: usize,
+    /// The donor-acceptor distance in Angstroms.
+    pub distance: f32,
+    /// The D-H...A angle in radians, if the hydrogen is known.
+    pub dha_angle: Option<f32>,
+    /// The H...A-AA angle, or D...A-AA angle without the hydrogen, in
+    /// radians, if the acceptor has an antecedent.
+    pub haa_angle: Option<f32>,
+}
+
+/// Returns the hydrogen bonds between atoms of different residues of a
+/// model, sorted by the donors and then the acceptors.
+///
+/// If a donor has several hydrogens satisfying the criteria, the one with
+/// the largest D-H...A angle is taken. Alternate locations should be
+/// removed beforehand, e.g. by `Model::retain_alt_loc`.
+///
+/// # Example
+///
+/// ```
+/// use biost::hbond::{hydrogen_bonds, Criteria};
+/// use biost::{Atom, Model, Vector3d};
+/// let atom = |name: &str, res_name: &str, res_seq: i32, position: Vector3d| {
+///     let mut atom = Atom::new(name, position);
+///     atom.res_name = res_name.to_string();
+///     atom.res_seq = res_seq;
+///     atom
+/// };
+/// let mut model = Model::new(1);
+/// model.atoms.push(atom("OG", "SER", 1, Vector3d::zero()));
+/// model.atoms.push(atom("HG", "SER", 1, Vector3d::new(0.96, 0.0, 0.0)));
+/// model.atoms.push(atom("O", "HOH", 2, Vector3d::new(2.8, 0.0, 0.0)));
+///
+/// // The water donates to the hydroxyl in turn, as its hydrogens are unknown.
+/// let bonds = hydrogen_bonds(&model, &Criteria::default());
+/// assert_eq!(2, bonds.len());
+/// assert_eq!((0, Some(1), 2), (bonds[0].donor, bonds[0].hydrogen, bonds[0].acceptor));
+/// assert!((bonds[0].distance - 2.8).abs() < 1e-5);
+/// ```
+pub fn hydrogen_bonds(model: &Model, criteria: &Criteria) -> Vec<HydrogenBond> {
+    let atoms = &model.atoms;
+    let mut residues = vec![0..0; atoms.len()];
+    for residue in model.residues() {
+        let range = residue.atom_indices();
+        for i in range.clone() {
+            residues[i] = range.clone();
+        }
+    }
+    let roles: Vec<Option<Role>> = atoms.iter().map(Role::of).collect();
+
+    let hydrogens = |donor: usize| -> Vec<usize> {
+        residues[donor]
+            .clone()
+            .filter(|&i| {
+                Element::is_hydrogen_atom(&atoms[i])
+                    && atoms[i].position.distance(&atoms[donor].position)
+                        <= MAX_HYDROGEN_BOND_LENGTH
+            })
+            .collect()
+    };
+    let antecedent = |acceptor: usize| -> Option<usize> {
+        let position = &atoms[acceptor].position;
+        residues[acceptor]
+            .clone()
+            .filter(|&i| i != acceptor && !Element::is_hydrogen_atom(&atoms[i]))
+            .map(|i| (i, atoms[i].position.distance(position)))
+            .filter(|&(_, distance)| distance <= MAX_COVALENT_BOND)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    };
+
+    let positions: Vec<Vector3d> = atoms.iter().map(|atom| atom.position).collect();
+    // Atoms at non-finite positions are left out of the grid, which would
+    // otherwise not be built at all.
+    let finite: Vec<usize> = (0..positions.len())
+        .filter(|&i| {
+            let p = &positions[i];
+            p.x.is_finite() && p.y.is_finite() && p.z.is_finite()
+        })
+        .collect();
+    let finite_positions: Vec<Vector3d> = finite.iter().map(|&i| positions[i]).collect();
+    let grid = match Grid::new(&finite_positions, criteria.max_distance.max(f32::EPSILON)) {
+        Some(grid) => grid,
+        None => return Vec::new(),
+    };
+    let mut bonds = Vec::new();
+    for donor in 0..atoms.len() {
+        if !roles[donor].is_some_and(|role| role.is_donor()) {
+            continue;
+        }
+        let hydrogens = hydrogens(donor);
+        if hydrogens.is_empty() && criteria.require_hydrogens {
+            continue;
+        }
+        let d = &positions[donor];
+        for acceptor in grid.within(d, criteria.max_distance) {
+            let acceptor = finite[acceptor];
+            if residues[acceptor] == residues[donor]
+                || !roles[acceptor].is_some_and(|role| role.is_acceptor())
+            {
+                continue;
+            }
+            let a = &positions[acceptor];
+            let distance = d.distance(a);
+            if distance < criteria.min_distance {
+                continue;
+            }
+            let hydrogen = if hydrogens.is_empty() {
+                None
+            } else {
+                let best = hydrogens
+                    .iter()
+                    .map(|&h| (h, (*d - positions[h]).angle(&(*a - positions[h]))))
+                    .filter(|&(_, angle)| angle >= criteria.min_dha_angle)
+                    .max_by(|a, b| a.1.total_cmp(&b.1));
+                match best {
+                    Some(best) => Some(best),
+                    None => continue,
+                }
+            };
+            let h = hydrogen.map_or(*d, |(h, _)| positions[h]);
+            let haa_angle = antecedent(acceptor).map(|aa| (h - *a).angle(&(positions[aa] - *a)));
+            if haa_angle.is_some_and(|angle| angle < criteria.min_haa_angle) {
+                continue;
+            }
+            bonds.push(HydrogenBond {
+                donor,
+                hydrogen: hydrogen.map(|(h, _)| h),
+                acceptor,
+                distance,
+                dha_angle: hydrogen.map(|(_, angle)| angle),
+                haa_angle,
+            });
+        }
+    }
+    bonds
+}
+
+/// An atom of a model labeled by its chain, residue and name.
+struct Label<'a>(&'a Atom);
+
+impl<'a> fmt::Display for Label<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let atom = self.0;
+        let residue = format!(
+            "{}{}",
+            atom.res_seq,
+            atom.i_code
+                .map(|i_code| i_code.to_string())
+                .unwrap_or_default()
+        );
+        write!(
+            f,
+            "{:>2} {:>5} {:>3} {:<4}",
+            atom.chain_id,
+            residue,
+            atom.res_name,
+            atom.name.trim()
+        )
+    }
+}
+
+/// Writes a table of hydrogen bonds between atoms of a model, one bond per
+/// line with the donor, hydrogen and acceptor, the D-A distance and the
+/// angles in degrees. Missing hydrogens and angles are written as `-`.
+///
+/// # Example
+///
+/// ```
+/// use biost::hbond::{write_table, HydrogenBond};
+/// use biost::{Atom, Model, Vector3d};
+/// let mut model = Model::new(1);
+/// for (name, res_seq) in [("N", 1), ("O", 7)] {
+///     let mut atom = Atom::new(name, Vector3d::zero());
+///     atom.res_name = "GLY".to_string();
+///     atom.chain_id = "A".to_string();
+///     atom.res_seq = res_seq;
+///     model.atoms.push(atom);
+/// }
+/// let bond = HydrogenBond {
+///     donor: 0,
+///     hydrogen: None,
+///     acceptor: 1,
+///     distance: 2.9,
+///     dha_angle: None,
+///     haa_angle: Some(2.5),
+/// };
+///
+/// let mut buffer = Vec::new();
+/// write_table(&mut buffer, &model, &[bond]).unwrap();
+/// let text = String::from_utf8(buffer).unwrap();
+/// assert_eq!(
+///     " A     1 GLY N    -                  A     7 GLY O     2.90       -   143.2",
+///     text.lines().nth(1).unwrap()
+/// );
+/// ```
+///
+/// # Panics
+///
+/// Panics if an index of a bond is out of the range of the atoms.
+pub fn write_table<W: Write>(
+    mut writer: W,
+    model: &Model,
+    bonds: &[HydrogenBond],
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        "{:<17} {:<17} {:<17} {:>5} {:>7} {:>7}",
+        "donor", "hydrogen", "acceptor", "D-A", "D-H-A", "H-A-AA"
+    )?;
+    let angle = |angle: Option<f32>| {
+        angle.map_or_else(
+            || "-".to_string(),
+            |angle| format!("{:.1}", angle.to_degrees()),
+        )
+    };
+    for bond in bonds {
+        let hydrogen = bond
+            .hydrogen
+            .map_or_else(|| "-".to_string(), |h| Label(&model.atoms[h]).to_string());
+        writeln!(
+            writer,
+            "{} {:<17} {} {:5.2} {:>7} {:>7}",
+            Label(&model.atoms[bond.donor]),
+            hydrogen,
+            Label(&model.atoms[bond.acceptor]),
+            bond.distance,
+            angle(bond.dha_angle),
+            angle(bond.haa_angle)
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atom(name: &str, res_name: &str, res_seq: i32, position: Vector3d) -> Atom {
+        let mut atom = Atom::new(name, position);
+        atom.res_name = res_name.to_string();
+        atom.chain_id = "A".to_string();
+        atom.res_seq = res_seq;
+        atom
+    }
+
+    #[test]
+    fn test_role() {
+        let mut n = atom("N", "GLY", 1, Vector3d::zero());
+        assert_eq!(Some(Role::Donor), Role::of(&n));
+        n.res_name = "PRO".to_string();
+        assert_eq!(None, Role::of(&n));
+        assert_eq!(
+            Some(Role::Acceptor),
+            Role::of(&atom("OXT", "ALA", 1, Vector3d::zero()))
+        );
+        assert_eq!(
+            Some(Role::Both),
+            Role::of(&atom("O", "HOH", 1, Vector3d::zero()))
+        );
+
+        let mut ligand = atom("N1", "ATP", 1, Vector3d::zero());
+        ligand.element = "N".to_string();
+        assert_eq!(Some(Role::Both), Role::of(&ligand));
+        ligand.name = "C1".to_string();
+        ligand.element = "C".to_string();
+        assert_eq!(None, Role::of(&ligand));
+        // An element is guessed from the atom name if it is blank.
+        ligand.name = "O2'".to_string();
+        ligand.element = String::new();
+        assert_eq!(Some(Role::Both), Role::of(&ligand));
+
+        // Protonation variants of histidine, aspartate and glutamate.
+        let role =
+            |name: &str, res_name: &str| Role::of(&atom(name, res_name, 1, Vector3d::zero()));
+        assert_eq!(Some(Role::Donor), role("ND1", "HID"));
+        assert_eq!(Some(Role::Acceptor), role("NE2", "HID"));
+        assert_eq!(Some(Role::Acceptor), role("ND1", "HIE"));
+        assert_eq!(Some(Role::Donor), role("NE2", "HIE"));
+        assert_eq!(Some(Role::Donor), role("ND1", "HIP"));
+        assert_eq!(Some(Role::Donor), role("NE2", "HIP"));
+        assert_eq!(Some(Role::Acceptor), role("OD1", "ASH"));
+        assert_eq!(Some(Role::Both), role("OD2", "ASH"));
+        assert_eq!(Some(Role::Acceptor), role("OE1", "GLH"));
+        assert_eq!(Some(Role::Both), role("OE2", "GLH"));
+        assert_eq!(Some(Role::Donor), role("N", "HIP"));
+
+        // Nucleotides, whose glycosidic nitrogens are neither.
+        assert_eq!(None, role("N9", "A"));
+        assert_eq!(None, role("N9", "DG"));
+        assert_eq!(None, role("N1", "C"));
+        assert_eq!(None, role("N1", "DT"));
+        assert_eq!(Some(Role::Acceptor), role("N7", "DA"));
+        assert_eq!(Some(Role::Acceptor), role("N7", "G"));
+        assert_eq!(Some(Role::Donor), role("N1", "G"));
+        assert_eq!(Some(Role::Acceptor), role("N1", "A"));
+        assert_eq!(Some(Role::Donor), role("N6", "A"));
+        assert_eq!(Some(Role::Donor), role("N4", "DC"));
+        assert_eq!(Some(Role::Donor), role("N3", "U"));
+        assert_eq!(Some(Role::Acceptor), role("O4", "DT"));
+        assert_eq!(Some(Role::Acceptor), role("OP1", "DC"));
+        assert_eq!(Some(Role::Acceptor), role("O3'", "U"));
+        assert_eq!(Some(Role::Both), role("O2'", "U"));
+        assert_eq!(None, role("C7", "DT"));
+
+        assert!(Role::Both.is_donor() && Role::Both.is_acceptor());
+        assert!(!Role::Donor.is_acceptor() && !Role::Acceptor.is_donor());
+    }
+
+    /// Returns a backbone N-H of a residue donating to the C=O of another
+    /// along the x axis, with the D-H...A angle bent by `bend` radians.
+    fn backbone_pair(distance: f32, bend: f32) -> Model {
+        let h = Vector3d::new(1.01, 0.0, 0.0);
+        let direction = Vector3d::new(-bend.cos(), bend.sin(), 0.0);
+        let o = h - direction * (distance - 1.01);
+        let mut model = Model::new(1);
+        model.atoms.push(atom("N", "ALA", 1, Vector3d::zero()));
+        model.atoms.push(atom("H", "ALA", 1, h));
+        model
+            .atoms
+            .push(atom("CA", "ALA", 1, Vector3d::new(-0.8, 1.1, 0.0)));
+        model.atoms.push(atom("O", "GLY", 5, o));
+        model
+            .atoms
+            .push(atom("C", "GLY", 5, o + Vector3d::new(1.23, 0.0, 0.0)));
+        model
+    }
+
+    #[test]
+    fn test_hydrogen_bonds() {
+        let model = backbone_pair(2.9, 0.0);
+        let bonds = hydrogen_bonds(&model, &Criteria::default());
+        assert_eq!(1, bonds.len());
+        let bond = bonds[0];
+        assert_eq!((0, Some(1), 3), (bond.donor, bond.hydrogen, bond.acceptor));
+        assert!((bond.distance - 2.9).abs() < 1e-5);
+        assert!((bond.dha_angle.unwrap() - PI).abs() < 1e-3);
+        assert!((bond.haa_angle.unwrap() - PI).abs() < 1e-3);
+
+        let far = backbone_pair(3.6, 0.0);
+        assert!(hydrogen_bonds(&far, &Criteria::default()).is_empty());
+        let criteria = Criteria {
+            max_distance: 4.0,
+            ..Criteria::default()
+        };
+        assert_eq!(1, hydrogen_bonds(&far, &criteria).len());
+    }
+
+    #[test]
+    fn test_hydrogen_bonds_angles() {
+        // A D-H...A angle of about 111 degrees.
+        let bent = backbone_pair(3.3, 1.2);
+        assert!(hydrogen_bonds(&bent, &Criteria::default()).is_empty());
+        let criteria = Criteria {
+            min_dha_angle: 100f32.to_radians(),
+            ..Criteria::default()
+        };
+        let bonds = hydrogen_bonds(&bent, &criteria);
+        assert_eq!(1, bonds.len());
+        assert!((bonds[0].dha_angle.unwrap().to_degrees() - 111.2).abs() < 0.1);
+
+        // The acceptor pointing away from the hydrogen.
+        let mut model = backbone_pair(2.9, 0.0);
+        let o = model.atoms[3].position;
+        model.atoms[4].position = o - Vector3d::new(1.23, 0.0, 0.0);
+        assert!(hydrogen_bonds(&model, &Criteria::default()).is_empty());
+        let criteria = Criteria {
+            min_haa_angle: 0.0,
+            ..Criteria::default()
+        };
+        let bonds = hydrogen_bonds(&model, &criteria);
+        assert!(bonds[0].haa_angle.unwrap().abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_hydrogen_bonds_without_hydrogens() {
+        let mut model = backbone_pair(2.9, 0.0);
+        model.atoms.remove(1);
+        let bonds = hydrogen_bonds(&model, &Criteria::default());
+        assert_eq!(1, bonds.len());
+        assert_eq!(
+            (0, None, 2),
+            (bonds[0].donor, bonds[0].hydrogen, bonds[0].acceptor)
+        );
+        assert_eq!(None, bonds[0].dha_angle);
+        assert!((bonds[0].haa_angle.unwrap() - PI).abs() < 1e-3);
+
+        let criteria = Criteria {
+            require_hydrogens: true,
+            ..Criteria::default()
+        };
+        assert!(hydrogen_bonds(&model, &criteria).is_empty());
+    }
+
+    #[test]
+    fn test_hydrogen_bonds_waters() {
+        let mut model = Model::new(1);
+        model.atoms.push(atom("O", "HOH", 1, Vector3d::zero()));
+        model
+            .atoms
+            .push(atom("O", "HOH", 2, Vector3d::new(2.8, 0.0, 0.0)));
+        model
+            .atoms
+            .push(atom("O", "HOH", 3, Vector3d::new(2.0, 0.0, 0.0)));
+        let bonds: Vec<(usize, usize)> = hydrogen_bonds(&model, &Criteria::default())
+            .iter()
+            .map(|bond| (bond.donor, bond.acceptor))
+            .collect();
+        // Waters both donate and accept, and the third is too close to the
+        // first.
+        assert_eq!(vec![(0, 1), (1, 0)], bonds);
+        assert!(hydrogen_bonds(&Model::new(1), &Criteria::default()).is_empty());
+    }
+
+    #[test]
+    fn test_hydrogen_bonds_same_residue() {
+        let mut model = Model::new(1);
+        model.atoms.push(atom("N", "SER", 1, Vector3d::zero()));
+        model
+            .atoms
+            .push(atom("OG", "SER", 1, Vector3d::new(2.9, 0.0, 0.0)));
+        assert!(hydrogen_bonds(&model, &Criteria::default()).is_empty());
+        model.atoms[1].res_seq = 2;
+        assert_eq!(1, hydrogen_bonds(&model, &Criteria::default()).len());
+    }
+
+    #[test]
+    fn test_hydrogen_bonds_non_finite_position() {
+        let mut model = backbone_pair(2.9, 0.0);
+        model
+            .atoms
+            .push(atom("O", "HOH", 9, Vector3d::new(f32::NAN, 0.0, 0.0)));
+        let bonds = hydrogen_bonds(&model, &Criteria::default());
+        assert_eq!(1, bonds.len());
+        assert_eq!((0, 3), (bonds[0].donor, bonds[0].acceptor));
+    }
+
+    #[test]
+    fn test_write_table() {
+        let model = backbone_pair(2.9, 0.0);
+        let bonds = hydrogen_bonds(&model, &Criteria::default());
+        let mut buffer = Vec::new();
+        write_table(&mut buffer, &model, &bonds).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            vec![
+                "donor             hydrogen          acceptor            D-A   D-H-A  H-A-AA",
+                " A     1 ALA N     A     1 ALA H     A     5 GLY O     2.90   180.0   180.0",
+            ],
+            lines
+        );
+    }
+}
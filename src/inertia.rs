@@ -0,0 +1,349 @@
+//! Centers, moments of inertia and shape descriptors of sets of
+//! coordinates.
+//!
+//! Functions taking masses weight each position by its mass, or all
+//! equally if the masses are `None`. They return `None` if the positions
+//! are empty, or if the number of masses differs from that of positions or
+//! the masses do not sum up to a positive value.
+
+use float::Float;
+use matrix::Matrix3;
+use Vector3d;
+
+/// Positions paired with their masses.
+type Weighted<T> = Vec<(Vector3d<T>, T)>;
+
+/// Returns the positions relative to their center and the masses, all one
+/// if not given, with the total mass.
+fn centered<T: Float>(positions: &[Vector3d<T>], masses: Option<&[T]>) -> Option<(Weighted<T>, T)> {
+    let center = centroid(positions, masses)?;
+    let weighted: Weighted<T> = match masses {
+        Some(masses) => positions
+            .iter()
+            .map(|&position| position - center)
+            .zip(masses.iter().cloned())
+            .collect(),
+        None => positions
+            .iter()
+            .map(|&position| (position - center, T::one()))
+            .collect(),
+    };
+    let total = weighted.iter().map(|&(_, mass)| mass).sum();
+    Some((weighted, total))
+}
+
+/// Returns the center of positions weighted by their masses, i.e. the
+/// center of mass, or the geometric center if the masses are `None`.
+///
+/// # Example
+///
+/// ```
+/// use biost::inertia::centroid;
+/// use biost::Vector3d;
+/// let positions = [Vector3d::new(0.0, 0.0, 0.0), Vector3d::new(4.0, 0.0, 0.0)];
+/// assert_eq!(Some(Vector3d::new(2.0, 0.0, 0.0)), centroid(&positions, None));
+/// assert_eq!(Some(Vector3d::new(3.0, 0.0, 0.0)), centroid(&positions, Some(&[1.0, 3.0])));
+/// assert_eq!(None, centroid(&positions, Some(&[1.0])));
+/// ```
+pub fn centroid<T: Float>(positions: &[Vector3d<T>], masses: Option<&[T]>) -> Option<Vector3d<T>> {
+    let (sum, total): (Vector3d<T>, T) = match masses {
+        Some(masses) if masses.len() != positions.len() => return None,
+        Some(masses) => (
+            positions
+                .iter()
+                .zip(masses)
+                .map(|(&position, &mass)| position * mass)
+                .sum(),
+            masses.iter().cloned().sum(),
+        ),
+        None => (positions.iter().sum(), T::from_f64(positions.len() as f64)),
+    };
+    if total > T::zero() {
+        Some(sum / total)
+    } else {
+        None
+    }
+}
+
+/// Returns the radius of gyration, the root-mean-square distance of
+/// positions from their center.
+///
+/// # Example
+///
+/// ```
+/// use biost::inertia::radius_of_gyration;
+/// use biost::Vector3d;
+/// let positions = [Vector3d::new(-1.0, 0.0, 0.0), Vector3d::new(1.0, 0.0, 0.0)];
+/// assert_eq!(Some(1.0), radius_of_gyration(&positions, None));
+/// assert_eq!(Some(0.8), radius_of_gyration(&positions, Some(&[1.0, 4.0])));
+/// ```
+pub fn radius_of_gyration<T: Float>(positions: &[Vector3d<T>], masses: Option<&[T]>) -> Option<T> {
+    let (centered, total) = centered(positions, masses)?;
+    let sum: T = centered
+        .iter()
+        .map(|&(position, mass)| position.norm_squared() * mass)
+        .sum();
+    Some((sum / total).sqrt())
+}
+
+/// Returns the inertia tensor about the center of positions,
+/// `sum m (|r|^2 E - r r^T)`.
+///
+/// # Example
+///
+/// ```
+/// use biost::inertia::inertia_tensor;
+/// use biost::{Matrix3, Vector3d};
+/// let positions = [Vector3d::new(-1.0, 0.0, 0.0), Vector3d::new(1.0, 0.0, 0.0)];
+/// let tensor = inertia_tensor(&positions, Some(&[2.0, 2.0])).unwrap();
+/// assert_eq!(Matrix3::diagonal(0.0, 4.0, 4.0), tensor);
+/// ```
+pub fn inertia_tensor<T: Float>(
+    positions: &[Vector3d<T>],
+    masses: Option<&[T]>,
+) -> Option<Matrix3<T>> {
+    let (centered, _) = centered(positions, masses)?;
+    let mut tensor = Matrix3::zero();
+    for (r, mass) in centered {
+        let squared = r.norm_squared();
+        tensor =
+            tensor + (Matrix3::diagonal(squared, squared, squared) - Matrix3::outer(&r, &r)) * mass;
+    }
+    Some(tensor)
+}
+
+/// Returns the principal moments of inertia in descending order, as the
+/// moments of `Gyration`, and the corresponding principal axes as the
+/// columns of a rotation matrix.
+///
+/// # Example
+///
+/// ```
+/// use biost::inertia::principal_axes;
+/// use biost::Vector3d;
+/// let positions: [Vector3d; 4] = [
+///     Vector3d::new(-2.0, -1.0, 0.0),
+///     Vector3d::new(2.0, 1.0, 0.0),
+///     Vector3d::new(-1.0, 2.0, 0.0),
+///     Vector3d::new(1.0, -2.0, 0.0),
+/// ];
+/// let (moments, axes) = principal_axes(&positions, None).unwrap();
+/// assert!((moments - Vector3d::new(20.0, 10.0, 10.0)).norm() < 1e-5);
+/// // The axis of the largest moment is normal to the plane of positions.
+/// assert!((axes.column(0).z.abs() - 1.0).abs() < 1e-5);
+/// ```
+pub fn principal_axes<T: Float>(
+    positions: &[Vector3d<T>],
+    masses: Option<&[T]>,
+) -> Option<(Vector3d<T>, Matrix3<T>)> {
+    let (moments, vectors) = inertia_tensor(positions, masses)?.symmetric_eigen();
+    let (first, second) = (vectors.column(0), vectors.column(1));
+    Some((
+        moments,
+        Matrix3::from_columns(first, second, first.cross(&second)),
+    ))
+}
+
+/// The principal components of the gyration tensor,
+/// `sum m r r^T / sum m` about the center of positions, which describe the
+/// shape of the positions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gyration<T: Float = f32> {
+    /// The eigenvalues in descending order, the squared lengths along the
+    /// principal axes.
+    pub moments: Vector3d<T>,
+    /// The principal axes as the columns of a rotation matrix.
+    pub axes: Matrix3<T>,
+}
+
+impl<T: Float> Gyration<T> {
+    /// Computes the gyration tensor of positions and its principal
+    /// components.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::inertia::Gyration;
+    /// use biost::Vector3d;
+    /// // The vertices of a regular tetrahedron are spherically symmetric.
+    /// let positions: [Vector3d; 4] = [
+    ///     Vector3d::new(1.0, 1.0, 1.0),
+    ///     Vector3d::new(1.0, -1.0, -1.0),
+    ///     Vector3d::new(-1.0, 1.0, -1.0),
+    ///     Vector3d::new(-1.0, -1.0, 1.0),
+    /// ];
+    /// let gyration = Gyration::new(&positions, None).unwrap();
+    /// assert!((gyration.radius_of_gyration() - 3f32.sqrt()).abs() < 1e-5);
+    /// assert!(gyration.asphericity().abs() < 1e-5);
+    /// assert!(gyration.anisotropy().abs() < 1e-5);
+    ///
+    /// // Collinear positions are the most anisotropic.
+    /// let rod: [Vector3d; 2] = [Vector3d::new(0.0, 0.0, -1.0), Vector3d::new(0.0, 0.0, 1.0)];
+    /// let gyration = Gyration::new(&rod, None).unwrap();
+    /// assert!((gyration.anisotropy() - 1.0).abs() < 1e-5);
+    /// ```
+    pub fn new(positions: &[Vector3d<T>], masses: Option<&[T]>) -> Option<Self> {
+        let (centered, total) = centered(positions, masses)?;
+        let mut tensor = Matrix3::zero();
+        for (r, mass) in centered {
+            tensor = tensor + Matrix3::outer(&r, &r) * mass;
+        }
+        let (moments, axes) = (tensor / total).symmetric_eigen();
+        Some(Gyration { moments, axes })
+    }
+
+    /// Returns the radius of gyration, the square root of the trace.
+    pub fn radius_of_gyration(&self) -> T {
+        (self.moments.x + self.moments.y + self.moments.z).sqrt()
+    }
+
+    /// Returns the asphericity `l1 - (l2 + l3) / 2`, where `l1 >= l2 >= l3`
+    /// are the moments, which is zero for spherically symmetric positions.
+    pub fn asphericity(&self) -> T {
+        let Vector3d { x, y, z } = self.moments;
+        x - (y + z) / T::from_f32(2.0)
+    }
+
+    /// Returns the acylindricity `l2 - l3`, which is zero for positions
+    /// symmetric about the first principal axis.
+    pub fn acylindricity(&self) -> T {
+        self.moments.y - self.moments.z
+    }
+
+    /// Returns the relative shape anisotropy
+    /// `1 - 3 (l1 l2 + l2 l3 + l3 l1) / (l1 + l2 + l3)^2`, which ranges from
+    /// zero for spherically symmetric positions to one for collinear ones.
+    /// It is zero also if all positions coincide.
+    pub fn anisotropy(&self) -> T {
+        let Vector3d { x, y, z } = self.moments;
+        let trace = x + y + z;
+        if trace <= T::zero() {
+            return T::zero();
+        }
+        T::one() - T::from_f32(3.0) * (x * y + y * z + z * x) / (trace * trace)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rotation::Rotation;
+
+    fn sample() -> Vec<Vector3d<f64>> {
+        vec![
+            Vector3d::new(1.0, 2.0, 3.0),
+            Vector3d::new(-2.0, 0.5, 1.0),
+            Vector3d::new(0.0, -1.0, 4.0),
+            Vector3d::new(3.0, 3.0, -2.0),
+            Vector3d::new(-1.0, 2.5, 0.0),
+        ]
+    }
+
+    #[test]
+    fn test_centers() {
+        let positions = sample();
+        let masses = [12.0, 14.0, 16.0, 1.0, 32.0];
+        let center = centroid(&positions, Some(&masses)).unwrap();
+        let expected = Vector3d::new(-45.0 / 75.0, 98.0 / 75.0, 112.0 / 75.0);
+        assert!((center - expected).norm() < 1e-12);
+        assert_eq!(
+            centroid(&positions, None),
+            centroid(&positions, Some(&[1.0; 5]))
+        );
+        assert_eq!(
+            Some(Vector3d::new(0.2, 1.4, 1.2)),
+            centroid(&positions, None)
+        );
+
+        assert_eq!(None, centroid::<f64>(&[], None));
+        assert_eq!(None, centroid::<f64>(&[], Some(&[])));
+        assert_eq!(None, centroid(&positions, Some(&masses[1..])));
+        assert_eq!(None, centroid(&positions, Some(&[0.0; 5])));
+        assert_eq!(None, centroid(&positions, Some(&[f64::NAN; 5])));
+    }
+
+    #[test]
+    fn test_radius_of_gyration() {
+        let positions = sample();
+        let masses = [12.0, 14.0, 16.0, 1.0, 32.0];
+        let center = centroid(&positions, Some(&masses)).unwrap();
+        let sum: f64 = positions
+            .iter()
+            .zip(masses.iter())
+            .map(|(p, m)| p.distance_squared(&center) * m)
+            .sum();
+        let expected = (sum / 75.0).sqrt();
+        let rg = radius_of_gyration(&positions, Some(&masses)).unwrap();
+        assert!((rg - expected).abs() < 1e-12);
+        let gyration = Gyration::new(&positions, Some(&masses)).unwrap();
+        assert!((gyration.radius_of_gyration() - expected).abs() < 1e-12);
+
+        assert_eq!(None, radius_of_gyration(&positions, Some(&masses[1..])));
+        assert_eq!(None, radius_of_gyration::<f64>(&[], None));
+        assert_eq!(Some(0.0), radius_of_gyration(&positions[..1], None));
+    }
+
+    #[test]
+    fn test_principal_axes() {
+        let positions = sample();
+        let masses = [12.0, 14.0, 16.0, 1.0, 32.0];
+        let tensor = inertia_tensor(&positions, Some(&masses)).unwrap();
+        let (moments, axes) = principal_axes(&positions, Some(&masses)).unwrap();
+        assert!(moments.x >= moments.y && moments.y >= moments.z);
+        assert!((axes.determinant() - 1.0).abs() < 1e-10);
+        for (i, moment) in [moments.x, moments.y, moments.z].iter().enumerate() {
+            let axis = axes.column(i);
+            assert!((tensor * axis - axis * *moment).norm() < 1e-8);
+        }
+        // The trace of the inertia tensor is twice the second moment.
+        let rg = radius_of_gyration(&positions, Some(&masses)).unwrap();
+        assert!((tensor.trace() - 2.0 * 75.0 * rg * rg).abs() < 1e-8);
+        // The moments of a rod along the x axis.
+        let rod = [Vector3d::new(-1.0, 0.0, 0.0), Vector3d::new(1.0, 0.0, 0.0)];
+        let (moments, axes) = principal_axes(&rod, None).unwrap();
+        assert!((moments - Vector3d::new(2.0, 2.0, 0.0)).norm() < 1e-12);
+        assert!((axes.column(2).x.abs() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_invariance() {
+        let positions = sample();
+        let masses = [12.0, 14.0, 16.0, 1.0, 32.0];
+        let rotation = Rotation::from_axis_angle(&Vector3d::new(1.0, -2.0, 0.5), 0.7).unwrap();
+        let moved: Vec<Vector3d<f64>> = positions
+            .iter()
+            .map(|p| rotation.rotate(p) + Vector3d::new(5.0, -3.0, 8.0))
+            .collect();
+
+        let (moments, _) = principal_axes(&positions, Some(&masses)).unwrap();
+        let (moved_moments, _) = principal_axes(&moved, Some(&masses)).unwrap();
+        assert!((moments - moved_moments).norm() < 1e-8);
+
+        let gyration = Gyration::new(&positions, Some(&masses)).unwrap();
+        let moved_gyration = Gyration::new(&moved, Some(&masses)).unwrap();
+        assert!((gyration.asphericity() - moved_gyration.asphericity()).abs() < 1e-8);
+        assert!((gyration.acylindricity() - moved_gyration.acylindricity()).abs() < 1e-8);
+        assert!((gyration.anisotropy() - moved_gyration.anisotropy()).abs() < 1e-8);
+    }
+
+    #[test]
+    fn test_shape() {
+        // A flat square is symmetric about its normal.
+        let square = [
+            Vector3d::new(1.0, 1.0, 0.0),
+            Vector3d::new(1.0, -1.0, 0.0),
+            Vector3d::new(-1.0, 1.0, 0.0),
+            Vector3d::new(-1.0, -1.0, 0.0),
+        ];
+        let gyration = Gyration::new(&square, None).unwrap();
+        assert!((gyration.moments - Vector3d::new(1.0, 1.0, 0.0)).norm() < 1e-6);
+        assert!((gyration.asphericity() - 0.5).abs() < 1e-6);
+        assert!((gyration.acylindricity() - 1.0).abs() < 1e-6);
+        assert!((gyration.anisotropy() - 0.25).abs() < 1e-6);
+        assert!((gyration.axes.column(2).z.abs() - 1.0).abs() < 1e-6);
+
+        let point = Gyration::new(&[Vector3d::new(1.0, 2.0, 3.0)], None).unwrap();
+        assert_eq!(0.0, point.anisotropy());
+        assert_eq!(None, Gyration::<f32>::new(&[], None));
+    }
+}
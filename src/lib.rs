@@ -1,13 +1,56 @@
+use std::iter;
 use std::ops;
 
-#[derive(Debug, Clone, Copy)]
-pub struct Vector3d {
-    pub x: f32,
-    pub y: f32,
-    pub z: f32,
+pub mod alignment;
+pub mod atom;
+pub mod cif;
+pub mod contact;
+pub mod dssp;
+pub mod element;
+pub mod float;
+pub mod hbond;
+pub mod inertia;
+pub mod matrix;
+pub mod neighbor;
+pub mod pdb;
+pub mod periodic;
+pub mod quaternion;
+pub mod ramachandran;
+pub mod rmsd;
+pub mod rotation;
+pub mod sasa;
+pub mod selection;
+pub mod similarity;
+pub mod structure;
+pub mod superposition;
+pub mod torsion;
+pub mod transform;
+pub mod zmatrix;
+
+pub use atom::Atom;
+pub use element::Element;
+pub use float::Float;
+pub use matrix::Matrix3;
+pub use quaternion::Quaternion;
+pub use rmsd::{deviations, fitted_rmsd, rmsd, rmsf, weighted_rmsd};
+pub use rotation::{EulerConvention, Rotation};
+pub use selection::Selection;
+pub use structure::{Chain, Model, Residue, Structure};
+pub use torsion::dihedral;
+pub use transform::Transform;
+
+/// A three dimensional vector whose components are of type `T`.
+///
+/// The component type defaults to `f32`; `Vector3d<f64>` can be used
+/// where accumulated rounding errors matter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vector3d<T: Float = f32> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
 }
 
-impl Vector3d {
+impl<T: Float> Vector3d<T> {
     /// Returns a three dimensional vector with given coordinates
     ///
     /// # Arguments
@@ -25,8 +68,8 @@ impl Vector3d {
     /// assert_eq!(2.0, vector.y);
     /// assert_eq!(3.0, vector.z);
     /// ```
-    pub fn new(x: f32, y: f32, z: f32) -> Self {
-        Vector3d { x: x, y: y, z: z }
+    pub fn new(x: T, y: T, z: T) -> Self {
+        Vector3d { x, y, z }
     }
 
     /// Returns a three dimensional vector at the origin.
@@ -35,24 +78,203 @@ impl Vector3d {
     ///
     /// ```
     /// use biost::Vector3d;
-    /// let zero = Vector3d::zero();
+    /// let zero: Vector3d = Vector3d::zero();
     /// assert_eq!(0.0, zero.x);
     /// assert_eq!(0.0, zero.y);
     /// assert_eq!(0.0, zero.z);
     /// ```
     pub fn zero() -> Self {
-        Self::new(0.0, 0.0, 0.0)
+        Self::new(T::zero(), T::zero(), T::zero())
+    }
+
+    /// Returns the dot product of two vectors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Vector3d;
+    /// let a = Vector3d::new(1.0, 2.0, 3.0);
+    /// let b = Vector3d::new(4.0, 5.0, 6.0);
+    /// assert_eq!(32.0, a.dot(&b));
+    /// ```
+    pub fn dot(&self, vector: &Self) -> T {
+        self.x * vector.x + self.y * vector.y + self.z * vector.z
+    }
+
+    /// Returns the cross product of two vectors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Vector3d;
+    /// let x = Vector3d::new(1.0, 0.0, 0.0);
+    /// let y = Vector3d::new(0.0, 1.0, 0.0);
+    /// assert_eq!(Vector3d::new(0.0, 0.0, 1.0), x.cross(&y));
+    /// ```
+    pub fn cross(&self, vector: &Self) -> Self {
+        Self::new(
+            self.y * vector.z - self.z * vector.y,
+            self.z * vector.x - self.x * vector.z,
+            self.x * vector.y - self.y * vector.x,
+        )
+    }
+
+    /// Returns the squared Euclidean norm of the vector.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Vector3d;
+    /// let vector = Vector3d::new(1.0, 2.0, 2.0);
+    /// assert_eq!(9.0, vector.norm_squared());
+    /// ```
+    pub fn norm_squared(&self) -> T {
+        self.dot(self)
+    }
+
+    /// Returns the Euclidean norm of the vector.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Vector3d;
+    /// let vector = Vector3d::new(1.0, 2.0, 2.0);
+    /// assert_eq!(3.0, vector.norm());
+    /// ```
+    pub fn norm(&self) -> T {
+        self.norm_squared().sqrt()
+    }
+
+    /// Returns the unit vector in the same direction.
+    ///
+    /// The components of the result are NaN for a zero-length vector;
+    /// use `try_normalize` when the input may be degenerate.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Vector3d;
+    /// let unit = Vector3d::new(0.0, 3.0, 4.0).normalize();
+    /// assert_eq!(Vector3d::new(0.0, 0.6, 0.8), unit);
+    /// ```
+    pub fn normalize(&self) -> Self {
+        *self / self.norm()
+    }
+
+    /// Returns the unit vector in the same direction, or `None` if the
+    /// vector is of zero length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Vector3d;
+    /// assert!(Vector3d::<f32>::zero().try_normalize().is_none());
+    /// let unit = Vector3d::new(0.0, 3.0, 4.0).try_normalize().unwrap();
+    /// assert_eq!(Vector3d::new(0.0, 0.6, 0.8), unit);
+    /// ```
+    pub fn try_normalize(&self) -> Option<Self> {
+        let norm = self.norm();
+        if norm > T::zero() && norm.is_finite() {
+            Some(*self / norm)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the squared distance between two points.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Vector3d;
+    /// let a = Vector3d::new(1.0, 1.0, 1.0);
+    /// let b = Vector3d::new(2.0, 3.0, 3.0);
+    /// assert_eq!(9.0, a.distance_squared(&b));
+    /// ```
+    pub fn distance_squared(&self, vector: &Self) -> T {
+        (*self - *vector).norm_squared()
+    }
+
+    /// Returns the Euclidean distance between two points.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Vector3d;
+    /// let a = Vector3d::new(1.0, 1.0, 1.0);
+    /// let b = Vector3d::new(2.0, 3.0, 3.0);
+    /// assert_eq!(3.0, a.distance(&b));
+    /// ```
+    pub fn distance(&self, vector: &Self) -> T {
+        self.distance_squared(vector).sqrt()
+    }
+
+    /// Returns the angle between two vectors in radians, in `[0, pi]`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Vector3d;
+    /// let x = Vector3d::new(1.0, 0.0, 0.0);
+    /// let y = Vector3d::new(0.0, 2.0, 0.0);
+    /// assert_eq!(std::f32::consts::FRAC_PI_2, x.angle(&y));
+    /// ```
+    pub fn angle(&self, vector: &Self) -> T {
+        self.cross(vector).norm().atan2(self.dot(vector))
+    }
+
+    /// Returns the element-wise minimum of two vectors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Vector3d;
+    /// let a = Vector3d::new(1.0, 5.0, 3.0);
+    /// let b = Vector3d::new(4.0, 2.0, 6.0);
+    /// assert_eq!(Vector3d::new(1.0, 2.0, 3.0), a.min(&b));
+    /// ```
+    pub fn min(&self, vector: &Self) -> Self {
+        Self::new(
+            self.x.min(vector.x),
+            self.y.min(vector.y),
+            self.z.min(vector.z),
+        )
+    }
+
+    /// Returns the element-wise maximum of two vectors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Vector3d;
+    /// let a = Vector3d::new(1.0, 5.0, 3.0);
+    /// let b = Vector3d::new(4.0, 2.0, 6.0);
+    /// assert_eq!(Vector3d::new(4.0, 5.0, 6.0), a.max(&b));
+    /// ```
+    pub fn max(&self, vector: &Self) -> Self {
+        Self::new(
+            self.x.max(vector.x),
+            self.y.max(vector.y),
+            self.z.max(vector.z),
+        )
     }
 }
 
-impl ops::Add for Vector3d {
+impl<T: Float> ops::Neg for Vector3d<T> {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl<T: Float> ops::Add for Vector3d<T> {
     type Output = Self;
     fn add(self, vector: Self) -> Self::Output {
         Self::new(self.x + vector.x, self.y + vector.y, self.z + vector.z)
     }
 }
 
-impl ops::AddAssign for Vector3d {
+impl<T: Float> ops::AddAssign for Vector3d<T> {
     fn add_assign(&mut self, vector: Self) {
         self.x += vector.x;
         self.y += vector.y;
@@ -60,14 +282,14 @@ impl ops::AddAssign for Vector3d {
     }
 }
 
-impl ops::Sub for Vector3d {
+impl<T: Float> ops::Sub for Vector3d<T> {
     type Output = Self;
     fn sub(self, vector: Self) -> Self::Output {
         Self::new(self.x - vector.x, self.y - vector.y, self.z - vector.z)
     }
 }
 
-impl ops::SubAssign for Vector3d {
+impl<T: Float> ops::SubAssign for Vector3d<T> {
     fn sub_assign(&mut self, vector: Self) {
         self.x -= vector.x;
         self.y -= vector.y;
@@ -75,36 +297,102 @@ impl ops::SubAssign for Vector3d {
     }
 }
 
-impl ops::Mul<f32> for Vector3d {
+impl<T: Float> ops::Mul<T> for Vector3d<T> {
     type Output = Self;
-    fn mul(self, value: f32) -> Self::Output {
+    fn mul(self, value: T) -> Self::Output {
         Self::new(self.x * value, self.y * value, self.z * value)
     }
 }
 
-impl ops::MulAssign<f32> for Vector3d {
-    fn mul_assign(&mut self, value: f32) {
+impl<T: Float> ops::MulAssign<T> for Vector3d<T> {
+    fn mul_assign(&mut self, value: T) {
         self.x *= value;
         self.y *= value;
         self.z *= value;
     }
 }
 
-impl ops::Div<f32> for Vector3d {
+impl<T: Float> ops::Div<T> for Vector3d<T> {
     type Output = Self;
-    fn div(self, value: f32) -> Self::Output {
+    fn div(self, value: T) -> Self::Output {
         Self::new(self.x / value, self.y / value, self.z / value)
     }
 }
 
-impl ops::DivAssign<f32> for Vector3d {
-    fn div_assign(&mut self, value: f32) {
+impl<T: Float> ops::DivAssign<T> for Vector3d<T> {
+    fn div_assign(&mut self, value: T) {
         self.x /= value;
         self.y /= value;
         self.z /= value;
     }
 }
 
+macro_rules! impl_scalar_mul {
+    ($t:ident) => {
+        impl ops::Mul<Vector3d<$t>> for $t {
+            type Output = Vector3d<$t>;
+            fn mul(self, vector: Vector3d<$t>) -> Self::Output {
+                vector * self
+            }
+        }
+    };
+}
+
+impl_scalar_mul!(f32);
+impl_scalar_mul!(f64);
+
+impl<T: Float> iter::Sum for Vector3d<T> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |acc, vector| acc + vector)
+    }
+}
+
+impl<'a, T: Float> iter::Sum<&'a Vector3d<T>> for Vector3d<T> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |acc, vector| acc + *vector)
+    }
+}
+
+impl Vector3d<f32> {
+    /// Converts the vector to double precision without loss.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Vector3d;
+    /// let vector: Vector3d = Vector3d::new(0.1, 0.2, 0.3);
+    /// let double = vector.to_f64();
+    /// assert_eq!(0.1f32 as f64, double.x);
+    /// assert_eq!(vector, double.to_f32());
+    /// ```
+    pub fn to_f64(&self) -> Vector3d<f64> {
+        Vector3d::new(self.x as f64, self.y as f64, self.z as f64)
+    }
+}
+
+impl Vector3d<f64> {
+    /// Converts the vector to single precision, rounding each component
+    /// to the nearest representable `f32`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Vector3d;
+    /// let vector: Vector3d<f64> = Vector3d::new(0.1, 0.2, 0.3);
+    /// let single = vector.to_f32();
+    /// assert_eq!(0.1f32, single.x);
+    /// ```
+    pub fn to_f32(&self) -> Vector3d<f32> {
+        Vector3d::new(self.x as f32, self.y as f32, self.z as f32)
+    }
+}
+
+impl From<Vector3d<f32>> for Vector3d<f64> {
+    fn from(vector: Vector3d<f32>) -> Self {
+        vector.to_f64()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -119,7 +407,7 @@ mod tests {
 
     #[test]
     fn test_zero() {
-        let zero = Vector3d::zero();
+        let zero: Vector3d = Vector3d::zero();
         assert_eq!(0.0, zero.x);
         assert_eq!(0.0, zero.y);
         assert_eq!(0.0, zero.z);
@@ -238,4 +526,133 @@ mod tests {
         assert_eq!(2.0, vector.y);
         assert_eq!(3.0, vector.z);
     }
+
+    #[test]
+    fn test_dot() {
+        let vector1 = Vector3d::new(1.0, 2.0, 3.0);
+        let vector2 = Vector3d::new(4.0, -5.0, 6.0);
+        assert_eq!(12.0, vector1.dot(&vector2));
+        assert_eq!(12.0, vector2.dot(&vector1));
+    }
+
+    #[test]
+    fn test_cross() {
+        let vector1 = Vector3d::new(1.0, 2.0, 3.0);
+        let vector2 = Vector3d::new(4.0, 5.0, 6.0);
+        let ans = vector1.cross(&vector2);
+        assert_eq!(-3.0, ans.x);
+        assert_eq!(6.0, ans.y);
+        assert_eq!(-3.0, ans.z);
+
+        // The cross product is orthogonal to both operands
+        assert_eq!(0.0, ans.dot(&vector1));
+        assert_eq!(0.0, ans.dot(&vector2));
+        assert_eq!(-ans, vector2.cross(&vector1));
+    }
+
+    #[test]
+    fn test_norm() {
+        let vector = Vector3d::new(2.0, 3.0, 6.0);
+        assert_eq!(49.0, vector.norm_squared());
+        assert_eq!(7.0, vector.norm());
+        assert_eq!(0.0, Vector3d::<f32>::zero().norm());
+    }
+
+    #[test]
+    fn test_normalize() {
+        let unit = Vector3d::new(2.0, 3.0, 6.0).normalize();
+        assert!((unit.norm() - 1.0).abs() < 1e-6);
+        assert!(Vector3d::<f32>::zero().normalize().x.is_nan());
+    }
+
+    #[test]
+    fn test_try_normalize() {
+        let unit = Vector3d::new(0.0, 0.0, -5.0).try_normalize();
+        assert_eq!(Some(Vector3d::new(0.0, 0.0, -1.0)), unit);
+        assert_eq!(None, Vector3d::<f32>::zero().try_normalize());
+    }
+
+    #[test]
+    fn test_distance() {
+        let vector1 = Vector3d::new(1.0, 2.0, 3.0);
+        let vector2 = Vector3d::new(3.0, 5.0, 9.0);
+        assert_eq!(49.0, vector1.distance_squared(&vector2));
+        assert_eq!(7.0, vector1.distance(&vector2));
+        assert_eq!(7.0, vector2.distance(&vector1));
+    }
+
+    #[test]
+    fn test_angle() {
+        use std::f32::consts::PI;
+        let x = Vector3d::new(1.0, 0.0, 0.0);
+        let xy = Vector3d::new(1.0, 1.0, 0.0);
+        assert!((x.angle(&xy) - PI / 4.0).abs() < 1e-6);
+        assert!((x.angle(&-x) - PI).abs() < 1e-6);
+        assert_eq!(0.0, x.angle(&x));
+    }
+
+    #[test]
+    fn test_neg() {
+        let ans = -Vector3d::new(1.0, -2.0, 3.0);
+        assert_eq!(-1.0, ans.x);
+        assert_eq!(2.0, ans.y);
+        assert_eq!(-3.0, ans.z);
+    }
+
+    #[test]
+    fn test_scalar_mul() {
+        let vector = Vector3d::new(1.0, 2.0, 3.0);
+        assert_eq!(vector * 2.0, 2.0 * vector);
+    }
+
+    #[test]
+    fn test_sum() {
+        let vectors = vec![
+            Vector3d::new(1.0, 2.0, 3.0),
+            Vector3d::new(4.0, 5.0, 6.0),
+            Vector3d::new(7.0, 8.0, 9.0),
+        ];
+        let ans: Vector3d = vectors.iter().sum();
+        assert_eq!(Vector3d::new(12.0, 15.0, 18.0), ans);
+        let ans: Vector3d = vectors.into_iter().sum();
+        assert_eq!(Vector3d::new(12.0, 15.0, 18.0), ans);
+
+        let empty: Vec<Vector3d> = Vec::new();
+        assert_eq!(Vector3d::zero(), empty.iter().sum());
+    }
+
+    #[test]
+    fn test_min_max() {
+        let vector1 = Vector3d::new(1.0, 5.0, -3.0);
+        let vector2 = Vector3d::new(4.0, 2.0, -6.0);
+        assert_eq!(Vector3d::new(1.0, 2.0, -6.0), vector1.min(&vector2));
+        assert_eq!(Vector3d::new(4.0, 5.0, -3.0), vector1.max(&vector2));
+    }
+
+    #[test]
+    fn test_f64() {
+        let vector1: Vector3d<f64> = Vector3d::new(1.0, 2.0, 3.0);
+        let vector2: Vector3d<f64> = Vector3d::new(4.0, 5.0, 6.0);
+        assert_eq!(Vector3d::new(5.0, 7.0, 9.0), vector1 + vector2);
+        assert_eq!(Vector3d::new(3.0, 3.0, 3.0), vector2 - vector1);
+        assert_eq!(Vector3d::new(2.0, 4.0, 6.0), 2.0 * vector1);
+        assert_eq!(32.0, vector1.dot(&vector2));
+        assert_eq!(Vector3d::new(-3.0, 6.0, -3.0), vector1.cross(&vector2));
+    }
+
+    #[test]
+    fn test_precision_conversion() {
+        let single: Vector3d = Vector3d::new(0.1, 0.2, 0.3);
+        let double: Vector3d<f64> = single.into();
+        assert_eq!(0.1f32 as f64, double.x);
+        assert_eq!(0.2f32 as f64, double.y);
+        assert_eq!(0.3f32 as f64, double.z);
+        assert_eq!(single, double.to_f32());
+
+        let double: Vector3d<f64> = Vector3d::new(0.1, 0.2, 0.3);
+        let single = double.to_f32();
+        assert_eq!(0.1f32, single.x);
+        assert_eq!(0.2f32, single.y);
+        assert_eq!(0.3f32, single.z);
+    }
 }
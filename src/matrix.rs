@@ -0,0 +1,494 @@
+use std::cmp::Ordering;
+use std::ops;
+
+use float::Float;
+use Vector3d;
+
+/// The maximum number of sweeps of the Jacobi eigenvalue method.
+const MAX_JACOBI_SWEEPS: usize = 50;
+
+/// A 3x3 matrix stored in row-major order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Matrix3<T: Float = f32> {
+    elements: [[T; 3]; 3],
+}
+
+impl<T: Float> Matrix3<T> {
+    /// Returns a matrix with given rows.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Matrix3;
+    /// let matrix = Matrix3::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]);
+    /// assert_eq!(2.0, matrix[(0, 1)]);
+    /// assert_eq!(4.0, matrix[(1, 0)]);
+    /// ```
+    pub fn new(elements: [[T; 3]; 3]) -> Self {
+        Matrix3 { elements }
+    }
+
+    /// Returns a matrix whose rows are given vectors.
+    pub fn from_rows(row0: Vector3d<T>, row1: Vector3d<T>, row2: Vector3d<T>) -> Self {
+        Self::new([
+            [row0.x, row0.y, row0.z],
+            [row1.x, row1.y, row1.z],
+            [row2.x, row2.y, row2.z],
+        ])
+    }
+
+    /// Returns a matrix whose columns are given vectors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{Matrix3, Vector3d};
+    /// let column = Vector3d::new(1.0, 2.0, 3.0);
+    /// let matrix = Matrix3::from_columns(column, Vector3d::zero(), Vector3d::zero());
+    /// assert_eq!(column, matrix.column(0));
+    /// ```
+    pub fn from_columns(column0: Vector3d<T>, column1: Vector3d<T>, column2: Vector3d<T>) -> Self {
+        Self::from_rows(column0, column1, column2).transpose()
+    }
+
+    /// Returns a matrix with all elements zero.
+    pub fn zero() -> Self {
+        Self::new([[T::zero(); 3]; 3])
+    }
+
+    /// Returns the identity matrix.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{Matrix3, Vector3d};
+    /// let vector = Vector3d::new(1.0, 2.0, 3.0);
+    /// assert_eq!(vector, Matrix3::identity() * vector);
+    /// ```
+    pub fn identity() -> Self {
+        Self::diagonal(T::one(), T::one(), T::one())
+    }
+
+    /// Returns a diagonal matrix with given diagonal elements.
+    pub fn diagonal(d0: T, d1: T, d2: T) -> Self {
+        let mut matrix = Self::zero();
+        matrix.elements[0][0] = d0;
+        matrix.elements[1][1] = d1;
+        matrix.elements[2][2] = d2;
+        matrix
+    }
+
+    /// Returns the outer product `a * b^T` of two vectors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{Matrix3, Vector3d};
+    /// let a = Vector3d::new(1.0, 2.0, 3.0);
+    /// let b = Vector3d::new(4.0, 5.0, 6.0);
+    /// assert_eq!(10.0, Matrix3::outer(&a, &b)[(1, 1)]);
+    /// ```
+    pub fn outer(a: &Vector3d<T>, b: &Vector3d<T>) -> Self {
+        Self::from_rows(*b * a.x, *b * a.y, *b * a.z)
+    }
+
+    /// Returns the `i`-th row as a vector.
+    pub fn row(&self, i: usize) -> Vector3d<T> {
+        let row = self.elements[i];
+        Vector3d::new(row[0], row[1], row[2])
+    }
+
+    /// Returns the `j`-th column as a vector.
+    pub fn column(&self, j: usize) -> Vector3d<T> {
+        Vector3d::new(
+            self.elements[0][j],
+            self.elements[1][j],
+            self.elements[2][j],
+        )
+    }
+
+    /// Returns the transposed matrix.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Matrix3;
+    /// let matrix = Matrix3::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]]);
+    /// let transposed = matrix.transpose();
+    /// assert_eq!(4.0, transposed[(0, 1)]);
+    /// assert_eq!(matrix, transposed.transpose());
+    /// ```
+    pub fn transpose(&self) -> Self {
+        let mut matrix = *self;
+        for i in 0..3 {
+            for j in 0..3 {
+                matrix.elements[i][j] = self.elements[j][i];
+            }
+        }
+        matrix
+    }
+
+    /// Returns the determinant.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Matrix3;
+    /// let matrix = Matrix3::new([[2.0, 0.0, 0.0], [1.0, 3.0, 0.0], [4.0, 5.0, 6.0]]);
+    /// assert_eq!(36.0, matrix.determinant());
+    /// ```
+    pub fn determinant(&self) -> T {
+        self.row(0).dot(&self.row(1).cross(&self.row(2)))
+    }
+
+    /// Returns the sum of the diagonal elements.
+    pub fn trace(&self) -> T {
+        self.elements[0][0] + self.elements[1][1] + self.elements[2][2]
+    }
+
+    /// Returns the inverse matrix, or `None` if the matrix is singular.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::Matrix3;
+    /// let matrix = Matrix3::diagonal(2.0, 4.0, 8.0);
+    /// assert_eq!(Some(Matrix3::diagonal(0.5, 0.25, 0.125)), matrix.inverse());
+    /// assert_eq!(None, Matrix3::<f32>::zero().inverse());
+    /// ```
+    pub fn inverse(&self) -> Option<Self> {
+        let determinant = self.determinant();
+        if determinant == T::zero() || !determinant.is_finite() {
+            return None;
+        }
+        // The columns of the inverse are the cross products of the rows.
+        let (r0, r1, r2) = (self.row(0), self.row(1), self.row(2));
+        let adjugate = Self::from_columns(r1.cross(&r2), r2.cross(&r0), r0.cross(&r1));
+        Some(adjugate / determinant)
+    }
+
+    /// Returns the eigenvalues in descending order and the corresponding
+    /// unit eigenvectors as the columns of a rotation matrix, assuming the
+    /// matrix is symmetric.
+    ///
+    /// The eigenvectors are computed with the cyclic Jacobi method.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{Matrix3, Vector3d};
+    /// let matrix: Matrix3 = Matrix3::new([[2.0, 1.0, 0.0], [1.0, 2.0, 0.0], [0.0, 0.0, 5.0]]);
+    /// let (values, vectors) = matrix.symmetric_eigen();
+    /// assert!((values - Vector3d::new(5.0, 3.0, 1.0)).norm() < 1e-6);
+    /// let vector = vectors.column(1);
+    /// assert!((matrix * vector - vector * values.y).norm() < 1e-6);
+    /// ```
+    pub fn symmetric_eigen(&self) -> (Vector3d<T>, Self) {
+        let mut matrix = *self;
+        let mut vectors = Self::identity();
+        for _ in 0..MAX_JACOBI_SWEEPS {
+            let off_diagonal = matrix[(0, 1)].abs() + matrix[(0, 2)].abs() + matrix[(1, 2)].abs();
+            let diagonal = matrix[(0, 0)].abs() + matrix[(1, 1)].abs() + matrix[(2, 2)].abs();
+            if off_diagonal <= T::epsilon() * (diagonal + off_diagonal) {
+                break;
+            }
+            for &(p, q) in [(0, 1), (0, 2), (1, 2)].iter() {
+                if matrix[(p, q)] == T::zero() {
+                    continue;
+                }
+                // The rotation in the p-q plane annihilating the (p, q) element
+                let theta = (matrix[(q, q)] - matrix[(p, p)]) / (T::from_f32(2.0) * matrix[(p, q)]);
+                let mut t = T::one() / (theta.abs() + (theta * theta + T::one()).sqrt());
+                if theta < T::zero() {
+                    t = -t;
+                }
+                let c = T::one() / (t * t + T::one()).sqrt();
+                let s = t * c;
+                let mut jacobi = Self::identity();
+                jacobi[(p, p)] = c;
+                jacobi[(q, q)] = c;
+                jacobi[(p, q)] = s;
+                jacobi[(q, p)] = -s;
+                matrix = jacobi.transpose() * matrix * jacobi;
+                vectors = vectors * jacobi;
+            }
+        }
+
+        let mut order = [0, 1, 2];
+        order.sort_by(|&i, &j| {
+            matrix[(j, j)]
+                .partial_cmp(&matrix[(i, i)])
+                .unwrap_or(Ordering::Equal)
+        });
+        let values = Vector3d::new(
+            matrix[(order[0], order[0])],
+            matrix[(order[1], order[1])],
+            matrix[(order[2], order[2])],
+        );
+        let column0 = vectors.column(order[0]);
+        let column1 = vectors.column(order[1]);
+        (
+            values,
+            Self::from_columns(column0, column1, column0.cross(&column1)),
+        )
+    }
+
+    /// Returns the singular value decomposition `U * diag(s) * V^T` as a
+    /// tuple `(U, s, V)`, where `U` and `V` are orthogonal matrices and the
+    /// singular values `s` are non-negative and in descending order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{Matrix3, Vector3d};
+    /// let matrix: Matrix3 = Matrix3::new([[1.0, 2.0, 0.0], [0.0, 1.0, -1.0], [3.0, 0.0, 1.0]]);
+    /// let (u, s, v) = matrix.svd();
+    /// let product = u * Matrix3::diagonal(s.x, s.y, s.z) * v.transpose();
+    /// for i in 0..3 {
+    ///     assert!((product.row(i) - matrix.row(i)).norm() < 1e-5);
+    /// }
+    /// ```
+    pub fn svd(&self) -> (Self, Vector3d<T>, Self) {
+        let (_, v) = (self.transpose() * *self).symmetric_eigen();
+        let image0 = *self * v.column(0);
+        let image1 = *self * v.column(1);
+        let image2 = *self * v.column(2);
+
+        // The left singular vectors are the orthonormalized images of the
+        // right ones, completed arbitrarily where a singular value is zero.
+        let u0 = image0
+            .try_normalize()
+            .unwrap_or_else(|| Vector3d::new(T::one(), T::zero(), T::zero()));
+        let u1 = (image1 - u0 * u0.dot(&image1))
+            .try_normalize()
+            .unwrap_or_else(|| perpendicular(&u0));
+        let mut u2 = u0.cross(&u1);
+        let mut s2 = u2.dot(&image2);
+        if s2 < T::zero() {
+            u2 = -u2;
+            s2 = -s2;
+        }
+        let s = Vector3d::new(image0.norm(), u1.dot(&image1), s2);
+        (Self::from_columns(u0, u1, u2), s, v)
+    }
+}
+
+/// Returns a unit vector perpendicular to a unit vector.
+fn perpendicular<T: Float>(vector: &Vector3d<T>) -> Vector3d<T> {
+    let (x, y, z) = (vector.x.abs(), vector.y.abs(), vector.z.abs());
+    let axis = if x <= y && x <= z {
+        Vector3d::new(T::one(), T::zero(), T::zero())
+    } else if y <= z {
+        Vector3d::new(T::zero(), T::one(), T::zero())
+    } else {
+        Vector3d::new(T::zero(), T::zero(), T::one())
+    };
+    vector.cross(&axis).normalize()
+}
+
+impl<T: Float> ops::Index<(usize, usize)> for Matrix3<T> {
+    type Output = T;
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        &self.elements[i][j]
+    }
+}
+
+impl<T: Float> ops::IndexMut<(usize, usize)> for Matrix3<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Self::Output {
+        &mut self.elements[i][j]
+    }
+}
+
+impl<T: Float> ops::Add for Matrix3<T> {
+    type Output = Self;
+    fn add(self, matrix: Self) -> Self::Output {
+        let mut ans = self;
+        for i in 0..3 {
+            for j in 0..3 {
+                ans.elements[i][j] += matrix.elements[i][j];
+            }
+        }
+        ans
+    }
+}
+
+impl<T: Float> ops::Sub for Matrix3<T> {
+    type Output = Self;
+    fn sub(self, matrix: Self) -> Self::Output {
+        let mut ans = self;
+        for i in 0..3 {
+            for j in 0..3 {
+                ans.elements[i][j] -= matrix.elements[i][j];
+            }
+        }
+        ans
+    }
+}
+
+impl<T: Float> ops::Mul for Matrix3<T> {
+    type Output = Self;
+    fn mul(self, matrix: Self) -> Self::Output {
+        let mut ans = Self::zero();
+        for i in 0..3 {
+            for j in 0..3 {
+                ans.elements[i][j] = self.row(i).dot(&matrix.column(j));
+            }
+        }
+        ans
+    }
+}
+
+impl<T: Float> ops::Mul<Vector3d<T>> for Matrix3<T> {
+    type Output = Vector3d<T>;
+    fn mul(self, vector: Vector3d<T>) -> Self::Output {
+        Vector3d::new(
+            self.row(0).dot(&vector),
+            self.row(1).dot(&vector),
+            self.row(2).dot(&vector),
+        )
+    }
+}
+
+impl<T: Float> ops::Mul<T> for Matrix3<T> {
+    type Output = Self;
+    fn mul(self, value: T) -> Self::Output {
+        let mut ans = self;
+        for row in ans.elements.iter_mut() {
+            for element in row.iter_mut() {
+                *element *= value;
+            }
+        }
+        ans
+    }
+}
+
+impl<T: Float> ops::Div<T> for Matrix3<T> {
+    type Output = Self;
+    fn div(self, value: T) -> Self::Output {
+        let mut ans = self;
+        for row in ans.elements.iter_mut() {
+            for element in row.iter_mut() {
+                *element /= value;
+            }
+        }
+        ans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Matrix3 {
+        Matrix3::new([[2.0, -1.0, 0.0], [1.0, 3.0, 2.0], [0.0, 1.0, 4.0]])
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let matrix = sample();
+        assert_eq!(Vector3d::new(1.0, 3.0, 2.0), matrix.row(1));
+        assert_eq!(Vector3d::new(-1.0, 3.0, 1.0), matrix.column(1));
+        let rebuilt = Matrix3::from_columns(matrix.column(0), matrix.column(1), matrix.column(2));
+        assert_eq!(matrix, rebuilt);
+        let rebuilt = Matrix3::from_rows(matrix.row(0), matrix.row(1), matrix.row(2));
+        assert_eq!(matrix, rebuilt);
+    }
+
+    #[test]
+    fn test_mul() {
+        let matrix = sample();
+        assert_eq!(matrix, matrix * Matrix3::identity());
+        assert_eq!(matrix, Matrix3::identity() * matrix);
+
+        let ans = matrix * matrix.transpose();
+        assert_eq!(5.0, ans[(0, 0)]);
+        assert_eq!(-1.0, ans[(0, 1)]);
+        assert_eq!(-1.0, ans[(1, 0)]);
+        assert_eq!(14.0, ans[(1, 1)]);
+        assert_eq!(11.0, ans[(1, 2)]);
+        assert_eq!(17.0, ans[(2, 2)]);
+
+        let vector = matrix * Vector3d::new(1.0, 2.0, 3.0);
+        assert_eq!(Vector3d::new(0.0, 13.0, 14.0), vector);
+    }
+
+    #[test]
+    fn test_determinant_and_trace() {
+        assert_eq!(24.0, sample().determinant());
+        assert_eq!(9.0, sample().trace());
+        assert_eq!(1.0, Matrix3::<f32>::identity().determinant());
+    }
+
+    #[test]
+    fn test_inverse() {
+        let matrix = sample();
+        let inverse = matrix.inverse().unwrap();
+        let product = matrix * inverse;
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((product[(i, j)] - expected).abs() < 1e-6);
+            }
+        }
+
+        let singular = Matrix3::new([[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [0.0, 1.0, 0.0]]);
+        assert_eq!(None, singular.inverse());
+    }
+
+    #[test]
+    fn test_symmetric_eigen() {
+        let matrix = Matrix3::<f64>::new([[4.0, 1.0, -2.0], [1.0, 2.0, 0.0], [-2.0, 0.0, 3.0]]);
+        let (values, vectors) = matrix.symmetric_eigen();
+        assert!(values.x >= values.y && values.y >= values.z);
+        assert!((values.x + values.y + values.z - matrix.trace()).abs() < 1e-12);
+        assert!((values.x * values.y * values.z - matrix.determinant()).abs() < 1e-12);
+        assert!((vectors.determinant() - 1.0).abs() < 1e-12);
+        for (j, &value) in [values.x, values.y, values.z].iter().enumerate() {
+            let vector = vectors.column(j);
+            assert!((matrix * vector - vector * value).norm() < 1e-12);
+        }
+
+        let (values, vectors) = Matrix3::<f32>::diagonal(1.0, 3.0, 2.0).symmetric_eigen();
+        assert_eq!(Vector3d::new(3.0, 2.0, 1.0), values);
+        assert_eq!(Vector3d::new(0.0, 1.0, 0.0), vectors.column(0));
+    }
+
+    #[test]
+    fn test_svd() {
+        let matrices = [
+            sample(),
+            Matrix3::new([[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [0.0, 1.0, 0.0]]),
+            Matrix3::outer(
+                &Vector3d::new(1.0, -2.0, 0.5),
+                &Vector3d::new(0.0, 3.0, 1.0),
+            ),
+            Matrix3::zero(),
+        ];
+        for matrix in matrices.iter() {
+            let (u, s, v) = matrix.svd();
+            assert!(s.x >= s.y && s.y >= s.z && s.z >= 0.0);
+            let product = u * Matrix3::diagonal(s.x, s.y, s.z) * v.transpose();
+            let orthogonality = u.transpose() * u - Matrix3::identity();
+            for i in 0..3 {
+                assert!((product.row(i) - matrix.row(i)).norm() < 1e-5);
+                assert!(orthogonality.row(i).norm() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_add_sub() {
+        let matrix = sample();
+        assert_eq!(matrix * 2.0, matrix + matrix);
+        assert_eq!(Matrix3::zero(), matrix - matrix);
+        assert_eq!(matrix, (matrix * 4.0) / 4.0);
+    }
+
+    #[test]
+    fn test_index_mut() {
+        let mut matrix = Matrix3::<f64>::zero();
+        matrix[(2, 1)] = 5.0;
+        assert_eq!(5.0, matrix[(2, 1)]);
+        assert_eq!(Vector3d::new(0.0, 5.0, 0.0), matrix.row(2));
+    }
+}
@@ -0,0 +1,781 @@
+//! Spatial search for neighboring positions.
+//!
+//! Two structures are provided: `Grid`, a uniform cell list suited to
+//! queries with a fixed cutoff, and `KdTree`, which additionally supports
+//! k-nearest-neighbor queries. Both optionally treat the positions as in a
+//! periodic `UnitCell`, orthorhombic or triclinic, in which case distances
+//! follow the minimum-image convention and the search radius should be at
+//! most half the smallest width of the cell.
+
+use std::cmp::Ordering;
+
+use float::Float;
+use periodic::UnitCell;
+use Vector3d;
+
+/// The maximum number of grid cells per position; sparse positions get
+/// larger cells than requested.
+const MAX_CELLS_PER_POSITION: usize = 8;
+
+fn component<T: Float>(vector: &Vector3d<T>, axis: usize) -> T {
+    match axis {
+        0 => vector.x,
+        1 => vector.y,
+        _ => vector.z,
+    }
+}
+
+fn is_finite<T: Float>(vector: &Vector3d<T>) -> bool {
+    vector.x.is_finite() && vector.y.is_finite() && vector.z.is_finite()
+}
+
+/// Returns the smallest cell size of at least `cell_size`, doubling it,
+/// for which the numbers of cells along the axes given by `dimensions` do
+/// not exceed `MAX_CELLS_PER_POSITION` for `count` positions. The numbers
+/// are counted in `f64` so that they cannot overflow.
+fn fit_cells<F: Fn(f64) -> [f64; 3]>(cell_size: f64, count: usize, dimensions: F) -> f64 {
+    let limit = (MAX_CELLS_PER_POSITION * count.max(1)) as f64;
+    let mut cell_size = cell_size;
+    while dimensions(cell_size).iter().product::<f64>() > limit {
+        cell_size *= 2.0;
+    }
+    cell_size
+}
+
+/// Returns the vector from `a` to the nearest image of `b`.
+fn displacement<T: Float>(
+    a: &Vector3d<T>,
+    b: &Vector3d<T>,
+    cell: Option<&UnitCell<T>>,
+) -> Vector3d<T> {
+    match cell {
+        Some(cell) => cell.minimum_image(a, b),
+        None => *b - *a,
+    }
+}
+
+/// A uniform grid of cells, each listing the positions in it.
+#[derive(Debug, Clone)]
+pub struct Grid<T: Float = f32> {
+    positions: Vec<Vector3d<T>>,
+    cell: Option<UnitCell<T>>,
+    origin: Vector3d<T>,
+    /// The widths of the cells along the axes, or perpendicular to the
+    /// faces of a periodic unit cell, whose cells are slices of fractional
+    /// coordinates.
+    cell_size: Vector3d<T>,
+    dimensions: [usize; 3],
+    /// The range of `indices` of the `i`-th cell is
+    /// `offsets[i]..offsets[i + 1]`.
+    offsets: Vec<usize>,
+    indices: Vec<usize>,
+}
+
+impl<T: Float> Grid<T> {
+    /// Builds a grid of cells of at least `cell_size`, typically the
+    /// cutoff of the queries.
+    ///
+    /// Returns `None` if `cell_size` is not positive or any position is not
+    /// finite.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::neighbor::Grid;
+    /// use biost::Vector3d;
+    /// let positions = [
+    ///     Vector3d::new(0.0, 0.0, 0.0),
+    ///     Vector3d::new(1.0, 0.0, 0.0),
+    ///     Vector3d::new(5.0, 0.0, 0.0),
+    /// ];
+    /// let grid = Grid::new(&positions, 2.0).unwrap();
+    /// assert_eq!(vec![0, 1], grid.within(&Vector3d::new(0.5, 0.5, 0.0), 1.5));
+    /// assert_eq!(vec![(0, 1, 1.0)], grid.pairs(2.0));
+    /// ```
+    pub fn new(positions: &[Vector3d<T>], cell_size: T) -> Option<Self> {
+        if !(cell_size > T::zero() && cell_size.is_finite() && positions.iter().all(is_finite)) {
+            return None;
+        }
+        let (min, max) = match positions.first() {
+            Some(first) => positions
+                .iter()
+                .fold((*first, *first), |(min, max), p| (min.min(p), max.max(p))),
+            None => (Vector3d::zero(), Vector3d::zero()),
+        };
+        // The extent may overflow `T`, so the cells are counted in `f64`.
+        let extent =
+            [0, 1, 2].map(|axis| component(&max, axis).to_f64() - component(&min, axis).to_f64());
+        let cell_size = fit_cells(cell_size.to_f64(), positions.len(), |cell_size| {
+            extent.map(|length| (length / cell_size).floor() + 1.0)
+        });
+        let dimensions = extent.map(|length| (length / cell_size).floor() as usize + 1);
+        let size = T::from_f64(cell_size);
+        let size = Vector3d::new(size, size, size);
+        Some(Self::build(positions.to_vec(), None, min, size, dimensions))
+    }
+
+    /// Builds a grid of the positions in a periodic unit cell, dividing
+    /// each of its widths into cells of at least `cell_size`.
+    ///
+    /// Returns `None` if `cell_size` is not positive or any position is not
+    /// finite.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::neighbor::Grid;
+    /// use biost::periodic::UnitCell;
+    /// use biost::Vector3d;
+    /// let positions: [Vector3d; 2] = [Vector3d::new(0.5, 5.0, 5.0), Vector3d::new(9.5, 5.0, 5.0)];
+    /// let cell = UnitCell::orthorhombic(10.0, 10.0, 10.0).unwrap();
+    /// let grid = Grid::periodic(&positions, 2.0, cell).unwrap();
+    /// // The positions are neighbors across the boundary.
+    /// let pairs = grid.pairs(2.0);
+    /// assert_eq!(1, pairs.len());
+    /// assert_eq!((0, 1), (pairs[0].0, pairs[0].1));
+    /// assert!((pairs[0].2 - 1.0).abs() < 1e-5);
+    /// ```
+    pub fn periodic(positions: &[Vector3d<T>], cell_size: T, cell: UnitCell<T>) -> Option<Self> {
+        if !(cell_size > T::zero() && cell_size.is_finite() && positions.iter().all(is_finite)) {
+            return None;
+        }
+        let widths = cell.widths();
+        let widths_f64 = [widths.x, widths.y, widths.z].map(|width| width.to_f64());
+        let cell_size = fit_cells(cell_size.to_f64(), positions.len(), |cell_size| {
+            widths_f64.map(|width| (width / cell_size).floor().max(1.0))
+        });
+        let dimensions = widths_f64.map(|width| ((width / cell_size).floor() as usize).max(1));
+        let size = Vector3d::new(
+            widths.x / T::from_f64(dimensions[0] as f64),
+            widths.y / T::from_f64(dimensions[1] as f64),
+            widths.z / T::from_f64(dimensions[2] as f64),
+        );
+        let positions = positions.iter().map(|p| cell.wrap(p)).collect();
+        Some(Self::build(
+            positions,
+            Some(cell),
+            Vector3d::zero(),
+            size,
+            dimensions,
+        ))
+    }
+
+    fn build(
+        positions: Vec<Vector3d<T>>,
+        cell: Option<UnitCell<T>>,
+        origin: Vector3d<T>,
+        cell_size: Vector3d<T>,
+        dimensions: [usize; 3],
+    ) -> Self {
+        let mut grid = Grid {
+            positions: Vec::new(),
+            cell,
+            origin,
+            cell_size,
+            dimensions,
+            offsets: vec![0; dimensions.iter().product::<usize>() + 1],
+            indices: vec![0; positions.len()],
+        };
+        let cells: Vec<usize> = positions
+            .iter()
+            .map(|position| {
+                let coordinates = grid.coordinates(position);
+                let cell = [0, 1, 2].map(|axis| {
+                    let index = component(&coordinates, axis).floor().to_f64() as isize;
+                    index.clamp(0, dimensions[axis] as isize - 1) as usize
+                });
+                (cell[0] * dimensions[1] + cell[1]) * dimensions[2] + cell[2]
+            })
+            .collect();
+        for &cell in cells.iter() {
+            grid.offsets[cell + 1] += 1;
+        }
+        for i in 1..grid.offsets.len() {
+            grid.offsets[i] += grid.offsets[i - 1];
+        }
+        let mut next = grid.offsets.clone();
+        for (index, &cell) in cells.iter().enumerate() {
+            grid.indices[next[cell]] = index;
+            next[cell] += 1;
+        }
+        grid.positions = positions;
+        grid
+    }
+
+    /// Returns the coordinates of a point in units of cells from the
+    /// origin of the grid.
+    fn coordinates(&self, point: &Vector3d<T>) -> Vector3d<T> {
+        match self.cell {
+            Some(ref cell) => {
+                let fractional = cell.to_fractional(point);
+                let scale = |axis: usize| T::from_f64(self.dimensions[axis] as f64);
+                Vector3d::new(
+                    fractional.x * scale(0),
+                    fractional.y * scale(1),
+                    fractional.z * scale(2),
+                )
+            }
+            None => {
+                let relative = *point - self.origin;
+                Vector3d::new(
+                    relative.x / self.cell_size.x,
+                    relative.y / self.cell_size.y,
+                    relative.z / self.cell_size.z,
+                )
+            }
+        }
+    }
+
+    /// Returns the indices in ascending order of the positions within
+    /// `radius` of `point`.
+    pub fn within(&self, point: &Vector3d<T>, radius: T) -> Vec<usize> {
+        let unit_cell = self.cell.as_ref();
+        let coordinates = self.coordinates(point);
+        let mut ranges = [(0, 0); 3];
+        for (axis, range) in ranges.iter_mut().enumerate() {
+            let value = component(&coordinates, axis);
+            let reach = radius / component(&self.cell_size, axis);
+            let count = self.dimensions[axis] as isize;
+            let mut low = (value - reach).floor().to_f64() as isize;
+            let mut high = (value + reach).floor().to_f64() as isize;
+            if unit_cell.is_some() {
+                // A large radius saturates the bounds, so the span is
+                // compared in float rather than by subtracting them.
+                let span = (value + reach).floor() - (value - reach).floor();
+                if span.to_f64() + 1.0 >= count as f64 {
+                    low = 0;
+                    high = count - 1;
+                }
+            } else {
+                low = low.max(0);
+                high = high.min(count - 1);
+                if low > high {
+                    return Vec::new();
+                }
+            }
+            *range = (low, high);
+        }
+
+        let squared = radius * radius;
+        let mut found = Vec::new();
+        let wrapped =
+            |index: isize, axis: usize| index.rem_euclid(self.dimensions[axis] as isize) as usize;
+        for x in ranges[0].0..=ranges[0].1 {
+            for y in ranges[1].0..=ranges[1].1 {
+                for z in ranges[2].0..=ranges[2].1 {
+                    let cell = (wrapped(x, 0) * self.dimensions[1] + wrapped(y, 1))
+                        * self.dimensions[2]
+                        + wrapped(z, 2);
+                    for &index in &self.indices[self.offsets[cell]..self.offsets[cell + 1]] {
+                        let d = displacement(point, &self.positions[index], unit_cell);
+                        if d.norm_squared() <= squared {
+                            found.push(index);
+                        }
+                    }
+                }
+            }
+        }
+        found.sort_unstable();
+        found
+    }
+
+    /// Returns the pairs `(i, j, distance)` with `i < j` of positions
+    /// within `cutoff`, ordered by `i` and then `j`.
+    pub fn pairs(&self, cutoff: T) -> Vec<(usize, usize, T)> {
+        pairs(&self.positions, self.cell.as_ref(), cutoff, |point| {
+            self.within(point, cutoff)
+        })
+    }
+}
+
+fn pairs<T: Float, F: Fn(&Vector3d<T>) -> Vec<usize>>(
+    positions: &[Vector3d<T>],
+    cell: Option<&UnitCell<T>>,
+    cutoff: T,
+    within: F,
+) -> Vec<(usize, usize, T)> {
+    let mut pairs = Vec::new();
+    for (i, position) in positions.iter().enumerate() {
+        for j in within(position).into_iter().filter(|&j| j > i) {
+            let distance = displacement(position, &positions[j], cell).norm();
+            if distance <= cutoff {
+                pairs.push((i, j, distance));
+            }
+        }
+    }
+    pairs
+}
+
+/// A k-d tree of positions.
+#[derive(Debug, Clone)]
+pub struct KdTree<T: Float = f32> {
+    positions: Vec<Vector3d<T>>,
+    cell: Option<UnitCell<T>>,
+    /// The indices of the positions as a balanced tree, whose root of
+    /// `indices[low..high]` is at the middle, splitting the positions
+    /// along `axes` of the same index.
+    indices: Vec<usize>,
+    axes: Vec<usize>,
+}
+
+impl<T: Float> KdTree<T> {
+    /// Builds a k-d tree of positions.
+    ///
+    /// Returns `None` if any position is not finite.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::neighbor::KdTree;
+    /// use biost::Vector3d;
+    /// let positions = [
+    ///     Vector3d::new(0.0, 0.0, 0.0),
+    ///     Vector3d::new(3.0, 0.0, 0.0),
+    ///     Vector3d::new(0.0, 1.0, 0.0),
+    /// ];
+    /// let tree = KdTree::new(&positions).unwrap();
+    /// let nearest = tree.nearest(&Vector3d::new(0.0, 2.0, 0.0), 2);
+    /// assert_eq!(vec![(2, 1.0), (0, 2.0)], nearest);
+    /// assert_eq!(vec![0, 2], tree.within(&Vector3d::zero(), 1.0));
+    /// ```
+    pub fn new(positions: &[Vector3d<T>]) -> Option<Self> {
+        if !positions.iter().all(is_finite) {
+            return None;
+        }
+        Some(Self::build(positions.to_vec(), None))
+    }
+
+    /// Builds a k-d tree of the positions in a periodic unit cell.
+    ///
+    /// Returns `None` if any position is not finite.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::neighbor::KdTree;
+    /// use biost::periodic::UnitCell;
+    /// use biost::Vector3d;
+    /// let positions = [Vector3d::new(0.5, 5.0, 5.0), Vector3d::new(7.0, 5.0, 5.0)];
+    /// let cell = UnitCell::orthorhombic(10.0, 10.0, 10.0).unwrap();
+    /// let tree = KdTree::periodic(&positions, cell).unwrap();
+    /// assert_eq!(vec![(0, 1.0)], tree.nearest(&Vector3d::new(9.5, 5.0, 5.0), 1));
+    /// ```
+    pub fn periodic(positions: &[Vector3d<T>], cell: UnitCell<T>) -> Option<Self> {
+        if !positions.iter().all(is_finite) {
+            return None;
+        }
+        let positions = positions.iter().map(|p| cell.wrap(p)).collect();
+        Some(Self::build(positions, Some(cell)))
+    }
+
+    fn build(positions: Vec<Vector3d<T>>, cell: Option<UnitCell<T>>) -> Self {
+        let mut indices: Vec<usize> = (0..positions.len()).collect();
+        let mut axes = vec![0; positions.len()];
+        split(&positions, &mut indices, &mut axes);
+        KdTree {
+            positions,
+            cell,
+            indices,
+            axes,
+        }
+    }
+
+    /// Returns the images of a query point needed to find the images of
+    /// the positions within `radius` of it, i.e. those translated by box
+    /// vectors to within `radius` of the faces of the primary cell.
+    fn images(&self, point: &Vector3d<T>, radius: T) -> Vec<Vector3d<T>> {
+        let cell = match self.cell {
+            Some(ref cell) => cell,
+            None => return vec![*point],
+        };
+        let point = cell.wrap(point);
+        let fractional = cell.to_fractional(&point);
+        let widths = cell.widths();
+        let shifts = |value: T, width: T| {
+            let reach = radius / width;
+            let mut shifts = vec![T::zero()];
+            if value - reach < T::zero() {
+                shifts.push(T::one());
+            }
+            if value + reach > T::one() {
+                shifts.push(-T::one());
+            }
+            shifts
+        };
+        let mut images = Vec::new();
+        for &i in shifts(fractional.x, widths.x).iter() {
+            for &j in shifts(fractional.y, widths.y).iter() {
+                for &k in shifts(fractional.z, widths.z).iter() {
+                    images.push(point + cell.to_cartesian(&Vector3d::new(i, j, k)));
+                }
+            }
+        }
+        images
+    }
+
+    /// Returns the indices in ascending order of the positions within
+    /// `radius` of `point`.
+    pub fn within(&self, point: &Vector3d<T>, radius: T) -> Vec<usize> {
+        let mut found = Vec::new();
+        for image in self.images(point, radius) {
+            self.search_within(0, self.indices.len(), &image, radius * radius, &mut found);
+        }
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    fn search_within(
+        &self,
+        low: usize,
+        high: usize,
+        point: &Vector3d<T>,
+        squared: T,
+        found: &mut Vec<usize>,
+    ) {
+        if low >= high {
+            return;
+        }
+        let middle = (low + high) / 2;
+        let index = self.indices[middle];
+        let position = &self.positions[index];
+        if point.distance_squared(position) <= squared {
+            found.push(index);
+        }
+        let difference =
+            component(point, self.axes[middle]) - component(position, self.axes[middle]);
+        if difference <= T::zero() || difference * difference <= squared {
+            self.search_within(low, middle, point, squared, found);
+        }
+        if difference >= T::zero() || difference * difference <= squared {
+            self.search_within(middle + 1, high, point, squared, found);
+        }
+    }
+
+    /// Returns the indices and the distances of the `k` nearest positions
+    /// to `point` in ascending order of the distance.
+    ///
+    /// Fewer are returned if there are fewer positions.
+    pub fn nearest(&self, point: &Vector3d<T>, k: usize) -> Vec<(usize, T)> {
+        let mut nearest = Vec::with_capacity(k + 1);
+        if k > 0 {
+            let images = match self.cell {
+                // Any image may be the nearest for a large k.
+                Some(ref cell) => {
+                    let widths = cell.widths();
+                    self.images(point, widths.x.max(widths.y).max(widths.z))
+                }
+                None => vec![*point],
+            };
+            for image in images {
+                self.search_nearest(0, self.indices.len(), &image, k, &mut nearest);
+            }
+        }
+        nearest
+            .into_iter()
+            .map(|(index, squared): (usize, T)| (index, squared.sqrt()))
+            .collect()
+    }
+
+    fn search_nearest(
+        &self,
+        low: usize,
+        high: usize,
+        point: &Vector3d<T>,
+        k: usize,
+        nearest: &mut Vec<(usize, T)>,
+    ) {
+        if low >= high {
+            return;
+        }
+        let middle = (low + high) / 2;
+        let index = self.indices[middle];
+        let position = &self.positions[index];
+        insert(nearest, k, index, point.distance_squared(position));
+
+        let difference =
+            component(point, self.axes[middle]) - component(position, self.axes[middle]);
+        let (near, far) = if difference <= T::zero() {
+            ((low, middle), (middle + 1, high))
+        } else {
+            ((middle + 1, high), (low, middle))
+        };
+        self.search_nearest(near.0, near.1, point, k, nearest);
+        if nearest.len() < k || difference * difference < nearest[nearest.len() - 1].1 {
+            self.search_nearest(far.0, far.1, point, k, nearest);
+        }
+    }
+
+    /// Returns the pairs `(i, j, distance)` with `i < j` of positions
+    /// within `cutoff`, ordered by `i` and then `j`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::neighbor::KdTree;
+    /// use biost::Vector3d;
+    /// let positions = [
+    ///     Vector3d::new(0.0, 0.0, 0.0),
+    ///     Vector3d::new(0.0, 0.0, 1.0),
+    ///     Vector3d::new(0.0, 0.0, 2.5),
+    /// ];
+    /// let tree = KdTree::new(&positions).unwrap();
+    /// assert_eq!(vec![(0, 1, 1.0), (1, 2, 1.5)], tree.pairs(1.5));
+    /// ```
+    pub fn pairs(&self, cutoff: T) -> Vec<(usize, usize, T)> {
+        pairs(&self.positions, self.cell.as_ref(), cutoff, |point| {
+            self.within(point, cutoff)
+        })
+    }
+}
+
+/// Inserts a candidate into the sorted list of at most `k` nearest ones,
+/// keeping the nearer of images of the same position.
+fn insert<T: Float>(nearest: &mut Vec<(usize, T)>, k: usize, index: usize, squared: T) {
+    if let Some(i) = nearest.iter().position(|&(other, _)| other == index) {
+        if nearest[i].1 <= squared {
+            return;
+        }
+        nearest.remove(i);
+    }
+    if nearest.len() == k && nearest[k - 1].1 <= squared {
+        return;
+    }
+    let at = nearest
+        .iter()
+        .position(|&(_, other)| other > squared)
+        .unwrap_or(nearest.len());
+    nearest.insert(at, (index, squared));
+    nearest.truncate(k);
+}
+
+/// Arranges `indices` into a balanced k-d tree, splitting along the axis
+/// of the widest spread at each node.
+fn split<T: Float>(positions: &[Vector3d<T>], indices: &mut [usize], axes: &mut [usize]) {
+    if indices.len() <= 1 {
+        return;
+    }
+    let first = positions[indices[0]];
+    let (min, max) = indices.iter().fold((first, first), |(min, max), &i| {
+        (min.min(&positions[i]), max.max(&positions[i]))
+    });
+    let spread = max - min;
+    let axis = if spread.x >= spread.y && spread.x >= spread.z {
+        0
+    } else if spread.y >= spread.z {
+        1
+    } else {
+        2
+    };
+    let middle = indices.len() / 2;
+    indices.select_nth_unstable_by(middle, |&a, &b| {
+        component(&positions[a], axis)
+            .partial_cmp(&component(&positions[b], axis))
+            .unwrap_or(Ordering::Equal)
+    });
+    axes[middle] = axis;
+    let (left, right) = indices.split_at_mut(middle);
+    let (left_axes, right_axes) = axes.split_at_mut(middle);
+    split(positions, left, left_axes);
+    split(positions, &mut right[1..], &mut right_axes[1..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns positions by a linear congruential generator.
+    fn random(count: usize, size: f64) -> Vec<Vector3d<f64>> {
+        let mut state = 12345u64;
+        let mut next = || {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 11) as f64 / (1u64 << 53) as f64 * size
+        };
+        (0..count)
+            .map(|_| Vector3d::new(next(), next(), next()))
+            .collect()
+    }
+
+    fn brute_force(
+        positions: &[Vector3d<f64>],
+        cell: Option<&UnitCell<f64>>,
+        point: &Vector3d<f64>,
+        radius: f64,
+    ) -> Vec<usize> {
+        (0..positions.len())
+            .filter(|&i| displacement(point, &positions[i], cell).norm() <= radius)
+            .collect()
+    }
+
+    #[test]
+    fn test_within() {
+        let positions = random(500, 20.0);
+        let grid = Grid::new(&positions, 3.0).unwrap();
+        let tree = KdTree::new(&positions).unwrap();
+        for point in random(20, 24.0)
+            .iter()
+            .map(|p| *p - Vector3d::new(2.0, 2.0, 2.0))
+        {
+            for &radius in [0.5, 3.0, 4.5].iter() {
+                let expected = brute_force(&positions, None, &point, radius);
+                assert_eq!(expected, grid.within(&point, radius));
+                assert_eq!(expected, tree.within(&point, radius));
+            }
+        }
+        assert!(Grid::new(&positions, 0.0).is_none());
+        assert!(Grid::<f64>::new(&[], 1.0)
+            .unwrap()
+            .within(&Vector3d::zero(), 1.0)
+            .is_empty());
+        assert!(KdTree::<f64>::new(&[])
+            .unwrap()
+            .within(&Vector3d::zero(), 1.0)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_cell_count_overflow() {
+        // The requested cells would number about 1e24 along each axis.
+        let positions = [
+            Vector3d::new(0.0, 0.0, 0.0),
+            Vector3d::new(1000.0, 1000.0, 1000.0),
+        ];
+        let grid = Grid::new(&positions, 1e-5).unwrap();
+        assert!(grid.dimensions.iter().product::<usize>() <= 2 * MAX_CELLS_PER_POSITION);
+        assert_eq!(
+            vec![1],
+            grid.within(&Vector3d::new(1000.0, 1000.0, 1000.0), 1e-5)
+        );
+        assert!(grid.pairs(1e-5).is_empty());
+
+        // The extent overflows f32.
+        let positions = [
+            Vector3d::new(-3e38f32, 0.0, 0.0),
+            Vector3d::new(3e38, 0.0, 0.0),
+        ];
+        let grid = Grid::new(&positions, 1.0).unwrap();
+        assert_eq!(vec![0], grid.within(&positions[0], 1.0));
+
+        let cell = UnitCell::orthorhombic(1e10, 1e10, 1e10).unwrap();
+        let grid = Grid::periodic(&positions[..1], 1e-30, cell);
+        assert!(grid.unwrap().dimensions.iter().product::<usize>() <= MAX_CELLS_PER_POSITION);
+
+        let cell = UnitCell::orthorhombic(5.0, 5.0, 5.0).unwrap();
+        for &value in [f32::INFINITY, f32::NAN].iter() {
+            let positions = [Vector3d::zero(), Vector3d::new(value, 0.0, 0.0)];
+            assert!(Grid::new(&positions, 1.0).is_none());
+            assert!(Grid::periodic(&positions, 1.0, cell).is_none());
+        }
+    }
+
+    #[test]
+    fn test_non_finite_position() {
+        let cell = UnitCell::orthorhombic(5.0, 5.0, 5.0).unwrap();
+        for &value in [f64::INFINITY, f64::NAN].iter() {
+            let mut positions = random(200, 20.0);
+            positions[7].y = value;
+            assert!(KdTree::new(&positions).is_none());
+            assert!(KdTree::periodic(&positions, cell).is_none());
+        }
+    }
+
+    #[test]
+    fn test_periodic_within() {
+        // Positions beyond the cell are wrapped into it.
+        let positions: Vec<_> = random(400, 20.0)
+            .into_iter()
+            .map(|p| p - Vector3d::new(4.0, 2.0, 5.0))
+            .collect();
+        let orthorhombic = UnitCell::orthorhombic(12.0, 15.0, 10.0).unwrap();
+        let triclinic = UnitCell::from_vectors(
+            Vector3d::new(12.0, 0.0, 0.0),
+            Vector3d::new(4.0, 14.0, 0.0),
+            Vector3d::new(-3.0, 5.0, 11.0),
+        )
+        .unwrap();
+        for &cell in [orthorhombic, triclinic].iter() {
+            let grid = Grid::periodic(&positions, 2.5, cell).unwrap();
+            let tree = KdTree::periodic(&positions, cell).unwrap();
+            for point in random(20, 15.0) {
+                for &radius in [1.0, 2.5, 4.0].iter() {
+                    let expected = brute_force(&positions, Some(&cell), &point, radius);
+                    assert_eq!(expected, grid.within(&point, radius));
+                    assert_eq!(expected, tree.within(&point, radius));
+                }
+            }
+        }
+        assert!(Grid::periodic(&positions, 0.0, orthorhombic).is_none());
+    }
+
+    #[test]
+    fn test_huge_radius() {
+        let positions: Vec<Vector3d<f32>> = (0..50)
+            .map(|i| Vector3d::new(i as f32 * 0.7, (i % 7) as f32, (i % 3) as f32 * 2.0))
+            .collect();
+        let all: Vec<usize> = (0..positions.len()).collect();
+        let cell = UnitCell::orthorhombic(12.0f32, 15.0, 10.0).unwrap();
+        let grid = Grid::new(&positions, 2.5).unwrap();
+        let periodic = Grid::periodic(&positions, 2.5, cell).unwrap();
+        let point = Vector3d::new(1.0, 2.0, 3.0);
+        for &radius in [f32::INFINITY, 1e30].iter() {
+            assert_eq!(all, grid.within(&point, radius));
+            assert_eq!(all, periodic.within(&point, radius));
+        }
+    }
+
+    #[test]
+    fn test_nearest() {
+        let positions = random(300, 10.0);
+        let tree = KdTree::new(&positions).unwrap();
+        for point in random(10, 12.0) {
+            let mut expected: Vec<(usize, f64)> = positions
+                .iter()
+                .enumerate()
+                .map(|(i, p)| (i, point.distance(p)))
+                .collect();
+            expected.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+            expected.truncate(7);
+            assert_eq!(expected, tree.nearest(&point, 7));
+        }
+        assert_eq!(300, tree.nearest(&Vector3d::zero(), 1000).len());
+        assert!(tree.nearest(&Vector3d::zero(), 0).is_empty());
+
+        let cell = UnitCell::orthorhombic(10.0, 10.0, 10.0).unwrap();
+        let tree = KdTree::periodic(&positions, cell).unwrap();
+        for point in random(10, 10.0) {
+            let mut expected: Vec<(usize, f64)> = positions
+                .iter()
+                .enumerate()
+                .map(|(i, p)| (i, cell.distance(&point, p)))
+                .collect();
+            expected.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+            expected.truncate(5);
+            let nearest = tree.nearest(&point, 5);
+            for (a, b) in expected.iter().zip(nearest.iter()) {
+                assert_eq!(a.0, b.0);
+                assert!((a.1 - b.1).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pairs() {
+        let positions = random(300, 15.0);
+        let mut expected = Vec::new();
+        for i in 0..positions.len() {
+            for j in i + 1..positions.len() {
+                let distance = positions[i].distance(&positions[j]);
+                if distance <= 2.0 {
+                    expected.push((i, j, distance));
+                }
+            }
+        }
+        assert!(!expected.is_empty());
+        assert_eq!(expected, Grid::new(&positions, 2.0).unwrap().pairs(2.0));
+        assert_eq!(expected, KdTree::new(&positions).unwrap().pairs(2.0));
+        // A cell size smaller than the cutoff is fine.
+        assert_eq!(expected, Grid::new(&positions, 0.7).unwrap().pairs(2.0));
+    }
+}
@@ -0,0 +1,78 @@
+//! Reading and writing structures in the fixed-column PDB format.
+
+use std::error;
+use std::fmt;
+use std::io;
+
+mod reader;
+mod writer;
+
+pub use self::reader::{read, read_file};
+pub use self::writer::{write, write_file};
+
+/// An error occurred while reading a PDB file.
+#[derive(Debug)]
+pub struct Error {
+    line: usize,
+    kind: ErrorKind,
+}
+
+/// The reason why a PDB file could not be read.
+#[derive(Debug)]
+pub enum ErrorKind {
+    Io(io::Error),
+    /// A mandatory field is blank or the line is too short to contain it.
+    MissingField(&'static str),
+    /// A field could not be parsed.
+    InvalidField(&'static str, String),
+    /// A `MODEL` record appears before the previous model is closed.
+    NestedModel,
+    /// An `ENDMDL` record appears without a preceding `MODEL` record.
+    UnexpectedEndmdl,
+    /// A coordinate record appears outside of `MODEL`/`ENDMDL` in a file
+    /// with multiple models.
+    RecordOutsideModel,
+    /// The file ends before the last model is closed.
+    UnterminatedModel,
+}
+
+impl Error {
+    pub(crate) fn new(line: usize, kind: ErrorKind) -> Self {
+        Error { line, kind }
+    }
+
+    /// Returns the line number, starting from one, where the error occurred.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match self.kind {
+            ErrorKind::Io(ref err) => write!(f, "{}", err),
+            ErrorKind::MissingField(name) => write!(f, "missing {}", name),
+            ErrorKind::InvalidField(name, ref value) => {
+                write!(f, "invalid {}: {:?}", name, value)
+            }
+            ErrorKind::NestedModel => write!(f, "MODEL before ENDMDL"),
+            ErrorKind::UnexpectedEndmdl => write!(f, "ENDMDL without MODEL"),
+            ErrorKind::RecordOutsideModel => write!(f, "record outside of MODEL"),
+            ErrorKind::UnterminatedModel => write!(f, "MODEL without ENDMDL"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self.kind {
+            ErrorKind::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
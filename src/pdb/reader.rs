@@ -0,0 +1,360 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::str::FromStr;
+
+use super::{Error, ErrorKind};
+use atom::Atom;
+use structure::{Model, Structure};
+use Vector3d;
+
+/// Reads a structure from PDB formatted text.
+///
+/// `ATOM`, `HETATM`, `TER`, `MODEL`, `ENDMDL` and `CONECT` records are
+/// interpreted; all other records are ignored. Reading stops at an `END`
+/// record. A file without `MODEL` records yields a single model with
+/// serial number one.
+///
+/// # Example
+///
+/// ```
+/// let text = "\
+/// ATOM      1  N   ALA A   1      11.104   6.134  -6.504  1.00  0.00           N
+/// ATOM      2  CA  ALA A   1      11.639   6.071  -5.147  1.00  0.00           C
+/// TER       3      ALA A   1
+/// END
+/// ";
+/// let structure = biost::pdb::read(text.as_bytes()).unwrap();
+/// let atoms = &structure.models[0].atoms;
+/// assert_eq!(2, atoms.len());
+/// assert_eq!("CA", atoms[1].name);
+/// assert_eq!(11.639, atoms[1].position.x);
+/// ```
+pub fn read<R: BufRead>(reader: R) -> Result<Structure, Error> {
+    let mut structure = Structure::default();
+    let mut bonds = HashSet::new();
+    // The model opened by a MODEL record.
+    let mut current: Option<Model> = None;
+    // The model of coordinate records in a file without MODEL records.
+    let mut implicit: Option<Model> = None;
+    let mut lineno = 0;
+
+    for line in reader.lines() {
+        lineno += 1;
+        let line = line.map_err(|err| Error::new(lineno, ErrorKind::Io(err)))?;
+        let error = |kind| Error::new(lineno, kind);
+        match field(&line, 0, 6).trim_end() {
+            record @ "ATOM" | record @ "HETATM" => {
+                let atom = parse_atom(&line, record == "HETATM").map_err(error)?;
+                open_model(&mut current, &mut implicit, &structure)
+                    .map_err(error)?
+                    .atoms
+                    .push(atom);
+            }
+            "TER" => {
+                let model = open_model(&mut current, &mut implicit, &structure).map_err(error)?;
+                let end = model.atoms.len();
+                model.chain_ends.push(end);
+            }
+            "MODEL" => {
+                if current.is_some() {
+                    return Err(error(ErrorKind::NestedModel));
+                }
+                if implicit.is_some() {
+                    return Err(error(ErrorKind::RecordOutsideModel));
+                }
+                let serial = if field(&line, 10, 14).trim().is_empty() {
+                    structure.models.len() as i32 + 1
+                } else {
+                    parse(&line, 10, 14, "model serial number").map_err(error)?
+                };
+                current = Some(Model::new(serial));
+            }
+            "ENDMDL" => match current.take() {
+                Some(model) => structure.models.push(model),
+                None => return Err(error(ErrorKind::UnexpectedEndmdl)),
+            },
+            "CONECT" => {
+                let serial: u32 = parse(&line, 6, 11, "atom serial number").map_err(error)?;
+                for &start in [11, 16, 21, 26].iter() {
+                    if field(&line, start, start + 5).trim().is_empty() {
+                        continue;
+                    }
+                    let bonded: u32 = parse(&line, start, start + 5, "bonded atom serial number")
+                        .map_err(error)?;
+                    let bond = (serial.min(bonded), serial.max(bonded));
+                    if bonds.insert(bond) {
+                        structure.bonds.push(bond);
+                    }
+                }
+            }
+            "END" => break,
+            _ => {}
+        }
+    }
+
+    if current.is_some() {
+        return Err(Error::new(lineno, ErrorKind::UnterminatedModel));
+    }
+    if let Some(model) = implicit {
+        structure.models.push(model);
+    }
+    Ok(structure)
+}
+
+/// Reads a structure from a PDB file.
+///
+/// An error in opening the file is reported at line zero.
+pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Structure, Error> {
+    let file = File::open(path).map_err(|err| Error::new(0, ErrorKind::Io(err)))?;
+    read(BufReader::new(file))
+}
+
+/// Returns the model to which a coordinate record belongs.
+fn open_model<'a>(
+    current: &'a mut Option<Model>,
+    implicit: &'a mut Option<Model>,
+    structure: &Structure,
+) -> Result<&'a mut Model, ErrorKind> {
+    if let Some(ref mut model) = *current {
+        return Ok(model);
+    }
+    if !structure.models.is_empty() {
+        return Err(ErrorKind::RecordOutsideModel);
+    }
+    Ok(implicit.get_or_insert_with(|| Model::new(1)))
+}
+
+fn parse_atom(line: &str, hetero: bool) -> Result<Atom, ErrorKind> {
+    let x = parse(line, 30, 38, "x coordinate")?;
+    let y = parse(line, 38, 46, "y coordinate")?;
+    let z = parse(line, 46, 54, "z coordinate")?;
+    let mut atom = Atom::new(field(line, 12, 16).trim(), Vector3d::new(x, y, z));
+    atom.serial = parse(line, 6, 11, "atom serial number")?;
+    atom.alt_loc = parse_char(line, 16);
+    atom.res_name = field(line, 17, 20).trim().to_string();
+    atom.chain_id = field(line, 21, 22).trim().to_string();
+    atom.res_seq = parse(line, 22, 26, "residue sequence number")?;
+    atom.i_code = parse_char(line, 26);
+    if !field(line, 54, 60).trim().is_empty() {
+        atom.occupancy = parse(line, 54, 60, "occupancy")?;
+    }
+    if !field(line, 60, 66).trim().is_empty() {
+        atom.temp_factor = parse(line, 60, 66, "temperature factor")?;
+    }
+    atom.element = field(line, 76, 78).trim().to_string();
+    atom.charge = parse_charge(field(line, 78, 80))?;
+    atom.hetero = hetero;
+    Ok(atom)
+}
+
+/// Returns the columns `start..end`, counted from zero, of a line, which
+/// are truncated or empty if the line is short.
+fn field(line: &str, start: usize, end: usize) -> &str {
+    let end = end.min(line.len());
+    if start >= end {
+        return "";
+    }
+    line.get(start..end).unwrap_or("")
+}
+
+fn parse<T: FromStr>(
+    line: &str,
+    start: usize,
+    end: usize,
+    name: &'static str,
+) -> Result<T, ErrorKind> {
+    let value = field(line, start, end).trim();
+    if value.is_empty() {
+        return Err(ErrorKind::MissingField(name));
+    }
+    value
+        .parse()
+        .map_err(|_| ErrorKind::InvalidField(name, value.to_string()))
+}
+
+fn parse_char(line: &str, column: usize) -> Option<char> {
+    field(line, column, column + 1)
+        .chars()
+        .next()
+        .filter(|c| *c != ' ')
+}
+
+/// Parses a charge written as e.g. `2+` or `1-`.
+fn parse_charge(value: &str) -> Result<i8, ErrorKind> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Ok(0);
+    }
+    let invalid = || ErrorKind::InvalidField("charge", value.to_string());
+    let (magnitude, sign) = if trimmed.ends_with('+') || trimmed.ends_with('-') {
+        trimmed.split_at(trimmed.len() - 1)
+    } else if trimmed.starts_with('+') || trimmed.starts_with('-') {
+        let (sign, magnitude) = trimmed.split_at(1);
+        (magnitude, sign)
+    } else {
+        return Err(invalid());
+    };
+    let magnitude: i8 = if magnitude.is_empty() {
+        1
+    } else {
+        magnitude.parse().map_err(|_| invalid())?
+    };
+    Ok(if sign == "-" { -magnitude } else { magnitude })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRAMBIN: &str = "\
+HEADER    PLANT PROTEIN                           30-APR-81   1CRN
+ATOM      1  N   THR A   1      17.047  14.099   3.625  1.00 13.79           N
+ATOM      2  CA  THR A   1      16.967  12.784   4.338  1.00 10.80           C
+ATOM      3  C  ATHR A   1      15.685  12.755   5.133  0.50  9.19           C
+ATOM      4  C  BTHR A   1      15.600  12.700   5.100  0.50  9.19           C
+ATOM      5  N   THR A   2A     15.115  11.555   5.265  1.00  7.81           N
+TER       6      THR A   2A
+HETATM    7 FE   HEM B 200      10.000  -1.500   0.250  1.00 20.00          FE3+
+HETATM    8  O   HOH B 301       1.000   2.000   3.000
+CONECT    7    8
+CONECT    8    7
+END
+ATOM      9  N   THR A   3       0.000   0.000   0.000  1.00  0.00           N
+";
+
+    #[test]
+    fn test_read_atoms() {
+        let structure = read(CRAMBIN.as_bytes()).unwrap();
+        assert_eq!(1, structure.models.len());
+        let model = &structure.models[0];
+        assert_eq!(1, model.serial);
+        assert_eq!(7, model.atoms.len());
+        assert_eq!(vec![5], model.chain_ends);
+
+        let atom = &model.atoms[1];
+        assert_eq!(2, atom.serial);
+        assert_eq!("CA", atom.name);
+        assert_eq!(None, atom.alt_loc);
+        assert_eq!("THR", atom.res_name);
+        assert_eq!("A", atom.chain_id);
+        assert_eq!(1, atom.res_seq);
+        assert_eq!(None, atom.i_code);
+        assert_eq!(Vector3d::new(16.967, 12.784, 4.338), atom.position);
+        assert_eq!(1.0, atom.occupancy);
+        assert_eq!(10.80, atom.temp_factor);
+        assert_eq!("C", atom.element);
+        assert_eq!(0, atom.charge);
+        assert!(!atom.hetero);
+
+        assert_eq!(Some('A'), model.atoms[2].alt_loc);
+        assert_eq!(Some('B'), model.atoms[3].alt_loc);
+        assert_eq!(0.5, model.atoms[3].occupancy);
+        assert_eq!(Some('A'), model.atoms[4].i_code);
+        assert_eq!(2, model.atoms[4].res_seq);
+
+        let iron = &model.atoms[5];
+        assert!(iron.hetero);
+        assert_eq!("FE", iron.name);
+        assert_eq!("FE", iron.element);
+        assert_eq!(3, iron.charge);
+        assert_eq!(-1.5, iron.position.y);
+
+        // Truncated records use default values.
+        let water = &model.atoms[6];
+        assert_eq!(1.0, water.occupancy);
+        assert_eq!(0.0, water.temp_factor);
+        assert_eq!("", water.element);
+    }
+
+    #[test]
+    fn test_read_bonds() {
+        let structure = read(CRAMBIN.as_bytes()).unwrap();
+        assert_eq!(vec![(7, 8)], structure.bonds);
+    }
+
+    #[test]
+    fn test_read_models() {
+        let text = "\
+MODEL        1
+ATOM      1  CA  GLY A   1       1.000   1.000   1.000  1.00  0.00           C
+ENDMDL
+MODEL        2
+ATOM      1  CA  GLY A   1       2.000   2.000   2.000  1.00  0.00           C
+ENDMDL
+";
+        let structure = read(text.as_bytes()).unwrap();
+        assert_eq!(2, structure.models.len());
+        assert_eq!(1, structure.models[0].serial);
+        assert_eq!(2, structure.models[1].serial);
+        assert_eq!(1.0, structure.models[0].atoms[0].position.x);
+        assert_eq!(2.0, structure.models[1].atoms[0].position.x);
+    }
+
+    #[test]
+    fn test_model_errors() {
+        let text = "MODEL        1\nMODEL        2\n";
+        let err = read(text.as_bytes()).unwrap_err();
+        assert_eq!(2, err.line());
+        match *err.kind() {
+            ErrorKind::NestedModel => {}
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+
+        let err = read("ENDMDL\n".as_bytes()).unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnexpectedEndmdl => {}
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+
+        let err = read("MODEL        1\n".as_bytes()).unwrap_err();
+        match *err.kind() {
+            ErrorKind::UnterminatedModel => {}
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+
+        let text = "\
+MODEL        1
+ENDMDL
+ATOM      1  CA  GLY A   1       2.000   2.000   2.000  1.00  0.00           C
+";
+        let err = read(text.as_bytes()).unwrap_err();
+        assert_eq!(3, err.line());
+        match *err.kind() {
+            ErrorKind::RecordOutsideModel => {}
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_field_errors() {
+        let text = "\
+REMARK   1 THIS LINE IS IGNORED
+ATOM      1  CA  GLY A   1       1.000   x.000   1.000  1.00  0.00           C
+";
+        let err = read(text.as_bytes()).unwrap_err();
+        assert_eq!(2, err.line());
+        match *err.kind() {
+            ErrorKind::InvalidField("y coordinate", ref value) => assert_eq!("x.000", value),
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+        assert_eq!("line 2: invalid y coordinate: \"x.000\"", err.to_string());
+
+        let err = read("ATOM      1  CA  GLY A   1       1.000\n".as_bytes()).unwrap_err();
+        match *err.kind() {
+            ErrorKind::MissingField("y coordinate") => {}
+            ref kind => panic!("unexpected error: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_parse_charge() {
+        assert_eq!(0, parse_charge("  ").unwrap());
+        assert_eq!(2, parse_charge("2+").unwrap());
+        assert_eq!(-1, parse_charge("1-").unwrap());
+        assert_eq!(-1, parse_charge("-").unwrap());
+        assert_eq!(1, parse_charge("+1").unwrap());
+        assert!(parse_charge("2").is_err());
+    }
+}
@@ -0,0 +1,424 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use atom::Atom;
+use structure::{Model, Structure};
+
+/// Writes a structure in the PDB format.
+///
+/// Models are enclosed in `MODEL`/`ENDMDL` records unless the structure
+/// consists of a single model with serial number one. A `TER` record is
+/// written at each chain end of a model; if the model does not record its
+/// chain ends, they are inferred from changes of the chain identifier
+/// between polymer atoms. Bonds are written as `CONECT` records.
+///
+/// Atoms are renumbered from one in each model, with each `TER` record
+/// taking a serial number of its own. Bonds, given by the serial numbers of
+/// atoms, are renumbered as the first model containing the atoms.
+///
+/// Values which do not fit into the fixed columns, such as serial numbers
+/// above 99999 or multi-character chain identifiers, and bonds between
+/// unknown atoms result in an error of kind `InvalidInput`.
+///
+/// # Example
+///
+/// ```
+/// use biost::{Atom, Model, Structure, Vector3d};
+/// let mut model = Model::new(1);
+/// let mut atom = Atom::new("CA", Vector3d::new(1.0, 2.0, 3.0));
+/// atom.serial = 1;
+/// atom.res_name = "GLY".to_string();
+/// atom.chain_id = "A".to_string();
+/// atom.res_seq = 1;
+/// atom.element = "C".to_string();
+/// model.atoms.push(atom);
+/// let structure = Structure { models: vec![model], bonds: vec![] };
+///
+/// let mut buffer = Vec::new();
+/// biost::pdb::write(&mut buffer, &structure).unwrap();
+/// let text = String::from_utf8(buffer).unwrap();
+/// assert_eq!(
+///     "ATOM      1  CA  GLY A   1       1.000   2.000   3.000  1.00  0.00           C  ",
+///     text.lines().next().unwrap()
+/// );
+/// ```
+pub fn write<W: Write>(mut writer: W, structure: &Structure) -> io::Result<()> {
+    let enclose = structure.models.len() > 1
+        || structure
+            .models
+            .first()
+            .is_some_and(|model| model.serial != 1);
+    let mut serials = BTreeMap::new();
+    for model in structure.models.iter() {
+        if enclose {
+            writeln!(writer, "MODEL     {:>4}", model.serial)?;
+        }
+        for (old, new) in write_model(&mut writer, model)? {
+            serials.entry(old).or_insert(new);
+        }
+        if enclose {
+            writeln!(writer, "ENDMDL")?;
+        }
+    }
+    write_bonds(&mut writer, &structure.bonds, &serials)?;
+    writeln!(writer, "END")
+}
+
+/// Writes a structure to a PDB file.
+pub fn write_file<P: AsRef<Path>>(path: P, structure: &Structure) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write(&mut writer, structure)?;
+    writer.flush()
+}
+
+/// Writes the atoms of a model, and returns the pairs of the original and
+/// written serial numbers of the atoms.
+fn write_model<W: Write>(writer: &mut W, model: &Model) -> io::Result<Vec<(u32, u32)>> {
+    let chain_ends = if model.chain_ends.is_empty() {
+        infer_chain_ends(&model.atoms)
+    } else {
+        model.chain_ends.clone()
+    };
+    let mut chain_ends = chain_ends.iter().peekable();
+    // TER records before the first atom are meaningless and omitted.
+    while chain_ends.peek() == Some(&&0) {
+        chain_ends.next();
+    }
+    let mut serials = Vec::with_capacity(model.atoms.len());
+    let mut next = 1;
+    for (i, atom) in model.atoms.iter().enumerate() {
+        write_atom(writer, atom, next)?;
+        serials.push((atom.serial, next));
+        next += 1;
+        let mut terminated = false;
+        while chain_ends.peek() == Some(&&(i + 1)) {
+            chain_ends.next();
+            terminated = true;
+        }
+        if terminated {
+            write_ter(writer, atom, next)?;
+            next += 1;
+        }
+    }
+    Ok(serials)
+}
+
+/// Returns the positions after the last polymer atom of each chain.
+fn infer_chain_ends(atoms: &[Atom]) -> Vec<usize> {
+    let mut ends = Vec::new();
+    for (i, atom) in atoms.iter().enumerate() {
+        if atom.hetero {
+            continue;
+        }
+        let end = match atoms.get(i + 1) {
+            Some(next) => next.hetero || next.chain_id != atom.chain_id,
+            None => true,
+        };
+        if end {
+            ends.push(i + 1);
+        }
+    }
+    ends
+}
+
+fn write_atom<W: Write>(writer: &mut W, atom: &Atom, number: u32) -> io::Result<()> {
+    let position = &atom.position;
+    writeln!(
+        writer,
+        "{:<6}{:>5} {:<4}{}{:>3} {}{:>4}{}   {:>8}{:>8}{:>8}{:>6}{:>6}          {:>2}{:<2}",
+        if atom.hetero { "HETATM" } else { "ATOM" },
+        serial(number)?,
+        justify_name(atom)?,
+        atom.alt_loc.unwrap_or(' '),
+        fit("residue name", &atom.res_name, 3)?,
+        fit("chain identifier", &atom.chain_id, 1)?,
+        res_seq(atom.res_seq)?,
+        atom.i_code.unwrap_or(' '),
+        real("coordinate", position.x, 8, 3)?,
+        real("coordinate", position.y, 8, 3)?,
+        real("coordinate", position.z, 8, 3)?,
+        real("occupancy", atom.occupancy, 6, 2)?,
+        real("temperature factor", atom.temp_factor, 6, 2)?,
+        fit("element", &atom.element, 2)?,
+        charge(atom.charge)?,
+    )
+}
+
+fn write_ter<W: Write>(writer: &mut W, atom: &Atom, number: u32) -> io::Result<()> {
+    writeln!(
+        writer,
+        "TER   {:>5}      {:>3} {}{:>4}{}",
+        serial(number)?,
+        fit("residue name", &atom.res_name, 3)?,
+        fit("chain identifier", &atom.chain_id, 1)?,
+        res_seq(atom.res_seq)?,
+        atom.i_code.unwrap_or(' '),
+    )
+}
+
+fn write_bonds<W: Write>(
+    writer: &mut W,
+    bonds: &[(u32, u32)],
+    serials: &BTreeMap<u32, u32>,
+) -> io::Result<()> {
+    let renumber = |serial: u32| {
+        serials
+            .get(&serial)
+            .cloned()
+            .ok_or_else(|| invalid(format!("bond to atom {} which does not exist", serial)))
+    };
+    let mut partners: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+    for &(a, b) in bonds.iter() {
+        let (a, b) = (renumber(a)?, renumber(b)?);
+        partners.entry(a).or_default().push(b);
+        partners.entry(b).or_default().push(a);
+    }
+    for (atom, bonded) in partners.iter_mut() {
+        bonded.sort();
+        for chunk in bonded.chunks(4) {
+            write!(writer, "CONECT{:>5}", serial(*atom)?)?;
+            for partner in chunk.iter() {
+                write!(writer, "{:>5}", serial(*partner)?)?;
+            }
+            writeln!(writer)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the atom name padded to four columns.
+///
+/// By convention, names shorter than four characters start at the second
+/// column when the element symbol is a single letter, so that e.g. the
+/// alpha carbon ` CA ` is distinguished from calcium `CA  `. Names with a
+/// leading digit, such as `1HB`, and blank elements are handled as if the
+/// digit or the first letter were the second character of the element.
+fn justify_name(atom: &Atom) -> io::Result<String> {
+    let name = fit("atom name", &atom.name, 4)?;
+    let leading_digit = name.starts_with(|c: char| c.is_ascii_digit());
+    if name.len() < 4 && !leading_digit && atom.element.trim().len() < 2 {
+        Ok(format!(" {:<3}", name))
+    } else {
+        Ok(format!("{:<4}", name))
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn fit<'a>(name: &str, value: &'a str, width: usize) -> io::Result<&'a str> {
+    if value.chars().count() > width {
+        return Err(invalid(format!(
+            "{} {:?} is longer than {} characters",
+            name, value, width
+        )));
+    }
+    Ok(value)
+}
+
+fn serial(value: u32) -> io::Result<u32> {
+    if value > 99_999 {
+        return Err(invalid(format!("serial number {} exceeds 99999", value)));
+    }
+    Ok(value)
+}
+
+fn res_seq(value: i32) -> io::Result<i32> {
+    if !(-999..=9999).contains(&value) {
+        return Err(invalid(format!(
+            "residue sequence number {} does not fit into 4 columns",
+            value
+        )));
+    }
+    Ok(value)
+}
+
+fn real(name: &str, value: f32, width: usize, precision: usize) -> io::Result<String> {
+    let formatted = format!("{:.*}", precision, value);
+    if formatted.len() > width {
+        return Err(invalid(format!(
+            "{} {} does not fit into {} columns",
+            name, value, width
+        )));
+    }
+    Ok(formatted)
+}
+
+fn charge(value: i8) -> io::Result<String> {
+    match value {
+        0 => Ok(String::new()),
+        1..=9 => Ok(format!("{}+", value)),
+        -9..=-1 => Ok(format!("{}-", -value)),
+        _ => Err(invalid(format!(
+            "charge {} does not fit into 2 columns",
+            value
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::read;
+    use super::*;
+    use Vector3d;
+
+    fn atom(serial: u32, name: &str, element: &str, chain_id: &str) -> Atom {
+        let mut atom = Atom::new(name, Vector3d::new(-1.5, 20.25, 300.125));
+        atom.serial = serial;
+        atom.res_name = "ALA".to_string();
+        atom.chain_id = chain_id.to_string();
+        atom.res_seq = 10;
+        atom.element = element.to_string();
+        atom
+    }
+
+    fn to_string(structure: &Structure) -> String {
+        let mut buffer = Vec::new();
+        write(&mut buffer, structure).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_columns() {
+        let mut model = Model::new(1);
+        model.atoms.push(atom(1, "CA", "C", "A"));
+        let mut iron = atom(2, "FE", "FE", "A");
+        iron.hetero = true;
+        iron.res_name = "HEM".to_string();
+        iron.res_seq = -5;
+        iron.alt_loc = Some('B');
+        iron.i_code = Some('C');
+        iron.occupancy = 0.5;
+        iron.temp_factor = 12.345;
+        iron.charge = 3;
+        model.atoms.push(iron);
+        model.atoms.push(atom(3, "HD21", "H", "A"));
+        model.atoms.push(atom(4, "1HB", "H", "A"));
+        let structure = Structure {
+            models: vec![model],
+            bonds: vec![],
+        };
+        let text = to_string(&structure);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            vec![
+                "ATOM      1  CA  ALA A  10      -1.500  20.250 300.125  1.00  0.00           C  ",
+                "TER       2      ALA A  10 ",
+                "HETATM    3 FE  BHEM A  -5C     -1.500  20.250 300.125  0.50 12.35          FE3+",
+                "ATOM      4 HD21 ALA A  10      -1.500  20.250 300.125  1.00  0.00           H  ",
+                "ATOM      5 1HB  ALA A  10      -1.500  20.250 300.125  1.00  0.00           H  ",
+                "TER       6      ALA A  10 ",
+                "END",
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn test_models_and_bonds() {
+        let mut model1 = Model::new(1);
+        for serial in 1..=6 {
+            model1.atoms.push(atom(serial, "C", "C", "A"));
+        }
+        model1.chain_ends.push(2);
+        let mut model2 = model1.clone();
+        model2.serial = 2;
+        let mut structure = Structure {
+            models: vec![model1, model2],
+            bonds: vec![(1, 2), (1, 3), (1, 4), (1, 5), (1, 6)],
+        };
+        let text = to_string(&structure);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!("MODEL        1", lines[0]);
+        assert_eq!("ENDMDL", lines[8]);
+        assert_eq!("MODEL        2", lines[9]);
+        assert_eq!("ENDMDL", lines[17]);
+        // The serial numbers after the TER record are shifted.
+        assert_eq!("CONECT    1    2    4    5    6", lines[18]);
+        assert_eq!("CONECT    1    7", lines[19]);
+        assert_eq!("CONECT    2    1", lines[20]);
+        assert_eq!("END", lines[lines.len() - 1]);
+
+        structure.bonds.push((1, 7));
+        let err = write(Vec::new(), &structure).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
+
+    #[test]
+    fn test_renumbering() {
+        // Serial numbers of TER records are not shared with atoms.
+        let text = "\
+ATOM      1  N   THR A   1      17.047  14.099   3.625  1.00 13.79           N
+TER       2      THR A   1
+HETATM    2  O   HOH A 301      10.000   2.000   3.000  1.00  0.00           O
+END
+";
+        let structure = read(text.as_bytes()).unwrap();
+        let written = to_string(&structure);
+        let lines: Vec<&str> = written.lines().map(|line| line.trim_end()).collect();
+        assert_eq!("TER       2      THR A   1", lines[1]);
+        assert!(lines[2].starts_with("HETATM    3  O   HOH A 301"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = "\
+MODEL        1
+ATOM      1  N   THR A   1      17.047  14.099   3.625  1.00 13.79           N
+ATOM      2  CA ATHR A   1      16.967  12.784   4.338  0.50 10.80           C
+ATOM      3  CA BTHR A   1      16.900  12.700   4.300  0.50 10.80           C
+ATOM      4  N   THR A   2A     15.115  11.555   5.265  1.00  7.81           N
+TER       5      THR A   2A
+HETATM    6 FE   HEM B 200      10.000  -1.500   0.250  1.00 20.00          FE3+
+HETATM    7  O   HOH B 301    -100.000   2.000   3.000  1.00  0.00           O1-
+ENDMDL
+MODEL        2
+ATOM      1  N   THR A   1      17.000  14.000   3.000  1.00 13.79           N
+TER       2      THR A   1
+ENDMDL
+CONECT    6    7
+CONECT    7    6
+END
+";
+        let structure = read(text.as_bytes()).unwrap();
+        let written = to_string(&structure);
+        let lines: Vec<&str> = written.lines().map(|line| line.trim_end()).collect();
+        assert_eq!(text.lines().collect::<Vec<_>>(), lines);
+        assert_eq!(structure, read(written.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let mut model = Model::new(1);
+        model.atoms.push(atom(1, "CA", "C", "A"));
+        model.atoms[0].temp_factor = 1000.0;
+        let structure = Structure {
+            models: vec![model],
+            bonds: vec![],
+        };
+        let err = write(Vec::new(), &structure).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+
+        let mut model = Model::new(1);
+        model.atoms.push(atom(1, "CA", "C", "A"));
+        model.atoms[0].occupancy = -100.0;
+        let structure = Structure {
+            models: vec![model],
+            bonds: vec![],
+        };
+        let err = write(Vec::new(), &structure).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+
+        let mut model = Model::new(1);
+        model.atoms.push(atom(1, "CA", "C", "AB"));
+        let structure = Structure {
+            models: vec![model],
+            bonds: vec![],
+        };
+        let err = write(Vec::new(), &structure).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
+}
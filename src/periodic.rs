@@ -0,0 +1,516 @@
+//! Periodic boundary conditions of simulation boxes and crystals.
+
+use std::collections::VecDeque;
+
+use float::Float;
+use matrix::Matrix3;
+use Vector3d;
+
+/// A unit cell spanned by three box vectors `a`, `b` and `c`, which may be
+/// orthorhombic or triclinic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnitCell<T: Float = f32> {
+    /// The box vectors as the columns.
+    vectors: Matrix3<T>,
+    inverse: Matrix3<T>,
+    orthorhombic: bool,
+}
+
+impl<T: Float> UnitCell<T> {
+    /// Returns an orthorhombic cell with the box vectors along the axes.
+    ///
+    /// Returns `None` if any of the lengths is not positive.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::periodic::UnitCell;
+    /// let cell = UnitCell::orthorhombic(10.0, 20.0, 30.0).unwrap();
+    /// assert_eq!(6000.0, cell.volume());
+    /// assert!(UnitCell::orthorhombic(10.0, 0.0, 30.0).is_none());
+    /// ```
+    pub fn orthorhombic(a: T, b: T, c: T) -> Option<Self> {
+        if [a, b, c]
+            .iter()
+            .any(|&length| length <= T::zero() || !length.is_finite())
+        {
+            return None;
+        }
+        Self::from_matrix(Matrix3::diagonal(a, b, c))
+    }
+
+    /// Returns a cell with given lengths of the box vectors and angles in
+    /// radians between them, `alpha` between `b` and `c`, `beta` between
+    /// `c` and `a` and `gamma` between `a` and `b`, as in the CRYST1
+    /// record of PDB files.
+    ///
+    /// The vector `a` is along the x axis and `b` in the xy plane. Returns
+    /// `None` if any of the lengths is not positive or the angles do not
+    /// form a cell.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::periodic::UnitCell;
+    /// use std::f32::consts::FRAC_PI_2;
+    /// let angle = 60f32.to_radians();
+    /// let cell = UnitCell::from_parameters(10.0, 10.0, 10.0, FRAC_PI_2, FRAC_PI_2, angle).unwrap();
+    /// let [_, b, _] = cell.vectors();
+    /// assert!((b.x - 5.0).abs() < 1e-5);
+    /// assert!(UnitCell::from_parameters(1.0, 1.0, 1.0, 0.1, 0.1, 3.0).is_none());
+    /// ```
+    pub fn from_parameters(a: T, b: T, c: T, alpha: T, beta: T, gamma: T) -> Option<Self> {
+        if [a, b, c]
+            .iter()
+            .any(|&length| length <= T::zero() || !length.is_finite())
+        {
+            return None;
+        }
+        let (cos_alpha, cos_beta, cos_gamma) = (alpha.cos(), beta.cos(), gamma.cos());
+        let sin_gamma = gamma.sin();
+        let y = (cos_alpha - cos_beta * cos_gamma) / sin_gamma;
+        let z_squared = T::one() - cos_beta * cos_beta - y * y;
+        if z_squared <= T::zero() || z_squared.is_nan() {
+            return None;
+        }
+        Self::from_vectors(
+            Vector3d::new(a, T::zero(), T::zero()),
+            Vector3d::new(b * cos_gamma, b * sin_gamma, T::zero()),
+            Vector3d::new(c * cos_beta, c * y, c * z_squared.sqrt()),
+        )
+    }
+
+    /// Returns a cell spanned by given box vectors.
+    ///
+    /// Returns `None` if the vectors are linearly dependent.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::periodic::UnitCell;
+    /// use biost::Vector3d;
+    /// let a = Vector3d::new(4.0, 0.0, 0.0);
+    /// let b = Vector3d::new(2.0, 4.0, 0.0);
+    /// let c = Vector3d::new(0.0, 0.0, 5.0);
+    /// let cell = UnitCell::from_vectors(a, b, c).unwrap();
+    /// assert_eq!(80.0, cell.volume());
+    /// assert!(UnitCell::from_vectors(a, a * 2.0, c).is_none());
+    /// ```
+    pub fn from_vectors(a: Vector3d<T>, b: Vector3d<T>, c: Vector3d<T>) -> Option<Self> {
+        Self::from_matrix(Matrix3::from_columns(a, b, c))
+    }
+
+    fn from_matrix(vectors: Matrix3<T>) -> Option<Self> {
+        let inverse = vectors.inverse()?;
+        let mut orthorhombic = true;
+        for i in 0..3 {
+            for j in 0..3 {
+                orthorhombic &= i == j || vectors[(i, j)] == T::zero();
+            }
+        }
+        Some(UnitCell {
+            vectors,
+            inverse,
+            orthorhombic,
+        })
+    }
+
+    /// Returns the box vectors `a`, `b` and `c`.
+    pub fn vectors(&self) -> [Vector3d<T>; 3] {
+        [
+            self.vectors.column(0),
+            self.vectors.column(1),
+            self.vectors.column(2),
+        ]
+    }
+
+    /// Returns the lengths of the box vectors.
+    pub fn lengths(&self) -> Vector3d<T> {
+        let [a, b, c] = self.vectors();
+        Vector3d::new(a.norm(), b.norm(), c.norm())
+    }
+
+    /// Returns the distances between the opposite faces of the cell, i.e.
+    /// its widths perpendicular to the planes of `b` and `c`, `c` and `a`
+    /// and `a` and `b`.
+    ///
+    /// A position has at most one image closer than half the smallest
+    /// width to another.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::periodic::UnitCell;
+    /// use biost::Vector3d;
+    /// let a = Vector3d::new(10.0, 0.0, 0.0);
+    /// let b = Vector3d::new(5.0, 10.0, 0.0);
+    /// let c = Vector3d::new(0.0, 0.0, 10.0);
+    /// let cell = UnitCell::from_vectors(a, b, c).unwrap();
+    /// let widths = cell.widths();
+    /// assert!((widths.x - 100.0 / 125f32.sqrt()).abs() < 1e-5);
+    /// assert!((widths.y - 10.0).abs() < 1e-5 && (widths.z - 10.0).abs() < 1e-5);
+    /// ```
+    pub fn widths(&self) -> Vector3d<T> {
+        let [a, b, c] = self.vectors();
+        let volume = self.volume();
+        Vector3d::new(
+            volume / b.cross(&c).norm(),
+            volume / c.cross(&a).norm(),
+            volume / a.cross(&b).norm(),
+        )
+    }
+
+    /// Returns the angles `alpha`, `beta` and `gamma` in radians between
+    /// the box vectors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::periodic::UnitCell;
+    /// let cell = UnitCell::from_parameters(5.0f64, 6.0, 7.0, 1.2, 1.4, 1.9).unwrap();
+    /// let lengths = cell.lengths();
+    /// let (alpha, beta, gamma) = cell.angles();
+    /// assert!((lengths.y - 6.0).abs() < 1e-9 && (lengths.z - 7.0).abs() < 1e-9);
+    /// assert!((alpha - 1.2).abs() < 1e-9 && (beta - 1.4).abs() < 1e-9 && (gamma - 1.9).abs() < 1e-9);
+    /// ```
+    pub fn angles(&self) -> (T, T, T) {
+        let [a, b, c] = self.vectors();
+        (b.angle(&c), c.angle(&a), a.angle(&b))
+    }
+
+    /// Returns the volume of the cell.
+    pub fn volume(&self) -> T {
+        self.vectors.determinant().abs()
+    }
+
+    /// Returns whether the box vectors are along the axes.
+    pub fn is_orthorhombic(&self) -> bool {
+        self.orthorhombic
+    }
+
+    /// Returns the fractional coordinates of a position, i.e. its
+    /// coordinates in the basis of the box vectors.
+    pub fn to_fractional(&self, position: &Vector3d<T>) -> Vector3d<T> {
+        self.inverse * *position
+    }
+
+    /// Returns the Cartesian position of fractional coordinates.
+    pub fn to_cartesian(&self, fractional: &Vector3d<T>) -> Vector3d<T> {
+        self.vectors * *fractional
+    }
+
+    /// Returns the image of a position in the primary cell, whose
+    /// fractional coordinates are in `[0, 1)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::periodic::UnitCell;
+    /// use biost::Vector3d;
+    /// let cell = UnitCell::orthorhombic(10.0, 10.0, 10.0).unwrap();
+    /// let wrapped = cell.wrap(&Vector3d::new(-1.0, 12.0, 5.0));
+    /// assert_eq!(Vector3d::new(9.0, 2.0, 5.0), wrapped);
+    /// ```
+    pub fn wrap(&self, position: &Vector3d<T>) -> Vector3d<T> {
+        let fractional = self.to_fractional(position);
+        let shift = Vector3d::new(
+            fractional.x.floor(),
+            fractional.y.floor(),
+            fractional.z.floor(),
+        );
+        *position - self.to_cartesian(&shift)
+    }
+
+    /// Moves every position into the primary cell.
+    pub fn wrap_all(&self, positions: &mut [Vector3d<T>]) {
+        for position in positions.iter_mut() {
+            *position = self.wrap(position);
+        }
+    }
+
+    /// Returns the shortest vector from `a` to any image of `b`, following
+    /// the minimum-image convention.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::periodic::UnitCell;
+    /// use biost::Vector3d;
+    /// let cell = UnitCell::orthorhombic(10.0, 10.0, 10.0).unwrap();
+    /// let a = Vector3d::new(1.0, 5.0, 5.0);
+    /// let b = Vector3d::new(9.0, 5.0, 25.5);
+    /// let d = cell.minimum_image(&a, &b);
+    /// assert!((d - Vector3d::new(-2.0, 0.0, 0.5)).norm() < 1e-5);
+    /// assert!((cell.distance(&a, &b) - 4.25f32.sqrt()).abs() < 1e-5);
+    /// ```
+    pub fn minimum_image(&self, a: &Vector3d<T>, b: &Vector3d<T>) -> Vector3d<T> {
+        let fractional = self.to_fractional(&(*b - *a));
+        let fractional = Vector3d::new(
+            fractional.x - fractional.x.round(),
+            fractional.y - fractional.y.round(),
+            fractional.z - fractional.z.round(),
+        );
+        let d = self.to_cartesian(&fractional);
+        if self.orthorhombic {
+            return d;
+        }
+        // The rounded image may not be the nearest in a skewed cell, so
+        // that the neighboring ones are examined as well.
+        let mut nearest = d;
+        let mut squared = d.norm_squared();
+        let steps = [-T::one(), T::zero(), T::one()];
+        for &i in steps.iter() {
+            for &j in steps.iter() {
+                for &k in steps.iter() {
+                    let image = d + self.to_cartesian(&Vector3d::new(i, j, k));
+                    if image.norm_squared() < squared {
+                        nearest = image;
+                        squared = image.norm_squared();
+                    }
+                }
+            }
+        }
+        nearest
+    }
+
+    /// Returns the distance between `a` and the nearest image of `b`.
+    pub fn distance(&self, a: &Vector3d<T>, b: &Vector3d<T>) -> T {
+        self.minimum_image(a, b).norm()
+    }
+
+    /// Translates the positions of each molecule by box vectors so that
+    /// bonded positions are next to each other, i.e. makes molecules split
+    /// across the boundaries whole again.
+    ///
+    /// The molecules are the connected components of `bonds`, given as
+    /// pairs of indices, and the first position of each stays in place.
+    /// Returns `None` without moving any position if a bond refers to an
+    /// index out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::periodic::UnitCell;
+    /// use biost::Vector3d;
+    /// let cell = UnitCell::orthorhombic(10.0, 10.0, 10.0).unwrap();
+    /// let mut positions = [
+    ///     Vector3d::new(9.5, 5.0, 5.0),
+    ///     Vector3d::new(0.5, 5.0, 5.0),
+    ///     Vector3d::new(1.5, 5.0, 5.0),
+    /// ];
+    /// cell.make_whole(&mut positions, &[(0, 1), (1, 2)]).unwrap();
+    /// assert_eq!(Vector3d::new(10.5, 5.0, 5.0), positions[1]);
+    /// assert_eq!(Vector3d::new(11.5, 5.0, 5.0), positions[2]);
+    /// ```
+    #[must_use]
+    pub fn make_whole(
+        &self,
+        positions: &mut [Vector3d<T>],
+        bonds: &[(usize, usize)],
+    ) -> Option<()> {
+        if bonds
+            .iter()
+            .any(|&(i, j)| i >= positions.len() || j >= positions.len())
+        {
+            return None;
+        }
+        let mut neighbors = vec![Vec::new(); positions.len()];
+        for &(i, j) in bonds {
+            neighbors[i].push(j);
+            neighbors[j].push(i);
+        }
+        let mut visited = vec![false; positions.len()];
+        let mut queue = VecDeque::new();
+        for start in 0..positions.len() {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            queue.push_back(start);
+            while let Some(i) = queue.pop_front() {
+                for &j in neighbors[i].iter() {
+                    if !visited[j] {
+                        visited[j] = true;
+                        positions[j] =
+                            positions[i] + self.minimum_image(&positions[i], &positions[j]);
+                        queue.push_back(j);
+                    }
+                }
+            }
+        }
+        Some(())
+    }
+
+    /// Translates each position by box vectors to the image nearest to the
+    /// corresponding reference position, e.g. in the previous frame of a
+    /// trajectory, so that positions do not jump across the boundaries.
+    ///
+    /// Returns `None` without moving any position if the numbers of
+    /// positions differ.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::periodic::UnitCell;
+    /// use biost::Vector3d;
+    /// let cell = UnitCell::orthorhombic(10.0, 10.0, 10.0).unwrap();
+    /// let previous = [Vector3d::new(9.8, 5.0, 5.0)];
+    /// let mut current = [Vector3d::new(0.3, 5.0, 5.0)];
+    /// cell.unwrap_positions(&previous, &mut current).unwrap();
+    /// assert_eq!(Vector3d::new(10.3, 5.0, 5.0), current[0]);
+    /// ```
+    #[must_use]
+    pub fn unwrap_positions(
+        &self,
+        reference: &[Vector3d<T>],
+        positions: &mut [Vector3d<T>],
+    ) -> Option<()> {
+        if reference.len() != positions.len() {
+            return None;
+        }
+        for (position, reference) in positions.iter_mut().zip(reference) {
+            *position = *reference + self.minimum_image(reference, position);
+        }
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triclinic() -> UnitCell<f64> {
+        UnitCell::from_vectors(
+            Vector3d::new(10.0, 0.0, 0.0),
+            Vector3d::new(4.0, 9.0, 0.0),
+            Vector3d::new(-3.0, 2.5, 8.0),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parameters() {
+        let cell = UnitCell::from_parameters(
+            10.0f64,
+            12.0,
+            14.0,
+            80f64.to_radians(),
+            95f64.to_radians(),
+            110f64.to_radians(),
+        )
+        .unwrap();
+        let lengths = cell.lengths();
+        assert!((lengths - Vector3d::new(10.0, 12.0, 14.0)).norm() < 1e-9);
+        let (alpha, beta, gamma) = cell.angles();
+        assert!((alpha.to_degrees() - 80.0).abs() < 1e-9);
+        assert!((beta.to_degrees() - 95.0).abs() < 1e-9);
+        assert!((gamma.to_degrees() - 110.0).abs() < 1e-9);
+        assert!(!cell.is_orthorhombic());
+
+        let right = 90f64.to_radians();
+        let cell = UnitCell::from_parameters(10.0, 12.0, 14.0, right, right, right).unwrap();
+        assert!((cell.volume() - 1680.0).abs() < 1e-9);
+        assert!(UnitCell::<f64>::orthorhombic(1.0, 2.0, 3.0)
+            .unwrap()
+            .is_orthorhombic());
+    }
+
+    #[test]
+    fn test_fractional() {
+        let cell = triclinic();
+        let position = Vector3d::new(3.0, -2.0, 7.5);
+        let fractional = cell.to_fractional(&position);
+        assert!((cell.to_cartesian(&fractional) - position).norm() < 1e-12);
+        let [a, b, c] = cell.vectors();
+        assert!(
+            (cell.to_fractional(&(a + b * 2.0 - c)) - Vector3d::new(1.0, 2.0, -1.0)).norm() < 1e-12
+        );
+    }
+
+    #[test]
+    fn test_wrap() {
+        let cell = triclinic();
+        let [a, b, c] = cell.vectors();
+        let inside = a * 0.2 + b * 0.7 + c * 0.4;
+        let outside = inside + a * 3.0 - b + c * -2.0;
+        assert!((cell.wrap(&outside) - inside).norm() < 1e-9);
+        let mut positions = [outside, inside];
+        cell.wrap_all(&mut positions);
+        for position in positions.iter() {
+            let fractional = cell.to_fractional(position);
+            for &value in [fractional.x, fractional.y, fractional.z].iter() {
+                assert!((0.0..1.0).contains(&value));
+            }
+        }
+    }
+
+    #[test]
+    fn test_minimum_image() {
+        // Compare with the brute force over many images.
+        let cell = triclinic();
+        let a = Vector3d::new(1.0, 1.0, 1.0);
+        for b in [
+            Vector3d::new(9.0, 8.5, 7.5),
+            Vector3d::new(12.0, -3.0, 4.0),
+            Vector3d::new(-5.5, 6.0, 15.0),
+        ]
+        .iter()
+        {
+            let mut expected = f64::INFINITY;
+            for i in -3..=3 {
+                for j in -3..=3 {
+                    for k in -3..=3 {
+                        let shift = cell.to_cartesian(&Vector3d::new(i as f64, j as f64, k as f64));
+                        expected = expected.min(a.distance(&(*b + shift)));
+                    }
+                }
+            }
+            assert!((cell.distance(&a, b) - expected).abs() < 1e-9);
+            let d = cell.minimum_image(&a, b);
+            assert!((cell.wrap(&(a + d)) - cell.wrap(b)).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_make_whole() {
+        let cell = triclinic();
+        let [a, b, _] = cell.vectors();
+        // Two molecules, one of which is split across the boundaries.
+        let whole = [
+            Vector3d::new(9.0, 8.0, 7.0),
+            Vector3d::new(10.0, 8.5, 7.5),
+            Vector3d::new(11.0, 9.5, 7.0),
+            Vector3d::new(3.0, 3.0, 3.0),
+            Vector3d::new(4.0, 3.0, 3.0),
+        ];
+        let mut positions = whole;
+        positions[1] -= a;
+        positions[2] -= a + b;
+        positions[4] += b;
+        assert_eq!(
+            Some(()),
+            cell.make_whole(&mut positions, &[(1, 2), (0, 1), (3, 4)])
+        );
+        for (position, expected) in positions.iter().zip(whole.iter()) {
+            assert!((*position - *expected).norm() < 1e-9);
+        }
+        let moved = positions;
+        assert_eq!(None, cell.make_whole(&mut positions, &[(0, 1), (4, 5)]));
+        assert_eq!(moved, positions);
+    }
+
+    #[test]
+    fn test_unwrap_positions() {
+        let cell = triclinic();
+        let [_, _, c] = cell.vectors();
+        let reference = [Vector3d::new(1.0, 2.0, 7.9), Vector3d::new(5.0, 5.0, 5.0)];
+        let moved = [reference[0] + Vector3d::new(0.1, 0.0, 0.3), reference[1]];
+        let mut positions = [moved[0] - c, moved[1] + c * 2.0];
+        assert_eq!(Some(()), cell.unwrap_positions(&reference, &mut positions));
+        for (position, expected) in positions.iter().zip(moved.iter()) {
+            assert!((*position - *expected).norm() < 1e-9);
+        }
+        assert_eq!(None, cell.unwrap_positions(&reference[..1], &mut positions));
+        assert!((positions[0] - moved[0]).norm() < 1e-9);
+    }
+}
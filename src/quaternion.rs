@@ -0,0 +1,420 @@
+use std::ops;
+
+use float::Float;
+use matrix::Matrix3;
+use rotation::Rotation;
+use Vector3d;
+
+/// A quaternion `w + xi + yj + zk`.
+///
+/// Unit quaternions represent rotations; `q` and `-q` describe the same
+/// rotation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quaternion<T: Float = f32> {
+    pub w: T,
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Float> Quaternion<T> {
+    /// Returns a quaternion with given components.
+    pub fn new(w: T, x: T, y: T, z: T) -> Self {
+        Quaternion { w, x, y, z }
+    }
+
+    /// Returns the quaternion `1`, which represents no rotation.
+    pub fn identity() -> Self {
+        Self::new(T::one(), T::zero(), T::zero(), T::zero())
+    }
+
+    /// Returns a quaternion with given scalar and vector parts.
+    pub fn from_parts(w: T, vector: &Vector3d<T>) -> Self {
+        Self::new(w, vector.x, vector.y, vector.z)
+    }
+
+    /// Returns the vector part.
+    pub fn vector(&self) -> Vector3d<T> {
+        Vector3d::new(self.x, self.y, self.z)
+    }
+
+    /// Returns the unit quaternion of the rotation by `angle` radians
+    /// about `axis`, or `None` if the axis is of zero length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{Quaternion, Vector3d};
+    /// let axis = Vector3d::new(0.0, 0.0, 1.0);
+    /// let quaternion = Quaternion::from_axis_angle(&axis, std::f32::consts::FRAC_PI_2).unwrap();
+    /// let rotated = quaternion.rotate(&Vector3d::new(1.0, 0.0, 0.0));
+    /// assert!((rotated - Vector3d::new(0.0, 1.0, 0.0)).norm() < 1e-6);
+    /// ```
+    pub fn from_axis_angle(axis: &Vector3d<T>, angle: T) -> Option<Self> {
+        let axis = axis.try_normalize()?;
+        let half = angle * T::from_f32(0.5);
+        Some(Self::from_parts(half.cos(), &(axis * half.sin())))
+    }
+
+    /// Returns the rotation axis and the angle in `[0, pi]` of a unit
+    /// quaternion. The axis is `None` for the identity rotation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{Quaternion, Vector3d};
+    /// let axis: Vector3d = Vector3d::new(0.0, 1.0, 0.0);
+    /// let quaternion = Quaternion::from_axis_angle(&axis, 0.5).unwrap();
+    /// let (rotation_axis, angle) = quaternion.to_axis_angle();
+    /// assert!((rotation_axis.unwrap() - axis).norm() < 1e-6);
+    /// assert!((angle - 0.5).abs() < 1e-6);
+    /// ```
+    pub fn to_axis_angle(&self) -> (Option<Vector3d<T>>, T) {
+        // Choose the sign with non-negative w so that the angle is in [0, pi].
+        let q = if self.w < T::zero() { -*self } else { *self };
+        let vector = q.vector();
+        let angle = vector.norm().atan2(q.w) * T::from_f32(2.0);
+        (vector.try_normalize(), angle)
+    }
+
+    /// Returns the unit quaternion of a rotation, using Shepperd's method.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{EulerConvention, Quaternion, Rotation, Vector3d};
+    /// let rotation = Rotation::from_euler(EulerConvention::ZYZ, 0.1, 0.2, 0.3);
+    /// let quaternion = Quaternion::from_rotation(&rotation);
+    /// let vector = Vector3d::new(1.0, 2.0, 3.0);
+    /// assert!((quaternion.rotate(&vector) - rotation * vector).norm() < 1e-5);
+    /// ```
+    pub fn from_rotation(rotation: &Rotation<T>) -> Self {
+        let m = rotation.matrix();
+        let (one, quarter) = (T::one(), T::from_f32(0.25));
+        let trace = m.trace();
+        let quaternion = if trace > m[(0, 0)] && trace > m[(1, 1)] && trace > m[(2, 2)] {
+            let s = (one + trace).sqrt() * T::from_f32(2.0);
+            Self::new(
+                quarter * s,
+                (m[(2, 1)] - m[(1, 2)]) / s,
+                (m[(0, 2)] - m[(2, 0)]) / s,
+                (m[(1, 0)] - m[(0, 1)]) / s,
+            )
+        } else if m[(0, 0)] > m[(1, 1)] && m[(0, 0)] > m[(2, 2)] {
+            let s = (one + m[(0, 0)] - m[(1, 1)] - m[(2, 2)]).sqrt() * T::from_f32(2.0);
+            Self::new(
+                (m[(2, 1)] - m[(1, 2)]) / s,
+                quarter * s,
+                (m[(0, 1)] + m[(1, 0)]) / s,
+                (m[(0, 2)] + m[(2, 0)]) / s,
+            )
+        } else if m[(1, 1)] > m[(2, 2)] {
+            let s = (one + m[(1, 1)] - m[(0, 0)] - m[(2, 2)]).sqrt() * T::from_f32(2.0);
+            Self::new(
+                (m[(0, 2)] - m[(2, 0)]) / s,
+                (m[(0, 1)] + m[(1, 0)]) / s,
+                quarter * s,
+                (m[(1, 2)] + m[(2, 1)]) / s,
+            )
+        } else {
+            let s = (one + m[(2, 2)] - m[(0, 0)] - m[(1, 1)]).sqrt() * T::from_f32(2.0);
+            Self::new(
+                (m[(1, 0)] - m[(0, 1)]) / s,
+                (m[(0, 2)] + m[(2, 0)]) / s,
+                (m[(1, 2)] + m[(2, 1)]) / s,
+                quarter * s,
+            )
+        };
+        quaternion.normalize()
+    }
+
+    /// Returns the rotation represented by the quaternion after
+    /// normalization, or `None` if the quaternion is zero.
+    pub fn to_rotation(&self) -> Option<Rotation<T>> {
+        Rotation::from_quaternion(self.w, self.x, self.y, self.z)
+    }
+
+    /// Returns the rotation matrix of the quaternion after normalization,
+    /// or `None` if the quaternion is zero.
+    pub fn to_matrix(&self) -> Option<Matrix3<T>> {
+        self.to_rotation().map(|rotation| *rotation.matrix())
+    }
+
+    /// Returns the conjugate `w - xi - yj - zk`.
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// Returns the multiplicative inverse, or `None` for the zero
+    /// quaternion.
+    pub fn inverse(&self) -> Option<Self> {
+        let norm_squared = self.norm_squared();
+        if norm_squared > T::zero() {
+            Some(self.conjugate() / norm_squared)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the four dimensional dot product.
+    pub fn dot(&self, quaternion: &Self) -> T {
+        self.w * quaternion.w
+            + self.x * quaternion.x
+            + self.y * quaternion.y
+            + self.z * quaternion.z
+    }
+
+    /// Returns the squared norm.
+    pub fn norm_squared(&self) -> T {
+        self.dot(self)
+    }
+
+    /// Returns the norm.
+    pub fn norm(&self) -> T {
+        self.norm_squared().sqrt()
+    }
+
+    /// Returns the unit quaternion in the same direction.
+    ///
+    /// The components of the result are NaN for the zero quaternion;
+    /// use `try_normalize` when the input may be degenerate.
+    pub fn normalize(&self) -> Self {
+        *self / self.norm()
+    }
+
+    /// Returns the unit quaternion in the same direction, or `None` for the
+    /// zero quaternion.
+    pub fn try_normalize(&self) -> Option<Self> {
+        let norm = self.norm();
+        if norm > T::zero() && norm.is_finite() {
+            Some(*self / norm)
+        } else {
+            None
+        }
+    }
+
+    /// Rotates a vector by a unit quaternion, i.e. computes `q v q*`.
+    pub fn rotate(&self, vector: &Vector3d<T>) -> Vector3d<T> {
+        // v' = v + 2w (u x v) + 2 u x (u x v) with u the vector part
+        let u = self.vector();
+        let t = u.cross(vector) * T::from_f32(2.0);
+        *vector + t * self.w + u.cross(&t)
+    }
+
+    /// Spherically interpolates between two unit quaternions along the
+    /// shortest arc; `t = 0` gives `self` and `t = 1` gives `quaternion`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{Quaternion, Vector3d};
+    /// let axis: Vector3d = Vector3d::new(0.0, 0.0, 1.0);
+    /// let start = Quaternion::identity();
+    /// let end = Quaternion::from_axis_angle(&axis, 1.0).unwrap();
+    /// let middle = start.slerp(&end, 0.5);
+    /// let (_, angle) = middle.to_axis_angle();
+    /// assert!((angle - 0.5).abs() < 1e-6);
+    /// ```
+    pub fn slerp(&self, quaternion: &Self, t: T) -> Self {
+        let mut end = *quaternion;
+        let mut cos = self.dot(quaternion);
+        if cos < T::zero() {
+            end = -end;
+            cos = -cos;
+        }
+        // Fall back to linear interpolation where sin(theta) vanishes.
+        if cos > T::one() - T::from_f32(1e-6) {
+            return (*self + (end - *self) * t).normalize();
+        }
+        let theta = cos.acos();
+        let sin = theta.sin();
+        let a = ((T::one() - t) * theta).sin() / sin;
+        let b = (t * theta).sin() / sin;
+        *self * a + end * b
+    }
+}
+
+impl<T: Float> From<Rotation<T>> for Quaternion<T> {
+    fn from(rotation: Rotation<T>) -> Self {
+        Self::from_rotation(&rotation)
+    }
+}
+
+impl<T: Float> ops::Add for Quaternion<T> {
+    type Output = Self;
+    fn add(self, q: Self) -> Self::Output {
+        Self::new(self.w + q.w, self.x + q.x, self.y + q.y, self.z + q.z)
+    }
+}
+
+impl<T: Float> ops::Sub for Quaternion<T> {
+    type Output = Self;
+    fn sub(self, q: Self) -> Self::Output {
+        Self::new(self.w - q.w, self.x - q.x, self.y - q.y, self.z - q.z)
+    }
+}
+
+impl<T: Float> ops::Neg for Quaternion<T> {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        Self::new(-self.w, -self.x, -self.y, -self.z)
+    }
+}
+
+impl<T: Float> ops::Mul for Quaternion<T> {
+    type Output = Self;
+
+    /// Returns the Hamilton product; as rotations, `a * b` applies `b`
+    /// first, then `a`.
+    fn mul(self, q: Self) -> Self::Output {
+        Self::new(
+            self.w * q.w - self.x * q.x - self.y * q.y - self.z * q.z,
+            self.w * q.x + self.x * q.w + self.y * q.z - self.z * q.y,
+            self.w * q.y - self.x * q.z + self.y * q.w + self.z * q.x,
+            self.w * q.z + self.x * q.y - self.y * q.x + self.z * q.w,
+        )
+    }
+}
+
+impl<T: Float> ops::Mul<T> for Quaternion<T> {
+    type Output = Self;
+    fn mul(self, value: T) -> Self::Output {
+        Self::new(
+            self.w * value,
+            self.x * value,
+            self.y * value,
+            self.z * value,
+        )
+    }
+}
+
+impl<T: Float> ops::Div<T> for Quaternion<T> {
+    type Output = Self;
+    fn div(self, value: T) -> Self::Output {
+        Self::new(
+            self.w / value,
+            self.x / value,
+            self.y / value,
+            self.z / value,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rotation::EulerConvention;
+
+    fn assert_close(expected: Vector3d<f64>, actual: Vector3d<f64>) {
+        assert!(
+            (expected - actual).norm() < 1e-12,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_hamilton_product() {
+        let i = Quaternion::new(0.0, 1.0, 0.0, 0.0);
+        let j = Quaternion::new(0.0, 0.0, 1.0, 0.0);
+        let k = Quaternion::new(0.0, 0.0, 0.0, 1.0);
+        let minus_one = Quaternion::new(-1.0, 0.0, 0.0, 0.0);
+        assert_eq!(minus_one, i * i);
+        assert_eq!(minus_one, j * j);
+        assert_eq!(minus_one, k * k);
+        assert_eq!(k, i * j);
+        assert_eq!(-k, j * i);
+        assert_eq!(minus_one, i * j * k);
+    }
+
+    #[test]
+    fn test_conjugate_and_inverse() {
+        let q = Quaternion::new(1.0, 2.0, 3.0, 4.0);
+        assert_eq!(Quaternion::new(1.0, -2.0, -3.0, -4.0), q.conjugate());
+        assert_eq!(30.0, q.norm_squared());
+        let product = q * q.inverse().unwrap();
+        assert!((product - Quaternion::identity()).norm() < 1e-12);
+        assert_eq!(None, Quaternion::<f64>::new(0.0, 0.0, 0.0, 0.0).inverse());
+    }
+
+    #[test]
+    fn test_normalize() {
+        let q = Quaternion::new(0.0, 3.0, 0.0, 4.0).normalize();
+        assert_eq!(Quaternion::new(0.0, 0.6, 0.0, 0.8), q);
+        assert_eq!(
+            None,
+            Quaternion::<f64>::new(0.0, 0.0, 0.0, 0.0).try_normalize()
+        );
+    }
+
+    #[test]
+    fn test_rotate() {
+        let axis = Vector3d::new(1.0, 2.0, -1.0);
+        let q = Quaternion::from_axis_angle(&axis, 0.8).unwrap();
+        let rotation = Rotation::from_axis_angle(&axis, 0.8).unwrap();
+        let vector = Vector3d::new(0.5, -1.0, 2.0);
+        assert_close(rotation * vector, q.rotate(&vector));
+
+        // q v q* agrees with the shortcut formula
+        let v = Quaternion::from_parts(0.0, &vector);
+        assert_close((q * v * q.conjugate()).vector(), q.rotate(&vector));
+    }
+
+    #[test]
+    fn test_rotation_round_trip() {
+        let conventions = [
+            (EulerConvention::XYZ, 0.1, 0.2, 0.3),
+            (EulerConvention::ZYZ, 3.0, 2.0, -3.0),
+            (EulerConvention::XYX, -2.5, 3.1, 1.0),
+            (EulerConvention::ZXY, 1.5, -1.2, 3.0),
+        ];
+        let vector = Vector3d::new(1.0, -2.0, 0.5);
+        for &(convention, a, b, c) in conventions.iter() {
+            let rotation = Rotation::from_euler(convention, a, b, c);
+            let q = Quaternion::from(rotation);
+            assert!((q.norm() - 1.0).abs() < 1e-12);
+            assert_close(rotation * vector, q.rotate(&vector));
+            let back = q.to_rotation().unwrap();
+            assert_close(rotation * vector, back * vector);
+        }
+    }
+
+    #[test]
+    fn test_axis_angle_round_trip() {
+        let axis = Vector3d::new(0.0, -3.0, 4.0);
+        let q = Quaternion::from_axis_angle(&axis, 2.5).unwrap();
+        let (rotation_axis, angle) = q.to_axis_angle();
+        assert_close(axis.normalize(), rotation_axis.unwrap());
+        assert!((angle - 2.5).abs() < 1e-12);
+
+        // -q gives the same axis and angle
+        let (rotation_axis, angle) = (-q).to_axis_angle();
+        assert_close(axis.normalize(), rotation_axis.unwrap());
+        assert!((angle - 2.5).abs() < 1e-12);
+
+        let (rotation_axis, angle) = Quaternion::<f64>::identity().to_axis_angle();
+        assert_eq!(None, rotation_axis);
+        assert_eq!(0.0, angle);
+    }
+
+    #[test]
+    fn test_slerp() {
+        let axis = Vector3d::new(1.0, 1.0, 0.0);
+        let start = Quaternion::from_axis_angle(&axis, 0.2).unwrap();
+        let end = Quaternion::from_axis_angle(&axis, 1.4).unwrap();
+        assert!((start.slerp(&end, 0.0) - start).norm() < 1e-12);
+        assert!((start.slerp(&end, 1.0) - end).norm() < 1e-12);
+        let quarter = start.slerp(&end, 0.25);
+        let expected = Quaternion::from_axis_angle(&axis, 0.5).unwrap();
+        assert!((quarter - expected).norm() < 1e-12);
+
+        // The shortest arc is taken even when the signs differ.
+        let quarter = start.slerp(&-end, 0.25);
+        assert!((quarter - expected).norm() < 1e-12);
+
+        // Nearly identical orientations stay normalized.
+        let middle = start.slerp(&start, 0.5);
+        assert!((middle - start).norm() < 1e-12);
+    }
+}
@@ -0,0 +1,739 @@
+//! Validation of backbone conformations against the Ramachandran plot.
+//!
+//! Each residue is assigned to one of six categories with distinct
+//! distributions of phi and psi, and its angles are classified as favored,
+//! allowed or an outlier by the density of the category at those angles.
+//!
+//! The bundled densities are an approximation drawn by hand on a grid of 10
+//! degrees after the published Top8000 contour plots of MolProbity; they
+//! are not derived from the Top8000 data. The log density is interpolated
+//! bilinearly between cells and compared to the contour levels of
+//! MolProbity: a relative density of 0.02 for favored angles and, for
+//! allowed ones, 0.0005 in the general case, 0.002 for cis-proline and
+//! 0.001 otherwise. Angles near a contour, and especially in sparsely
+//! populated regions, may therefore be classified differently than by
+//! MolProbity.
+
+use std::fmt;
+
+use structure::{Chain, Residue};
+use torsion::torsions;
+
+/// The relative density above which angles are favored in every category.
+const FAVORED_LEVEL: f32 = 0.02;
+
+/// A condensed density table of 36 rows of psi from 175 down to -175
+/// degrees, each of 36 cells of phi from -175 to 175 degrees. A cell is `#`
+/// in the cores of the peaks, `F` in the rest of the favored region, `a` in
+/// the allowed region and `.` elsewhere.
+type Grid = [&'static str; 36];
+
+/// Returns the base 10 logarithm of the relative density of a cell.
+fn level(cell: u8) -> f32 {
+    match cell {
+        b'#' => 0.0,
+        b'F' => -0.7,
+        b'a' => -2.3,
+        _ => -5.0,
+    }
+}
+
+const GENERAL: Grid = [
+    "FFFFFFFFFFFFFa....................aa",
+    "FFFFFFFFFFFFFa....................aa",
+    "FFFFFFFFFFF#Fa....................aa",
+    "FFFFF##FFF##Fa....................aa",
+    "FFFF####FFF#Fa......................",
+    "FFFF####FFFFFa......................",
+    "FFFFF##FFFFFaa......................",
+    "FFFFFFFFFFaaaa......................",
+    "FFFFFFFaaaaaa..........aaa..........",
+    "aaaaaaaaaaaaa..........aaaa.........",
+    "aaaaaaaaaaaaa..........aaaa.........",
+    "aaaaaaaaaaaaa.........aaaaaa........",
+    "aaaaaaaaaaaaa.........aFFaaa........",
+    "aaaaaaaaaaaaa.........aFFFaa........",
+    "aaaaaaaaaaaaa.........aFFFaa........",
+    "aaaaaaaaaaaaa.........aFFaaa........",
+    "aaaaaaaaaaaaa.........aaaaaa........",
+    "aaaaaFFFFFFaaa........aaaaaa........",
+    "aaaaFFFFFFFFaa........aaaaaa........",
+    "aaaFFFFFFFFFFaa.......aaaaaa........",
+    "aaFFFFFFFFFFFFa........aaaa.........",
+    "aaFFFFFFFFF##Fa........aaa..........",
+    "aaaFFFFFFFF##Fa.....................",
+    "aaaaFFFFFFFFFFa.....................",
+    "aaaaaaaFFFFFFaa.....................",
+    "aaaaaaaaaaaaaa......................",
+    "........aaaaa.......................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "aaaaaaaaaaaaaa......................",
+    "aaaaaaaaaaaaaa......................",
+    "FFFFFFFFFFFFFa....................aa",
+    "FFFFFFFFFFFFFa....................aa",
+];
+
+const GLYCINE: Grid = [
+    "##FFaaaF####a..........aF##FFFaaFF##",
+    "##FaaaaF####a..........aaFFFFaaaaF##",
+    "FFaaaaaFFFFFa..........aaaaaaaaaaaFF",
+    "aaaaaaaaaFaaa.......................",
+    "aaaaaaaaaaaaa.......................",
+    "aaaaaaaaaaaaa.......................",
+    "aaaaaaaaaaaa........................",
+    "aaaaaaaaa...........................",
+    "....................................",
+    "..........................aaaaaaaaaa",
+    "........................aaaaaaaaaaaa",
+    ".......................aaaaaaaaaaaaa",
+    "......................aa#FFFFaaaaaaa",
+    "aaaaaaaa..............a###FFFFaaaaaa",
+    "aaaaaaaaaaa...........a###FFFFaaaaaa",
+    "aaaaaaaaaaaa..........aF#FFFFFaaaaaa",
+    "aaaaaaaaaaaaa.........aFFFFFFaaaaaaa",
+    "aaaaaaaaaaaaaa........aaaFFFaaaaaaaa",
+    "aaaaaaaaFFFaaa........aaaaaaaaaaaaaa",
+    "aaaaaaaFFFFFFa.........aaaaaaaaaaaaa",
+    "aaaaaaFFFFF#Fa..........aaaaaaaaaaaa",
+    "aaaaaaFFFF###a...........aaaaaaaaaaa",
+    "aaaaaaFFFF###a..............aaaaaaaa",
+    "aaaaaaaFFFF#aa......................",
+    "aaaaaaaaaaaaa.......................",
+    "aaaaaaaaaaaa........................",
+    "aaaaaaaaaa..........................",
+    "....................................",
+    "...........................aaaaaaaaa",
+    "........................aaaaaaaaaaaa",
+    ".......................aaaaaaaaaaaaa",
+    ".......................aaaaaaaaaaaaa",
+    ".......................aaaFaaaaaaaaa",
+    "FFaaaaaaaaaaa..........aFFFFFaaaaaFF",
+    "##FaaaaFFFFaa..........a####FaaaaF##",
+    "##FFaaFFF##Fa..........a####FaaaFF##",
+];
+
+const TRANS_PROLINE: Grid = [
+    "........aFFFFa......................",
+    "........aFFFFa......................",
+    "........aFF#Fa......................",
+    "........aF###a......................",
+    "........aFF#Fa......................",
+    "........aFFFFa......................",
+    "........aFFFaa......................",
+    "........aaaaaa......................",
+    "........aaaaaa......................",
+    "........aaaaaa......................",
+    "........aaaaaa......................",
+    "........aaaaaa......................",
+    "........aaaaa.......................",
+    "........aaaaa.......................",
+    "........aaaaa.......................",
+    "........aaaaa.......................",
+    "........aaaaa.......................",
+    "........aaaaa.......................",
+    "........aaaaaa......................",
+    "........aaFFFa......................",
+    "........aFF#Fa......................",
+    "........aF###a......................",
+    "........aFF#Fa......................",
+    "........aFFFF.......................",
+    "........aaaa........................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "........aaaaaa......................",
+    "........aaFFFa......................",
+];
+
+const CIS_PROLINE: Grid = [
+    "........aaaa........................",
+    "........aFFF........................",
+    "........aF#F........................",
+    "........aF#F........................",
+    "........aFFF........................",
+    "........aaaa........................",
+    "........aaaa........................",
+    "........aaa.........................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "........aa..........................",
+    "........aaaa........................",
+    "........aFaa........................",
+    "........aFFa........................",
+    "........a##a........................",
+    "........aFFa........................",
+    "........aaaa........................",
+    "........aaaa........................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "........aaaa........................",
+];
+
+const PRE_PROLINE: Grid = [
+    "aFFFFFFFFFFFFa....................aa",
+    "aFFF##FFFFFFFa....................aa",
+    "aFF####FFFF#Fa....................aa",
+    "aFFF##FFFF###a....................aa",
+    "aFFFFFFFFF###a......................",
+    "aFFFFFFFFFF#Fa......................",
+    "aFFFFFFFFFaaaa......................",
+    "aaaaaaaaaaaaaa......................",
+    "aaaaaaaaaaaaaa......................",
+    "aaaaaaaaaaaaaa......................",
+    "aaaaaaaaaaaaaa......................",
+    "aaaaaaaaaaaaa.......................",
+    "aa.....aaaa.........................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....aaaaaaaa........................",
+    "....aaaaaaaaa.......................",
+    "...aaaaaaaaaaa......................",
+    "...aaaaaaaFFFa......................",
+    "...aaaaaaaFFFa......................",
+    "..aaaaaaaaFFFa......................",
+    "..aaaaaaaaaaa.......................",
+    ".....aaaaa..........................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "aaaaaaaaaaaaaa....................aa",
+    "aaaaaaaaaaaaaa....................aa",
+];
+
+const ILE_VAL: Grid = [
+    "aFFFFFFFFFFFaa....................aa",
+    "aFFFFFFFFFFFaa....................aa",
+    "aFFFFFFFFFFFaa....................aa",
+    "aFFFF##FFFFFaa....................aa",
+    "aFFF####FFFFaa......................",
+    "aFFF####FFFFaa......................",
+    "aFFFF##FFFFFaa......................",
+    "aFFFFFFFFaaaa.......................",
+    "aaaaaaaaaaaa........................",
+    "aaaaaaaa............................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "......aaaaaa........................",
+    "......aaaaaaa.......................",
+    ".....aaaaaaaaa......................",
+    ".....aaaaaaaaa......................",
+    ".....aaaaaF#Fa......................",
+    ".....aaaaaF##a......................",
+    "....aaaaaaaFF.......................",
+    "....aaaaaaaa........................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "....................................",
+    "aaaaaaaaaaaaaa....................aa",
+    "aaaaaaaaaaaaaa....................aa",
+];
+
+/// The categories of residues with distinct Ramachandran distributions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    /// Residues not in any other category.
+    General,
+    Glycine,
+    /// Proline with a trans peptide bond to the previous residue.
+    TransProline,
+    /// Proline with a cis peptide bond to the previous residue.
+    CisProline,
+    /// Residues other than glycine and proline preceding a proline.
+    PreProline,
+    /// Isoleucine and valine, which are beta-branched.
+    IleVal,
+}
+
+impl Category {
+    /// Returns the category of a residue by its name, that of the next
+    /// residue, if any, and the omega angle in radians of the peptide bond
+    /// preceding it, if any. A proline is cis if omega is within 30 degrees
+    /// of zero and trans otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::ramachandran::Category;
+    /// assert_eq!(Category::Glycine, Category::of("GLY", Some("PRO"), None));
+    /// assert_eq!(Category::PreProline, Category::of("VAL", Some("PRO"), None));
+    /// assert_eq!(Category::IleVal, Category::of("VAL", None, None));
+    /// assert_eq!(Category::General, Category::of("ALA", Some("GLY"), None));
+    /// assert_eq!(Category::CisProline, Category::of("PRO", None, Some(0.1)));
+    /// assert_eq!(Category::TransProline, Category::of("PRO", None, None));
+    /// ```
+    pub fn of(name: &str, next: Option<&str>, omega: Option<f32>) -> Self {
+        match name {
+            "GLY" => Category::Glycine,
+            "PRO" if omega.is_some_and(|omega| omega.abs() < 30f32.to_radians()) => {
+                Category::CisProline
+            }
+            "PRO" => Category::TransProline,
+            _ if next == Some("PRO") => Category::PreProline,
+            "ILE" | "VAL" => Category::IleVal,
+            _ => Category::General,
+        }
+    }
+
+    fn grid(self) -> &'static Grid {
+        match self {
+            Category::General => &GENERAL,
+            Category::Glycine => &GLYCINE,
+            Category::TransProline => &TRANS_PROLINE,
+            Category::CisProline => &CIS_PROLINE,
+            Category::PreProline => &PRE_PROLINE,
+            Category::IleVal => &ILE_VAL,
+        }
+    }
+
+    /// Returns the relative density of the allowed contour of MolProbity.
+    fn allowed_level(self) -> f32 {
+        match self {
+            Category::General => 0.0005,
+            Category::CisProline => 0.002,
+            _ => 0.001,
+        }
+    }
+
+    /// Returns the density of the category at given angles in radians,
+    /// relative to the cores of the peaks.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::ramachandran::Category;
+    /// let helix = Category::General.density((-63f32).to_radians(), (-43f32).to_radians());
+    /// assert!((helix - 1.0).abs() < 1e-3);
+    /// ```
+    pub fn density(self, phi: f32, psi: f32) -> f32 {
+        let grid = self.grid();
+        let x = (phi.to_degrees() + 175.0) / 10.0;
+        let y = (175.0 - psi.to_degrees()) / 10.0;
+        let (i, j) = (x.floor(), y.floor());
+        let (u, v) = (x - i, y - j);
+        let cell = |i: f32, j: f32| {
+            let row = grid[(j as i32).rem_euclid(36) as usize].as_bytes();
+            level(row[(i as i32).rem_euclid(36) as usize])
+        };
+        let log = (1.0 - u) * (1.0 - v) * cell(i, j)
+            + u * (1.0 - v) * cell(i + 1.0, j)
+            + (1.0 - u) * v * cell(i, j + 1.0)
+            + u * v * cell(i + 1.0, j + 1.0);
+        10f32.powf(log)
+    }
+
+    /// Classifies angles in radians by the density of the category.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::ramachandran::{Category, Class};
+    /// let (phi, psi) = (60f32.to_radians(), (-120f32).to_radians());
+    /// assert_eq!(Class::Outlier, Category::General.classify(phi, psi));
+    /// let (phi, psi) = ((-60f32).to_radians(), (-45f32).to_radians());
+    /// assert_eq!(Class::Favored, Category::General.classify(phi, psi));
+    /// ```
+    pub fn classify(self, phi: f32, psi: f32) -> Class {
+        let density = self.density(phi, psi);
+        if density >= FAVORED_LEVEL {
+            Class::Favored
+        } else if density >= self.allowed_level() {
+            Class::Allowed
+        } else {
+            Class::Outlier
+        }
+    }
+}
+
+/// The classes of backbone conformations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Class {
+    Favored,
+    Allowed,
+    Outlier,
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Class::Favored => write!(f, "favored"),
+            Class::Allowed => write!(f, "allowed"),
+            Class::Outlier => write!(f, "outlier"),
+        }
+    }
+}
+
+/// The assessment of the backbone conformation of a residue.
+#[derive(Debug, Clone)]
+pub struct Assessment<'a> {
+    pub residue: Residue<'a>,
+    /// The angles in radians.
+    pub phi: f32,
+    pub psi: f32,
+    pub category: Category,
+    pub class: Class,
+}
+
+impl<'a> fmt::Display for Assessment<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {}{} {} {:7.1} {:7.1} {:?} {}",
+            self.residue.chain().id(),
+            self.residue.res_seq(),
+            self.residue
+                .i_code()
+                .map(|i_code| i_code.to_string())
+                .unwrap_or_default(),
+            self.residue.name(),
+            self.phi.to_degrees(),
+            self.psi.to_degrees(),
+            self.category,
+            self.class
+        )
+    }
+}
+
+/// Assesses each residue of a chain with both phi and psi defined.
+///
+/// # Example
+///
+/// ```
+/// let text = "\
+/// ATOM      1  N   ALA A   1      -0.500  -1.400   0.000  1.00  0.00           N
+/// ATOM      2  CA  ALA A   1       0.000   0.000   0.000  1.00  0.00           C
+/// ATOM      3  C   ALA A   1       1.500   0.000   0.000  1.00  0.00           C
+/// ATOM      4  N   GLY A   2       2.100   1.200   0.000  1.00  0.00           N
+/// ATOM      5  CA  GLY A   2       3.550   1.300   0.000  1.00  0.00           C
+/// ATOM      6  C   GLY A   2       4.100   2.700   0.000  1.00  0.00           C
+/// ATOM      7  N   ALA A   3       5.400   2.750   0.000  1.00  0.00           N
+/// ";
+/// use biost::ramachandran::{assess, Category, Class};
+/// let structure = biost::pdb::read(text.as_bytes()).unwrap();
+/// let chain = structure.models[0].chains().next().unwrap();
+/// let assessments = assess(&chain);
+/// // Only the glycine has both angles, which are 180 degrees.
+/// assert_eq!(1, assessments.len());
+/// assert_eq!(2, assessments[0].residue.res_seq());
+/// assert_eq!(Category::Glycine, assessments[0].category);
+/// assert_eq!(Class::Favored, assessments[0].class);
+/// ```
+pub fn assess<'a>(chain: &Chain<'a>) -> Vec<Assessment<'a>> {
+    let residues: Vec<Residue<'a>> = chain.residues().collect();
+    torsions(chain)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, torsions)| {
+            let (phi, psi) = (torsions.phi?, torsions.psi?);
+            let residue = residues[i].clone();
+            let next = residues.get(i + 1).map(|next| next.name());
+            let category = Category::of(residue.name(), next, torsions.omega);
+            Some(Assessment {
+                residue,
+                phi,
+                psi,
+                category,
+                class: category.classify(phi, psi),
+            })
+        })
+        .collect()
+}
+
+/// The numbers of residues in each class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Summary {
+    pub favored: usize,
+    pub allowed: usize,
+    pub outliers: usize,
+}
+
+impl Summary {
+    /// Counts the residues in each class.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::ramachandran::Summary;
+    /// let summary = Summary::new(&[]);
+    /// assert_eq!(0, summary.total());
+    /// assert_eq!(None, summary.favored_fraction());
+    /// ```
+    pub fn new(assessments: &[Assessment]) -> Self {
+        let mut summary = Self::default();
+        for assessment in assessments {
+            match assessment.class {
+                Class::Favored => summary.favored += 1,
+                Class::Allowed => summary.allowed += 1,
+                Class::Outlier => summary.outliers += 1,
+            }
+        }
+        summary
+    }
+
+    /// Returns the number of residues assessed.
+    pub fn total(&self) -> usize {
+        self.favored + self.allowed + self.outliers
+    }
+
+    /// Returns the fraction of favored residues, or `None` if there are no
+    /// residues.
+    pub fn favored_fraction(&self) -> Option<f32> {
+        self.fraction(self.favored)
+    }
+
+    /// Returns the fraction of allowed residues, which excludes favored
+    /// ones, or `None` if there are no residues.
+    pub fn allowed_fraction(&self) -> Option<f32> {
+        self.fraction(self.allowed)
+    }
+
+    /// Returns the fraction of outliers, or `None` if there are no
+    /// residues.
+    pub fn outlier_fraction(&self) -> Option<f32> {
+        self.fraction(self.outliers)
+    }
+
+    fn fraction(&self, count: usize) -> Option<f32> {
+        match self.total() {
+            0 => None,
+            total => Some(count as f32 / total as f32),
+        }
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let total = self.total();
+        let percentage = |count: usize| self.fraction(count).unwrap_or(0.0) * 100.0;
+        writeln!(
+            f,
+            "Ramachandran favored:  {:6.2}% ({}/{})",
+            percentage(self.favored),
+            self.favored,
+            total
+        )?;
+        writeln!(
+            f,
+            "Ramachandran allowed:  {:6.2}% ({}/{})",
+            percentage(self.allowed),
+            self.allowed,
+            total
+        )?;
+        write!(
+            f,
+            "Ramachandran outliers: {:6.2}% ({}/{})",
+            percentage(self.outliers),
+            self.outliers,
+            total
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atom::Atom;
+    use structure::Model;
+
+    fn classify(category: Category, phi: f32, psi: f32) -> Class {
+        category.classify(phi.to_radians(), psi.to_radians())
+    }
+
+    #[test]
+    fn test_category() {
+        let trans = Some(180f32.to_radians());
+        let cis = Some((-10f32).to_radians());
+        assert_eq!(
+            Category::TransProline,
+            Category::of("PRO", Some("PRO"), trans)
+        );
+        assert_eq!(Category::CisProline, Category::of("PRO", Some("PRO"), cis));
+        assert_eq!(Category::PreProline, Category::of("ILE", Some("PRO"), cis));
+        assert_eq!(Category::IleVal, Category::of("ILE", Some("ALA"), trans));
+        assert_eq!(Category::General, Category::of("SER", None, None));
+    }
+
+    #[test]
+    fn test_general() {
+        let general = |phi, psi| classify(Category::General, phi, psi);
+        // Alpha helix, beta strand, polyproline II, the extended region
+        // across the edges of the plot and the left-handed helix.
+        assert_eq!(Class::Favored, general(-57.0, -47.0));
+        assert_eq!(Class::Favored, general(-120.0, 130.0));
+        assert_eq!(Class::Favored, general(-65.0, 145.0));
+        assert_eq!(Class::Favored, general(-170.0, 170.0));
+        assert_eq!(Class::Favored, general(-150.0, -175.0));
+        assert_eq!(Class::Favored, general(60.0, 40.0));
+        // The bridge region, the left-handed region beyond its favored core
+        // and extended backbones past phi of 180 degrees.
+        assert_eq!(Class::Allowed, general(-140.0, 60.0));
+        assert_eq!(Class::Allowed, general(80.0, 0.0));
+        assert_eq!(Class::Allowed, general(175.0, 175.0));
+        assert_eq!(Class::Allowed, general(-100.0, -80.0));
+        assert_eq!(Class::Outlier, general(60.0, -120.0));
+        assert_eq!(Class::Outlier, general(-60.0, -130.0));
+        assert_eq!(Class::Outlier, general(0.0, 0.0));
+        assert_eq!(Class::Outlier, general(120.0, 100.0));
+    }
+
+    #[test]
+    fn test_glycine() {
+        let glycine = |phi, psi| classify(Category::Glycine, phi, psi);
+        // Glycine is symmetric and favored in extended regions across the
+        // boundaries of the plot.
+        assert_eq!(Class::Favored, glycine(63.0, 41.0));
+        assert_eq!(Class::Favored, glycine(-63.0, -41.0));
+        assert_eq!(Class::Favored, glycine(-80.0, 170.0));
+        assert_eq!(Class::Favored, glycine(80.0, -170.0));
+        assert_eq!(Class::Favored, glycine(-175.0, 178.0));
+        assert_eq!(Class::Favored, glycine(175.0, -178.0));
+        assert_eq!(Class::Allowed, glycine(-140.0, 0.0));
+        assert_eq!(Class::Allowed, glycine(140.0, 0.0));
+        assert_eq!(Class::Allowed, glycine(100.0, -130.0));
+        assert_eq!(Class::Outlier, glycine(0.0, 100.0));
+        assert_eq!(Class::Outlier, glycine(-60.0, -130.0));
+        assert_eq!(Class::Outlier, glycine(60.0, 130.0));
+    }
+
+    #[test]
+    fn test_proline() {
+        // Proline has phi restricted about -65 degrees.
+        let trans = |phi, psi| classify(Category::TransProline, phi, psi);
+        assert_eq!(Class::Favored, trans(-65.0, -30.0));
+        assert_eq!(Class::Favored, trans(-65.0, 145.0));
+        assert_eq!(Class::Allowed, trans(-65.0, 70.0));
+        assert_eq!(Class::Allowed, trans(-90.0, 20.0));
+        assert_eq!(Class::Outlier, trans(-20.0, -30.0));
+        assert_eq!(Class::Outlier, trans(-120.0, 140.0));
+        assert_eq!(Class::Outlier, trans(-65.0, -120.0));
+        assert_eq!(Class::Outlier, trans(60.0, 40.0));
+
+        let cis = |phi, psi| classify(Category::CisProline, phi, psi);
+        assert_eq!(Class::Favored, cis(-75.0, 150.0));
+        assert_eq!(Class::Favored, cis(-80.0, -15.0));
+        assert_eq!(Class::Allowed, cis(-90.0, 110.0));
+        assert_eq!(Class::Outlier, cis(-65.0, 60.0));
+        assert_eq!(Class::Outlier, cis(-65.0, -60.0));
+    }
+
+    #[test]
+    fn test_pre_proline_and_ile_val() {
+        let pre_proline = |phi, psi| classify(Category::PreProline, phi, psi);
+        assert_eq!(Class::Favored, pre_proline(-65.0, 140.0));
+        assert_eq!(Class::Favored, pre_proline(-130.0, 155.0));
+        assert_eq!(Class::Favored, pre_proline(-60.0, -35.0));
+        assert_eq!(Class::Allowed, pre_proline(-120.0, 70.0));
+        assert_eq!(Class::Allowed, pre_proline(-130.0, -30.0));
+        assert_eq!(Class::Outlier, pre_proline(-100.0, -120.0));
+        assert_eq!(Class::Outlier, pre_proline(60.0, 40.0));
+
+        let ile_val = |phi, psi| classify(Category::IleVal, phi, psi);
+        assert_eq!(Class::Favored, ile_val(-120.0, 130.0));
+        assert_eq!(Class::Favored, ile_val(-63.0, -42.0));
+        assert_eq!(Class::Allowed, ile_val(-100.0, -40.0));
+        assert_eq!(Class::Allowed, ile_val(-175.0, 120.0));
+        assert_eq!(Class::Outlier, ile_val(-120.0, 40.0));
+        assert_eq!(Class::Outlier, ile_val(60.0, 40.0));
+    }
+
+    #[test]
+    fn test_density() {
+        // The density is continuous and periodic.
+        let density =
+            |phi: f32, psi: f32| Category::General.density(phi.to_radians(), psi.to_radians());
+        assert!((density(-65.0, -40.0) - 1.0).abs() < 1e-6);
+        assert!((density(-170.0, 170.0) - density(190.0, -190.0)).abs() < 1e-6);
+        assert!((density(-100.0, 95.0) - density(-100.0, 95.001)).abs() < 1e-4);
+        assert!(density(0.0, 0.0) < 1e-4);
+    }
+
+    #[test]
+    fn test_assess() {
+        // A planar chain ALA-VAL-PRO-GLY-ALA with phi and psi of 180 degrees,
+        // whose peptide bonds are cis since the CA atoms are all on one side.
+        let names = ["ALA", "VAL", "PRO", "GLY", "ALA"];
+        let mut model = Model::new(1);
+        for (i, name) in names.iter().enumerate() {
+            let x = i as f32 * 3.63;
+            for &(atom_name, dx, dy) in
+                [("N", 0.0, 0.0), ("CA", 1.19, 0.84), ("C", 2.55, 0.14)].iter()
+            {
+                let mut atom = Atom::new(atom_name, ::Vector3d::new(x + dx, dy, 0.0));
+                atom.res_name = name.to_string();
+                atom.res_seq = i as i32 + 1;
+                atom.chain_id = "A".to_string();
+                model.atoms.push(atom);
+            }
+        }
+        let chain = model.chains().next().unwrap();
+        let assessments = assess(&chain);
+        let categories: Vec<_> = assessments.iter().map(|a| a.category).collect();
+        assert_eq!(
+            vec![
+                Category::PreProline,
+                Category::CisProline,
+                Category::Glycine
+            ],
+            categories
+        );
+        let classes: Vec<_> = assessments.iter().map(|a| a.class).collect();
+        assert_eq!(
+            vec![Class::Allowed, Class::Outlier, Class::Favored],
+            classes
+        );
+
+        let summary = Summary::new(&assessments);
+        assert_eq!(3, summary.total());
+        assert_eq!(Some(1.0 / 3.0), summary.favored_fraction());
+        assert_eq!(
+            "Ramachandran favored:   33.33% (1/3)\n\
+             Ramachandran allowed:   33.33% (1/3)\n\
+             Ramachandran outliers:  33.33% (1/3)",
+            summary.to_string()
+        );
+        assert!(assessments[1].to_string().starts_with("A 3 PRO"));
+    }
+}
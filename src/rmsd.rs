@@ -0,0 +1,226 @@
+//! Deviations between corresponding sets of coordinates.
+//!
+//! All functions return `None` if the sets of coordinates differ in length
+//! or are empty.
+
+use float::Float;
+use superposition::kabsch;
+use Vector3d;
+
+/// Returns the root-mean-square deviation between two sets of coordinates
+/// as they are, without superposition.
+///
+/// # Example
+///
+/// ```
+/// use biost::{rmsd, Vector3d};
+/// let a = [Vector3d::new(0.0, 0.0, 0.0), Vector3d::new(1.0, 0.0, 0.0)];
+/// let b = [Vector3d::new(0.0, 1.0, 0.0), Vector3d::new(1.0, 0.0, 1.0)];
+/// assert_eq!(Some(1.0), rmsd(&a, &b));
+/// assert_eq!(None, rmsd(&a, &b[..1]));
+/// ```
+pub fn rmsd<T: Float>(a: &[Vector3d<T>], b: &[Vector3d<T>]) -> Option<T> {
+    if a.len() != b.len() || a.is_empty() {
+        return None;
+    }
+    let sum: T = a.iter().zip(b).map(|(p, q)| p.distance_squared(q)).sum();
+    Some((sum / T::from_f64(a.len() as f64)).sqrt())
+}
+
+/// Returns the root-mean-square deviation in which the squared deviation
+/// of each pair is weighted, e.g. by the atomic mass.
+///
+/// Returns `None` also if the number of weights differs or the weights do
+/// not sum up to a positive value.
+///
+/// # Example
+///
+/// ```
+/// use biost::{weighted_rmsd, Vector3d};
+/// let a = [Vector3d::new(0.0, 0.0, 0.0), Vector3d::new(1.0, 0.0, 0.0)];
+/// let b = [Vector3d::new(0.0, 2.0, 0.0), Vector3d::new(1.0, 0.0, 0.0)];
+/// assert_eq!(Some(1.0), weighted_rmsd(&a, &b, &[1.0, 3.0]));
+/// ```
+pub fn weighted_rmsd<T: Float>(a: &[Vector3d<T>], b: &[Vector3d<T>], weights: &[T]) -> Option<T> {
+    if a.len() != b.len() || a.len() != weights.len() {
+        return None;
+    }
+    let total: T = weights.iter().cloned().sum();
+    if total <= T::zero() || total.is_nan() {
+        return None;
+    }
+    let sum: T = a
+        .iter()
+        .zip(b)
+        .zip(weights)
+        .map(|((p, q), &weight)| p.distance_squared(q) * weight)
+        .sum();
+    Some((sum / total).sqrt())
+}
+
+/// Returns the (weighted) root-mean-square deviation of `mobile` from
+/// `reference` after superposing `mobile` optimally onto `reference`.
+///
+/// See `superposition::kabsch` for the weights.
+///
+/// # Example
+///
+/// ```
+/// use biost::{fitted_rmsd, Rotation, Vector3d};
+/// let reference = [
+///     Vector3d::new(0.0, 0.0, 0.0),
+///     Vector3d::new(1.0, 0.0, 0.0),
+///     Vector3d::new(0.0, 2.0, 0.0),
+/// ];
+/// let rotation = Rotation::from_axis_angle(&Vector3d::new(0.0, 0.0, 1.0), 0.5).unwrap();
+/// let mobile: Vec<Vector3d> = reference
+///     .iter()
+///     .map(|v| rotation.rotate(v) + Vector3d::new(3.0, 0.0, 1.0))
+///     .collect();
+/// assert!(fitted_rmsd(&mobile, &reference, None).unwrap() < 1e-5);
+/// ```
+pub fn fitted_rmsd<T: Float>(
+    mobile: &[Vector3d<T>],
+    reference: &[Vector3d<T>],
+    weights: Option<&[T]>,
+) -> Option<T> {
+    let transform = kabsch(mobile, reference, weights)?;
+    let fitted: Vec<Vector3d<T>> = mobile.iter().map(|v| transform.apply(v)).collect();
+    match weights {
+        Some(weights) => weighted_rmsd(&fitted, reference, weights),
+        None => rmsd(&fitted, reference),
+    }
+}
+
+/// Returns the deviation vectors `b[i] - a[i]` of corresponding positions.
+///
+/// # Example
+///
+/// ```
+/// use biost::{deviations, Vector3d};
+/// let a = [Vector3d::new(1.0, 2.0, 3.0)];
+/// let b = [Vector3d::new(1.5, 2.0, 1.0)];
+/// assert_eq!(Some(vec![Vector3d::new(0.5, 0.0, -2.0)]), deviations(&a, &b));
+/// ```
+pub fn deviations<T: Float>(a: &[Vector3d<T>], b: &[Vector3d<T>]) -> Option<Vec<Vector3d<T>>> {
+    if a.len() != b.len() || a.is_empty() {
+        return None;
+    }
+    Some(a.iter().zip(b).map(|(p, q)| *q - *p).collect())
+}
+
+/// Returns the root-mean-square fluctuation of each position about its
+/// mean over an ensemble, e.g. frames of a trajectory or NMR models.
+///
+/// The frames are assumed to be superposed beforehand. Returns `None` if
+/// there are no frames, a frame is empty, or the frames differ in length.
+///
+/// # Example
+///
+/// ```
+/// use biost::{rmsf, Vector3d};
+/// let frames = vec![
+///     vec![Vector3d::new(0.0, 0.0, 0.0), Vector3d::new(5.0, 0.0, 0.0)],
+///     vec![Vector3d::new(0.0, 2.0, 0.0), Vector3d::new(5.0, 0.0, 0.0)],
+/// ];
+/// assert_eq!(Some(vec![1.0, 0.0]), rmsf(&frames));
+/// ```
+pub fn rmsf<T: Float, F: AsRef<[Vector3d<T>]>>(frames: &[F]) -> Option<Vec<T>> {
+    let len = frames.first()?.as_ref().len();
+    if len == 0 || frames.iter().any(|frame| frame.as_ref().len() != len) {
+        return None;
+    }
+    let count = T::from_f64(frames.len() as f64);
+    let mut means = vec![Vector3d::zero(); len];
+    for frame in frames {
+        for (mean, position) in means.iter_mut().zip(frame.as_ref()) {
+            *mean += *position;
+        }
+    }
+    for mean in means.iter_mut() {
+        *mean /= count;
+    }
+    let mut sums = vec![T::zero(); len];
+    for frame in frames {
+        for ((sum, mean), position) in sums.iter_mut().zip(&means).zip(frame.as_ref()) {
+            *sum += position.distance_squared(mean);
+        }
+    }
+    Some(sums.into_iter().map(|sum| (sum / count).sqrt()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rotation::{EulerConvention, Rotation};
+
+    fn sample() -> Vec<Vector3d<f64>> {
+        vec![
+            Vector3d::new(-1.2, 0.3, 2.0),
+            Vector3d::new(0.5, 1.1, -0.7),
+            Vector3d::new(2.2, -0.4, 0.1),
+            Vector3d::new(0.0, 2.5, 1.4),
+        ]
+    }
+
+    #[test]
+    fn test_rmsd() {
+        let a = sample();
+        let b: Vec<_> = a
+            .iter()
+            .map(|v| *v + Vector3d::new(0.0, 3.0, 4.0))
+            .collect();
+        assert_eq!(Some(5.0), rmsd(&a, &b));
+        assert_eq!(Some(0.0), rmsd(&a, &a));
+        assert_eq!(None, rmsd::<f64>(&[], &[]));
+
+        assert_eq!(Some(5.0), weighted_rmsd(&a, &b, &[1.0, 2.0, 0.0, 4.0]));
+        assert_eq!(None, weighted_rmsd(&a, &b, &[0.0; 4]));
+        assert_eq!(None, weighted_rmsd(&a, &b, &[1.0; 3]));
+    }
+
+    #[test]
+    fn test_fitted_rmsd() {
+        let reference = sample();
+        let rotation = Rotation::from_euler(EulerConvention::ZXZ, 1.0, 2.0, -0.5);
+        let mut mobile: Vec<_> = reference
+            .iter()
+            .map(|v| rotation.rotate(v) + Vector3d::new(1.0, 1.0, -4.0))
+            .collect();
+        assert!(fitted_rmsd(&mobile, &reference, None).unwrap() < 1e-9);
+        assert!(rmsd(&mobile, &reference).unwrap() > 1.0);
+
+        // Perturbing a single position by d gives RMSD no more than d / 2
+        // for four positions.
+        mobile[0].z += 1.0;
+        let fitted = fitted_rmsd(&mobile, &reference, None).unwrap();
+        assert!(fitted > 0.0 && fitted < 0.5);
+
+        let weights = [0.0, 1.0, 1.0, 1.0];
+        assert!(fitted_rmsd(&mobile, &reference, Some(&weights)).unwrap() < 1e-9);
+    }
+
+    #[test]
+    fn test_deviations() {
+        let a = sample();
+        let b: Vec<_> = a.iter().map(|v| *v * 2.0).collect();
+        assert_eq!(Some(a.clone()), deviations(&a, &b));
+        assert_eq!(None, deviations(&a, &b[1..]));
+    }
+
+    #[test]
+    fn test_rmsf() {
+        let first = sample();
+        let second: Vec<_> = first
+            .iter()
+            .enumerate()
+            .map(|(i, v)| *v + Vector3d::new(i as f64 * 2.0, 0.0, 0.0))
+            .collect();
+        let fluctuations = rmsf(&[first.clone(), second]).unwrap();
+        assert_eq!(vec![0.0, 1.0, 2.0, 3.0], fluctuations);
+
+        assert_eq!(Some(vec![0.0; 4]), rmsf(&[&first[..]]));
+        assert_eq!(None, rmsf::<f64, Vec<_>>(&[]));
+        assert_eq!(None, rmsf(&[&first[..], &first[1..]]));
+    }
+}
@@ -0,0 +1,303 @@
+use std::ops;
+
+use float::Float;
+use matrix::Matrix3;
+use Vector3d;
+
+/// The order of the axes about which Euler angles are applied.
+///
+/// Angles `(a, b, c)` for `ZYZ` describe the intrinsic rotation about
+/// z by `a`, then about the new y by `b` and finally about the new z by
+/// `c`, i.e. the matrix `Rz(a) * Ry(b) * Rz(c)`. This is identical to the
+/// extrinsic rotation applying the axes in reverse order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EulerConvention {
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX,
+    XYX,
+    XZX,
+    YXY,
+    YZY,
+    ZXZ,
+    ZYZ,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl EulerConvention {
+    fn axes(self) -> [Axis; 3] {
+        use self::Axis::*;
+        use self::EulerConvention::*;
+        match self {
+            XYZ => [X, Y, Z],
+            XZY => [X, Z, Y],
+            YXZ => [Y, X, Z],
+            YZX => [Y, Z, X],
+            ZXY => [Z, X, Y],
+            ZYX => [Z, Y, X],
+            XYX => [X, Y, X],
+            XZX => [X, Z, X],
+            YXY => [Y, X, Y],
+            YZY => [Y, Z, Y],
+            ZXZ => [Z, X, Z],
+            ZYZ => [Z, Y, Z],
+        }
+    }
+}
+
+/// A proper rotation in three dimensional space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rotation<T: Float = f32> {
+    matrix: Matrix3<T>,
+}
+
+impl<T: Float> Rotation<T> {
+    /// Returns the rotation which does nothing.
+    pub fn identity() -> Self {
+        Rotation {
+            matrix: Matrix3::identity(),
+        }
+    }
+
+    /// Returns a rotation with given rotation matrix.
+    ///
+    /// The matrix is assumed to be orthogonal with determinant one; this
+    /// is not checked.
+    pub fn from_matrix_unchecked(matrix: Matrix3<T>) -> Self {
+        Rotation { matrix }
+    }
+
+    /// Returns the rotation by `angle` radians about `axis`, following the
+    /// right-hand rule. The axis does not need to be normalized.
+    ///
+    /// Returns `None` if the axis is of zero length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{Rotation, Vector3d};
+    /// let axis = Vector3d::new(0.0, 0.0, 2.0);
+    /// let rotation = Rotation::from_axis_angle(&axis, std::f32::consts::FRAC_PI_2).unwrap();
+    /// let rotated = rotation * Vector3d::new(1.0, 0.0, 0.0);
+    /// assert!((rotated - Vector3d::new(0.0, 1.0, 0.0)).norm() < 1e-6);
+    /// ```
+    pub fn from_axis_angle(axis: &Vector3d<T>, angle: T) -> Option<Self> {
+        let axis = axis.try_normalize()?;
+        let (sin, cos) = (angle.sin(), angle.cos());
+        let cross = Matrix3::new([
+            [T::zero(), -axis.z, axis.y],
+            [axis.z, T::zero(), -axis.x],
+            [-axis.y, axis.x, T::zero()],
+        ]);
+        let matrix = Matrix3::identity() * cos
+            + cross * sin
+            + Matrix3::outer(&axis, &axis) * (T::one() - cos);
+        Some(Self::from_matrix_unchecked(matrix))
+    }
+
+    /// Returns the rotation given by Euler angles in radians.
+    ///
+    /// See `EulerConvention` for how the angles are interpreted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{EulerConvention, Rotation, Vector3d};
+    /// use std::f32::consts::FRAC_PI_2;
+    /// let rotation = Rotation::from_euler(EulerConvention::ZYX, FRAC_PI_2, 0.0, 0.0);
+    /// let rotated = rotation * Vector3d::new(1.0, 0.0, 0.0);
+    /// assert!((rotated - Vector3d::new(0.0, 1.0, 0.0)).norm() < 1e-6);
+    /// ```
+    pub fn from_euler(convention: EulerConvention, a: T, b: T, c: T) -> Self {
+        let axes = convention.axes();
+        Self::elemental(axes[0], a) * Self::elemental(axes[1], b) * Self::elemental(axes[2], c)
+    }
+
+    fn elemental(axis: Axis, angle: T) -> Self {
+        let (o, l) = (T::zero(), T::one());
+        let (s, c) = (angle.sin(), angle.cos());
+        let matrix = match axis {
+            Axis::X => Matrix3::new([[l, o, o], [o, c, -s], [o, s, c]]),
+            Axis::Y => Matrix3::new([[c, o, s], [o, l, o], [-s, o, c]]),
+            Axis::Z => Matrix3::new([[c, -s, o], [s, c, o], [o, o, l]]),
+        };
+        Self::from_matrix_unchecked(matrix)
+    }
+
+    /// Returns the rotation given by the quaternion `w + xi + yj + zk`.
+    /// The quaternion does not need to be normalized.
+    ///
+    /// Returns `None` if the quaternion is of zero length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{Rotation, Vector3d};
+    /// let half = std::f32::consts::FRAC_PI_4;
+    /// let rotation = Rotation::from_quaternion(half.cos(), 0.0, 0.0, half.sin()).unwrap();
+    /// let rotated = rotation * Vector3d::new(1.0, 0.0, 0.0);
+    /// assert!((rotated - Vector3d::new(0.0, 1.0, 0.0)).norm() < 1e-6);
+    /// ```
+    pub fn from_quaternion(w: T, x: T, y: T, z: T) -> Option<Self> {
+        let norm = (w * w + x * x + y * y + z * z).sqrt();
+        if !(norm > T::zero() && norm.is_finite()) {
+            return None;
+        }
+        let (w, x, y, z) = (w / norm, x / norm, y / norm, z / norm);
+        let (l, two) = (T::one(), T::from_f32(2.0));
+        let matrix = Matrix3::new([
+            [
+                l - two * (y * y + z * z),
+                two * (x * y - z * w),
+                two * (x * z + y * w),
+            ],
+            [
+                two * (x * y + z * w),
+                l - two * (x * x + z * z),
+                two * (y * z - x * w),
+            ],
+            [
+                two * (x * z - y * w),
+                two * (y * z + x * w),
+                l - two * (x * x + y * y),
+            ],
+        ]);
+        Some(Self::from_matrix_unchecked(matrix))
+    }
+
+    /// Returns the rotation matrix.
+    pub fn matrix(&self) -> &Matrix3<T> {
+        &self.matrix
+    }
+
+    /// Returns the rotation angle in radians, in `[0, pi]`.
+    pub fn angle(&self) -> T {
+        let half = T::from_f32(0.5);
+        let cos = (self.matrix.trace() - T::one()) * half;
+        cos.max(-T::one()).min(T::one()).acos()
+    }
+
+    /// Returns the inverse rotation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{Rotation, Vector3d};
+    /// let axis = Vector3d::new(1.0, 1.0, 0.0);
+    /// let rotation = Rotation::from_axis_angle(&axis, 0.5).unwrap();
+    /// let vector = Vector3d::new(1.0, 2.0, 3.0);
+    /// let back = rotation.inverse() * (rotation * vector);
+    /// assert!((back - vector).norm() < 1e-6);
+    /// ```
+    pub fn inverse(&self) -> Self {
+        Self::from_matrix_unchecked(self.matrix.transpose())
+    }
+
+    /// Rotates a vector.
+    pub fn rotate(&self, vector: &Vector3d<T>) -> Vector3d<T> {
+        self.matrix * *vector
+    }
+}
+
+impl<T: Float> ops::Mul for Rotation<T> {
+    type Output = Self;
+
+    /// Composes two rotations; `a * b` applies `b` first, then `a`.
+    fn mul(self, rotation: Self) -> Self::Output {
+        Self::from_matrix_unchecked(self.matrix * rotation.matrix)
+    }
+}
+
+impl<T: Float> ops::Mul<Vector3d<T>> for Rotation<T> {
+    type Output = Vector3d<T>;
+    fn mul(self, vector: Vector3d<T>) -> Self::Output {
+        self.rotate(&vector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    fn assert_close(expected: Vector3d<f64>, actual: Vector3d<f64>) {
+        assert!(
+            (expected - actual).norm() < 1e-12,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_axis_angle() {
+        let axis = Vector3d::new(1.0, 1.0, 1.0);
+        let rotation = Rotation::from_axis_angle(&axis, 2.0 * PI / 3.0).unwrap();
+        let x = Vector3d::new(1.0, 0.0, 0.0);
+        let y = Vector3d::new(0.0, 1.0, 0.0);
+        assert_close(y, rotation * x);
+        assert!((rotation.angle() - 2.0 * PI / 3.0).abs() < 1e-12);
+        assert!((rotation.matrix().determinant() - 1.0).abs() < 1e-12);
+
+        assert!(Rotation::from_axis_angle(&Vector3d::zero(), 1.0).is_none());
+    }
+
+    #[test]
+    fn test_euler() {
+        let x = Vector3d::new(1.0, 0.0, 0.0);
+        let y = Vector3d::new(0.0, 1.0, 0.0);
+        let z = Vector3d::new(0.0, 0.0, 1.0);
+
+        // Intrinsic: rotate about z, then about the new y.
+        let rotation = Rotation::from_euler(EulerConvention::ZYX, FRAC_PI_2, FRAC_PI_2, 0.0);
+        assert_close(-z, rotation * x);
+
+        let rotation = Rotation::from_euler(EulerConvention::ZYZ, 0.3, 0.7, -0.3);
+        let expected = Rotation::from_axis_angle(&z, 0.3).unwrap()
+            * Rotation::from_axis_angle(&y, 0.7).unwrap()
+            * Rotation::from_axis_angle(&z, -0.3).unwrap();
+        let vector = Vector3d::new(1.0, 2.0, 3.0);
+        assert_close(expected * vector, rotation * vector);
+    }
+
+    #[test]
+    fn test_quaternion() {
+        let half = 0.35f64;
+        let axis = Vector3d::new(1.0, -2.0, 0.5).normalize();
+        let rotation = Rotation::from_quaternion(
+            half.cos(),
+            axis.x * half.sin(),
+            axis.y * half.sin(),
+            axis.z * half.sin(),
+        )
+        .unwrap();
+        let expected = Rotation::from_axis_angle(&axis, 2.0 * half).unwrap();
+        let vector = Vector3d::new(1.0, 2.0, 3.0);
+        assert_close(expected * vector, rotation * vector);
+
+        // Scaling a quaternion does not change the rotation.
+        let scaled = Rotation::from_quaternion(3.0, 0.0, 0.0, 0.0).unwrap();
+        assert_close(vector, scaled * vector);
+
+        assert!(Rotation::<f64>::from_quaternion(0.0, 0.0, 0.0, 0.0).is_none());
+    }
+
+    #[test]
+    fn test_inverse_and_compose() {
+        let rotation = Rotation::from_euler(EulerConvention::XYZ, 0.1, 0.2, 0.3);
+        let identity = rotation * rotation.inverse();
+        let vector = Vector3d::new(-1.0, 4.0, 2.0);
+        assert_close(vector, identity * vector);
+        assert_close(vector, Rotation::identity() * vector);
+        assert!(identity.angle().abs() < 1e-6);
+    }
+}
@@ -0,0 +1,440 @@
+//! Solvent accessible surface area (SASA).
+//!
+//! The surface is that traced by the center of a spherical probe rolling
+//! over the van der Waals spheres of atoms. It is computed either by the
+//! Shrake-Rupley method, counting exposed points on each expanded sphere,
+//! or by the Lee-Richards method, summing exposed arcs of slices through
+//! each sphere. Areas are in square Angstroms.
+
+use std::cmp::Ordering;
+use std::f32::consts::PI;
+
+use atom::Atom;
+use element::Element;
+use neighbor::Grid;
+use structure::{Model, Residue};
+use Vector3d;
+
+/// The radius of a water molecule as the probe.
+pub const PROBE_RADIUS: f32 = 1.4;
+
+/// The radius of atoms of unknown elements or van der Waals radii.
+const DEFAULT_RADIUS: f32 = 1.80;
+
+/// Returns the van der Waals radius of an atom by its element, guessed by
+/// `Element::of` if the element is blank.
+///
+/// # Example
+///
+/// ```
+/// use biost::{sasa, Atom, Vector3d};
+/// let mut atom = Atom::new("CA", Vector3d::zero());
+/// assert_eq!(1.70, sasa::radius(&atom));
+/// atom.element = "CA".to_string();
+/// assert_eq!(2.31, sasa::radius(&atom));
+/// ```
+pub fn radius(atom: &Atom) -> f32 {
+    Element::of(atom)
+        .and_then(|element| element.vdw_radius())
+        .unwrap_or(DEFAULT_RADIUS)
+}
+
+/// The methods to compute the surface area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// The Shrake-Rupley method with given number of points per sphere.
+    ShrakeRupley(usize),
+    /// The Lee-Richards method with given number of slices per sphere.
+    LeeRichards(usize),
+}
+
+impl Default for Method {
+    fn default() -> Self {
+        Method::ShrakeRupley(100)
+    }
+}
+
+impl Method {
+    /// Returns the accessible surface area of each sphere with given
+    /// centers and radii to a probe of given radius.
+    ///
+    /// Returns `None` if the numbers of centers and radii differ, a center
+    /// is not finite or the number of points or slices is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::sasa::Method;
+    /// use biost::Vector3d;
+    /// let centers = [Vector3d::zero()];
+    /// for method in [Method::ShrakeRupley(1000), Method::LeeRichards(100)].iter() {
+    ///     let areas = method.areas(&centers, &[1.6], 1.4).unwrap();
+    ///     // The area of the sphere of radius 1.6 + 1.4
+    ///     assert!((areas[0] - 36.0 * std::f32::consts::PI).abs() < 0.1);
+    /// }
+    /// ```
+    pub fn areas(&self, centers: &[Vector3d], radii: &[f32], probe: f32) -> Option<Vec<f32>> {
+        if centers.len() != radii.len() {
+            return None;
+        }
+        let radii: Vec<f32> = radii.iter().map(|radius| radius + probe).collect();
+        let max_radius = radii.iter().fold(0.0f32, |max, &radius| max.max(radius));
+        let grid = Grid::new(centers, 2.0 * max_radius.max(f32::EPSILON))?;
+        let neighbors = |i: usize| -> Vec<usize> {
+            grid.within(&centers[i], radii[i] + max_radius)
+                .into_iter()
+                .filter(|&j| j != i && centers[i].distance(&centers[j]) < radii[i] + radii[j])
+                .collect()
+        };
+        match *self {
+            Method::ShrakeRupley(0) | Method::LeeRichards(0) => None,
+            Method::ShrakeRupley(points) => {
+                let points = sphere_points(points);
+                let areas = (0..centers.len())
+                    .map(|i| {
+                        let neighbors = neighbors(i);
+                        let exposed = points
+                            .iter()
+                            .filter(|point| {
+                                let point = centers[i] + **point * radii[i];
+                                neighbors.iter().all(|&j| {
+                                    point.distance_squared(&centers[j]) >= radii[j] * radii[j]
+                                })
+                            })
+                            .count();
+                        4.0 * PI * radii[i] * radii[i] * exposed as f32 / points.len() as f32
+                    })
+                    .collect();
+                Some(areas)
+            }
+            Method::LeeRichards(slices) => {
+                let areas = (0..centers.len())
+                    .map(|i| lee_richards(centers, &radii, i, &neighbors(i), slices))
+                    .collect();
+                Some(areas)
+            }
+        }
+    }
+}
+
+/// Returns points evenly distributed on the unit sphere by the golden
+/// section spiral.
+fn sphere_points(count: usize) -> Vec<Vector3d> {
+    let increment = PI * (3.0 - 5.0f32.sqrt());
+    let step = 2.0 / count as f32;
+    (0..count)
+        .map(|k| {
+            let z = k as f32 * step - 1.0 + step / 2.0;
+            let r = (1.0 - z * z).sqrt();
+            let phi = k as f32 * increment;
+            Vector3d::new(r * phi.cos(), r * phi.sin(), z)
+        })
+        .collect()
+}
+
+/// Returns the exposed area of the `i`-th sphere by summing the exposed
+/// arcs of the circles of slices through it along the z axis.
+fn lee_richards(
+    centers: &[Vector3d],
+    radii: &[f32],
+    i: usize,
+    neighbors: &[usize],
+    slices: usize,
+) -> f32 {
+    let (center, radius) = (centers[i], radii[i]);
+    let thickness = 2.0 * radius / slices as f32;
+    let mut area = 0.0;
+    for slice in 0..slices {
+        let z = -radius + thickness * (slice as f32 + 0.5);
+        let r = (radius * radius - z * z).sqrt();
+        let mut covered: Vec<(f32, f32)> = Vec::new();
+        let mut buried = false;
+        for &j in neighbors {
+            let offset = centers[j] - center;
+            let height = z - offset.z;
+            let squared = radii[j] * radii[j] - height * height;
+            if squared <= 0.0 {
+                continue;
+            }
+            let rj = squared.sqrt();
+            let d = (offset.x * offset.x + offset.y * offset.y).sqrt();
+            if d >= r + rj || d + rj <= r {
+                continue;
+            }
+            if d + r <= rj {
+                buried = true;
+                break;
+            }
+            let alpha = offset.y.atan2(offset.x);
+            let beta = ((r * r + d * d - rj * rj) / (2.0 * r * d))
+                .clamp(-1.0, 1.0)
+                .acos();
+            covered.push((alpha - beta, alpha + beta));
+        }
+        if !buried {
+            area += (2.0 * PI - covered_length(&covered)) * radius * thickness;
+        }
+    }
+    area
+}
+
+/// Returns the total length of the union of arcs given by their start and
+/// end angles within `2 pi` of each other.
+fn covered_length(arcs: &[(f32, f32)]) -> f32 {
+    // Split arcs crossing the branch cut so that all lie in [0, 2 pi].
+    let mut split = Vec::with_capacity(arcs.len() * 2);
+    for &(start, end) in arcs {
+        let length = end - start;
+        let start = start.rem_euclid(2.0 * PI);
+        let end = start + length;
+        if end > 2.0 * PI {
+            split.push((start, 2.0 * PI));
+            split.push((0.0, end - 2.0 * PI));
+        } else {
+            split.push((start, end));
+        }
+    }
+    split.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+    let mut total = 0.0;
+    let mut current: Option<(f32, f32)> = None;
+    for &(start, end) in split.iter() {
+        current = match current {
+            Some((low, high)) if start <= high => Some((low, high.max(end))),
+            Some((low, high)) => {
+                total += high - low;
+                Some((start, end))
+            }
+            None => Some((start, end)),
+        };
+    }
+    if let Some((low, high)) = current {
+        total += high - low;
+    }
+    total.min(2.0 * PI)
+}
+
+/// Returns the accessible surface area of each atom of a model, with radii
+/// by `radius` and a probe of `PROBE_RADIUS`.
+///
+/// All atoms are taken into account, so that hydrogens, waters or ligands
+/// should be removed beforehand if they are not to be.
+///
+/// Returns `None` in the cases of `Method::areas`, i.e. if the number of
+/// points or slices is zero or a position is not finite.
+///
+/// # Example
+///
+/// ```
+/// use biost::sasa::{atom_sasa, Method};
+/// use biost::{Atom, Model, Vector3d};
+/// let mut model = Model::new(1);
+/// model.atoms.push(Atom::new("O", Vector3d::zero()));
+/// model.atoms.push(Atom::new("O", Vector3d::new(20.0, 0.0, 0.0)));
+/// let areas = atom_sasa(&model, Method::LeeRichards(50)).unwrap();
+/// assert!((areas[1] - 4.0 * std::f32::consts::PI * 2.92 * 2.92).abs() < 0.5);
+/// assert!(atom_sasa(&model, Method::LeeRichards(0)).is_none());
+/// ```
+pub fn atom_sasa(model: &Model, method: Method) -> Option<Vec<f32>> {
+    let centers: Vec<Vector3d> = model.atoms.iter().map(|atom| atom.position).collect();
+    let radii: Vec<f32> = model.atoms.iter().map(radius).collect();
+    method.areas(&centers, &radii, PROBE_RADIUS)
+}
+
+/// Returns the residues of a model with the sums of the areas of their
+/// atoms, given in the order of the atoms of the model.
+///
+/// Returns `None` if the number of areas differs from that of atoms.
+pub fn residue_sasa<'a>(model: &'a Model, areas: &[f32]) -> Option<Vec<(Residue<'a>, f32)>> {
+    if areas.len() != model.atoms.len() {
+        return None;
+    }
+    let residues = model
+        .residues()
+        .map(|residue| {
+            let area = areas[residue.atom_indices()].iter().sum();
+            (residue, area)
+        })
+        .collect();
+    Some(residues)
+}
+
+/// The scales of the maximum accessible surface areas of amino acids in
+/// Gly-X-Gly tripeptides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scale {
+    /// The theoretical values of Tien et al. (2013).
+    TienTheoretical,
+    /// The empirical values of Tien et al. (2013).
+    TienEmpirical,
+    /// The values of Miller et al. (1987).
+    Miller,
+}
+
+/// The maximum areas of the theoretical and empirical scales of Tien et
+/// al. and that of Miller et al.
+const MAX_AREAS: [(&str, [f32; 3]); 20] = [
+    ("ALA", [129.0, 121.0, 113.0]),
+    ("ARG", [274.0, 265.0, 241.0]),
+    ("ASN", [195.0, 187.0, 158.0]),
+    ("ASP", [193.0, 187.0, 151.0]),
+    ("CYS", [167.0, 148.0, 140.0]),
+    ("GLN", [225.0, 214.0, 189.0]),
+    ("GLU", [223.0, 214.0, 183.0]),
+    ("GLY", [104.0, 97.0, 85.0]),
+    ("HIS", [224.0, 216.0, 194.0]),
+    ("ILE", [197.0, 195.0, 182.0]),
+    ("LEU", [201.0, 191.0, 180.0]),
+    ("LYS", [236.0, 230.0, 211.0]),
+    ("MET", [224.0, 203.0, 204.0]),
+    ("PHE", [240.0, 228.0, 218.0]),
+    ("PRO", [159.0, 154.0, 143.0]),
+    ("SER", [155.0, 143.0, 122.0]),
+    ("THR", [172.0, 163.0, 146.0]),
+    ("TRP", [285.0, 264.0, 259.0]),
+    ("TYR", [263.0, 255.0, 229.0]),
+    ("VAL", [174.0, 165.0, 160.0]),
+];
+
+impl Scale {
+    /// Returns the maximum area of an amino acid, or `None` if the residue
+    /// is not one of the standard amino acids.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::sasa::Scale;
+    /// assert_eq!(Some(129.0), Scale::TienTheoretical.max_area("ALA"));
+    /// assert_eq!(Some(85.0), Scale::Miller.max_area("GLY"));
+    /// assert_eq!(None, Scale::Miller.max_area("HOH"));
+    /// ```
+    pub fn max_area(&self, res_name: &str) -> Option<f32> {
+        let index = match self {
+            Scale::TienTheoretical => 0,
+            Scale::TienEmpirical => 1,
+            Scale::Miller => 2,
+        };
+        MAX_AREAS
+            .iter()
+            .find(|&&(name, _)| name == res_name)
+            .map(|&(_, areas)| areas[index])
+    }
+
+    /// Returns the area of a residue relative to its maximum area, or
+    /// `None` if the residue is not one of the standard amino acids.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::sasa::Scale;
+    /// assert_eq!(Some(0.5), Scale::Miller.relative("GLY", 42.5));
+    /// ```
+    pub fn relative(&self, res_name: &str, area: f32) -> Option<f32> {
+        self.max_area(res_name).map(|max| area / max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the exposed area of the first of two overlapping spheres.
+    fn exposed(r1: f32, r2: f32, d: f32) -> f32 {
+        let height = r1 - (d * d + r1 * r1 - r2 * r2) / (2.0 * d);
+        4.0 * PI * r1 * r1 - 2.0 * PI * r1 * height
+    }
+
+    #[test]
+    fn test_radius() {
+        let mut atom = Atom::new("1HG2", Vector3d::zero());
+        assert_eq!(1.20, radius(&atom));
+        atom.element = "Se".to_string();
+        assert_eq!(1.90, radius(&atom));
+        atom.element = "XX".to_string();
+        assert_eq!(DEFAULT_RADIUS, radius(&atom));
+        // Iron has no van der Waals radius of Bondi.
+        atom.element = "FE".to_string();
+        assert_eq!(DEFAULT_RADIUS, radius(&atom));
+        let chlorine = Atom::new("CL1", Vector3d::zero());
+        assert_eq!(1.75, radius(&chlorine));
+    }
+
+    #[test]
+    fn test_two_spheres() {
+        let centers = [Vector3d::zero(), Vector3d::new(1.0, 2.0, 2.0)];
+        let radii = [1.5, 1.0];
+        let expected = [exposed(2.9, 2.4, 3.0), exposed(2.4, 2.9, 3.0)];
+        for &method in [Method::ShrakeRupley(2000), Method::LeeRichards(200)].iter() {
+            let areas = method.areas(&centers, &radii, PROBE_RADIUS).unwrap();
+            for (area, expected) in areas.iter().zip(expected.iter()) {
+                assert!((area - expected).abs() / expected < 0.01);
+            }
+        }
+    }
+
+    #[test]
+    fn test_buried() {
+        // A small sphere inside a large one and a cluster of spheres.
+        let centers = [Vector3d::zero(), Vector3d::new(0.5, 0.0, 0.0)];
+        for &method in [Method::ShrakeRupley(100), Method::LeeRichards(20)].iter() {
+            let areas = method.areas(&centers, &[3.0, 1.0], 0.0).unwrap();
+            assert_eq!(0.0, areas[1]);
+            assert!((areas[0] - 4.0 * PI * 9.0).abs() < 0.5);
+        }
+
+        let mut centers = Vec::new();
+        for x in 0..4 {
+            for y in 0..4 {
+                for z in 0..4 {
+                    centers.push(Vector3d::new(x as f32, y as f32, z as f32) * 1.5);
+                }
+            }
+        }
+        let radii = vec![1.7; centers.len()];
+        let shrake = Method::ShrakeRupley(500)
+            .areas(&centers, &radii, PROBE_RADIUS)
+            .unwrap();
+        let lee = Method::LeeRichards(100)
+            .areas(&centers, &radii, PROBE_RADIUS)
+            .unwrap();
+        let total = |areas: &[f32]| areas.iter().sum::<f32>();
+        assert!((total(&shrake) - total(&lee)).abs() / total(&lee) < 0.01);
+        // An atom at the center of the cluster is buried.
+        assert_eq!(0.0, shrake[21]);
+        assert_eq!(0.0, lee[21]);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let centers = [Vector3d::zero()];
+        assert_eq!(None, Method::ShrakeRupley(0).areas(&centers, &[1.0], 1.4));
+        assert_eq!(None, Method::LeeRichards(10).areas(&centers, &[], 1.4));
+        assert_eq!(Some(vec![]), Method::default().areas(&[], &[], 1.4));
+
+        let mut model = Model::new(1);
+        model.atoms.push(Atom::new("C", Vector3d::zero()));
+        assert_eq!(None, atom_sasa(&model, Method::ShrakeRupley(0)));
+        model.atoms[0].position.x = f32::NAN;
+        assert_eq!(None, atom_sasa(&model, Method::default()));
+        assert_eq!(Some(vec![]), atom_sasa(&Model::new(1), Method::default()));
+    }
+
+    #[test]
+    fn test_residue_sasa() {
+        let mut model = Model::new(1);
+        for (res_seq, name) in [(1, "N"), (1, "CA"), (2, "N")].iter() {
+            let mut atom = Atom::new(name, Vector3d::new(*res_seq as f32 * 10.0, 0.0, 0.0));
+            atom.res_name = "GLY".to_string();
+            atom.res_seq = *res_seq;
+            model.atoms.push(atom);
+        }
+        let residues = residue_sasa(&model, &[1.0, 2.0, 4.0]).unwrap();
+        assert_eq!(2, residues.len());
+        assert_eq!(3.0, residues[0].1);
+        assert_eq!(4.0, residues[1].1);
+        assert!(residue_sasa(&model, &[1.0]).is_none());
+        assert_eq!(
+            Some(4.0 / 104.0),
+            Scale::TienTheoretical.relative("GLY", residues[1].1)
+        );
+    }
+}
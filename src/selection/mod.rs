@@ -0,0 +1,384 @@
+//! Selecting atoms of a model with a small query language.
+//!
+//! A selection is built from the following predicates on atoms:
+//!
+//! * `all`, `none`
+//! * `chain A B`, `resname HEM`, `name CA CB`, `element C N`, `altloc A`
+//! * `resid 10-50 60`, `serial 1:100`, with inclusive ranges written as
+//!   `first-last` or `first:last`
+//! * `protein`, `nucleic`, `backbone`, `sidechain`, `hydrogen`, `water`,
+//!   `hetero`
+//! * `within 5.0 of <selection>`, the atoms within a distance in Angstroms
+//!   of any atom of another selection
+//!
+//! combined with `not`, `and` and `or`, in order of decreasing precedence,
+//! and parentheses. Keywords are case-insensitive; values are not. Values
+//! which coincide with keywords or contain whitespace or parentheses are
+//! quoted with `"` or `'`, e.g. `chain "OR"`.
+//!
+//! # Example
+//!
+//! ```
+//! use biost::selection::Selection;
+//! let text = "\
+//! ATOM      1  N   ALA A  10       0.000   0.000   0.000  1.00  0.00           N
+//! ATOM      2  CA  ALA A  10       1.458   0.000   0.000  1.00  0.00           C
+//! ATOM      3  CB  ALA A  10       1.988   1.420   0.000  1.00  0.00           C
+//! ATOM      4  CA  ALA B  10       9.000   0.000   0.000  1.00  0.00           C
+//! HETATM    5 FE   HEM A 200       2.000   3.000   0.000  1.00  0.00          FE
+//! ";
+//! let structure = biost::pdb::read(text.as_bytes()).unwrap();
+//! let model = &structure.models[0];
+//!
+//! let selection = Selection::parse("chain A and resid 10-50 and name CA").unwrap();
+//! assert_eq!(vec![1], selection.evaluate(model));
+//!
+//! let selection: Selection = "within 2.0 of resname HEM".parse().unwrap();
+//! assert_eq!(vec![2, 4], selection.evaluate(model));
+//!
+//! let selection = Selection::parse("backbone").unwrap() & !Selection::parse("name N").unwrap();
+//! assert_eq!(vec![1, 3], selection.evaluate(model));
+//! ```
+
+use std::error;
+use std::fmt;
+use std::ops;
+use std::str::FromStr;
+
+use atom::Atom;
+use element::Element;
+use neighbor::Grid;
+use structure::Model;
+use Vector3d;
+
+mod parser;
+
+const PROTEIN_RESIDUES: [&str; 34] = [
+    "ALA", "ARG", "ASN", "ASP", "CYS", "GLN", "GLU", "GLY", "HIS", "ILE", "LEU", "LYS", "MET",
+    "PHE", "PRO", "SER", "THR", "TRP", "TYR", "VAL", "SEC", "PYL", "MSE", "HID", "HIE", "HIP",
+    "HSD", "HSE", "HSP", "CYX", "ASH", "GLH", "LYN", "ASX",
+];
+
+const NUCLEIC_RESIDUES: [&str; 12] = [
+    "A", "C", "G", "U", "I", "DA", "DC", "DG", "DT", "DU", "DI", "T",
+];
+
+const WATER_RESIDUES: [&str; 6] = ["HOH", "WAT", "H2O", "DOD", "SOL", "TIP3"];
+
+const BACKBONE_ATOMS: [&str; 4] = ["N", "CA", "C", "O"];
+
+/// A parsed selection.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Selection {
+    All,
+    None,
+    Chain(Vec<String>),
+    /// Inclusive ranges of residue sequence numbers.
+    ResId(Vec<(i32, i32)>),
+    ResName(Vec<String>),
+    Name(Vec<String>),
+    /// Element symbols, compared to the element of each atom by
+    /// `Element::of`, so that blank elements are guessed.
+    Element(Vec<String>),
+    AltLoc(Vec<char>),
+    /// Inclusive ranges of atom serial numbers.
+    Serial(Vec<(u32, u32)>),
+    /// Atoms of standard and common modified amino acids.
+    Protein,
+    /// Atoms of standard nucleotides.
+    Nucleic,
+    /// The `N`, `CA`, `C` and `O` atoms of amino acids.
+    Backbone,
+    /// The atoms of amino acids other than the backbone.
+    Sidechain,
+    Hydrogen,
+    Water,
+    /// Atoms from `HETATM` records.
+    Hetero,
+    /// Atoms within a distance of any atom of a selection. No atoms are
+    /// within a negative or non-finite distance.
+    Within(f32, Box<Selection>),
+    Not(Box<Selection>),
+    And(Box<Selection>, Box<Selection>),
+    Or(Box<Selection>, Box<Selection>),
+}
+
+impl Selection {
+    /// Parses a selection.
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        parser::parse(text)
+    }
+
+    /// Returns the indices in ascending order of the atoms of a model
+    /// matching the selection.
+    pub fn evaluate(&self, model: &Model) -> Vec<usize> {
+        self.mask(model)
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, selected)| selected)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Returns whether each atom of a model matches the selection.
+    pub fn mask(&self, model: &Model) -> Vec<bool> {
+        let atoms = &model.atoms;
+        match *self {
+            Selection::Within(cutoff, _) if !(cutoff >= 0.0 && cutoff.is_finite()) => {
+                vec![false; atoms.len()]
+            }
+            Selection::Within(cutoff, ref selection) => {
+                // Atoms at non-finite positions are never within a finite
+                // distance, so they are left out of the grid.
+                let targets: Vec<Vector3d<f32>> = selection
+                    .evaluate(model)
+                    .into_iter()
+                    .map(|i| atoms[i].position)
+                    .filter(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite())
+                    .collect();
+                let grid = match Grid::new(&targets, cutoff.max(f32::EPSILON)) {
+                    Some(grid) => grid,
+                    None => return vec![false; atoms.len()],
+                };
+                atoms
+                    .iter()
+                    .map(|atom| !grid.within(&atom.position, cutoff).is_empty())
+                    .collect()
+            }
+            Selection::Not(ref selection) => {
+                selection.mask(model).into_iter().map(|m| !m).collect()
+            }
+            Selection::And(ref a, ref b) => a
+                .mask(model)
+                .into_iter()
+                .zip(b.mask(model))
+                .map(|(a, b)| a && b)
+                .collect(),
+            Selection::Or(ref a, ref b) => a
+                .mask(model)
+                .into_iter()
+                .zip(b.mask(model))
+                .map(|(a, b)| a || b)
+                .collect(),
+            _ => atoms.iter().map(|atom| self.matches(atom)).collect(),
+        }
+    }
+
+    /// Returns whether an atom matches a predicate not depending on other
+    /// atoms.
+    fn matches(&self, atom: &Atom) -> bool {
+        let contains = |values: &[String], value: &str| values.iter().any(|v| v == value);
+        match *self {
+            Selection::All => true,
+            Selection::None => false,
+            Selection::Chain(ref ids) => contains(ids, &atom.chain_id),
+            Selection::ResId(ref ranges) => ranges
+                .iter()
+                .any(|&(first, last)| first <= atom.res_seq && atom.res_seq <= last),
+            Selection::ResName(ref names) => contains(names, &atom.res_name),
+            Selection::Name(ref names) => contains(names, &atom.name),
+            Selection::Element(ref elements) => Element::of(atom).is_some_and(|element| {
+                elements
+                    .iter()
+                    .any(|symbol| Element::from_symbol(symbol) == Some(element))
+            }),
+            Selection::AltLoc(ref alt_locs) => atom
+                .alt_loc
+                .is_some_and(|alt_loc| alt_locs.contains(&alt_loc)),
+            Selection::Serial(ref ranges) => ranges
+                .iter()
+                .any(|&(first, last)| first <= atom.serial && atom.serial <= last),
+            Selection::Protein => is_protein(atom),
+            Selection::Nucleic => NUCLEIC_RESIDUES.contains(&atom.res_name.as_str()),
+            Selection::Backbone => is_protein(atom) && BACKBONE_ATOMS.contains(&atom.name.as_str()),
+            Selection::Sidechain => {
+                is_protein(atom) && !BACKBONE_ATOMS.contains(&atom.name.as_str())
+            }
+            Selection::Hydrogen => Element::is_hydrogen_atom(atom),
+            Selection::Water => WATER_RESIDUES.contains(&atom.res_name.as_str()),
+            Selection::Hetero => atom.hetero,
+            Selection::Within(..) | Selection::Not(_) | Selection::And(..) | Selection::Or(..) => {
+                unreachable!()
+            }
+        }
+    }
+}
+
+fn is_protein(atom: &Atom) -> bool {
+    PROTEIN_RESIDUES.contains(&atom.res_name.as_str())
+}
+
+impl FromStr for Selection {
+    type Err = Error;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::parse(text)
+    }
+}
+
+impl ops::BitAnd for Selection {
+    type Output = Self;
+    fn bitand(self, selection: Self) -> Self::Output {
+        Selection::And(Box::new(self), Box::new(selection))
+    }
+}
+
+impl ops::BitOr for Selection {
+    type Output = Self;
+    fn bitor(self, selection: Self) -> Self::Output {
+        Selection::Or(Box::new(self), Box::new(selection))
+    }
+}
+
+impl ops::Not for Selection {
+    type Output = Self;
+    fn not(self) -> Self::Output {
+        Selection::Not(Box::new(self))
+    }
+}
+
+/// An error occurred while parsing a selection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    position: usize,
+    kind: ErrorKind,
+}
+
+/// The reason why a selection could not be parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    /// The selection ends where a predicate is expected.
+    UnexpectedEnd,
+    UnexpectedToken(String),
+    /// A keyword is not followed by any value.
+    MissingValue(String),
+    InvalidValue(String),
+    UnclosedParenthesis,
+    /// A quoted value is not closed by the same quotation mark.
+    UnterminatedQuote,
+}
+
+impl Error {
+    fn new(position: usize, kind: ErrorKind) -> Self {
+        Error { position, kind }
+    }
+
+    /// Returns the byte offset in the selection where the error occurred.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "column {}: ", self.position + 1)?;
+        match self.kind {
+            ErrorKind::UnexpectedEnd => write!(f, "unexpected end of selection"),
+            ErrorKind::UnexpectedToken(ref token) => write!(f, "unexpected {:?}", token),
+            ErrorKind::MissingValue(ref keyword) => write!(f, "missing value of {}", keyword),
+            ErrorKind::InvalidValue(ref value) => write!(f, "invalid value {:?}", value),
+            ErrorKind::UnclosedParenthesis => write!(f, "unclosed parenthesis"),
+            ErrorKind::UnterminatedQuote => write!(f, "unterminated quoted value"),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Vector3d;
+
+    fn atom(name: &str, element: &str, res_name: &str, res_seq: i32, x: f32) -> Atom {
+        let mut atom = Atom::new(name, Vector3d::new(x, 0.0, 0.0));
+        atom.element = element.to_string();
+        atom.res_name = res_name.to_string();
+        atom.res_seq = res_seq;
+        atom.chain_id = "A".to_string();
+        atom
+    }
+
+    fn sample() -> Model {
+        let mut model = Model::new(1);
+        model.atoms.push(atom("N", "N", "GLY", 1, 0.0));
+        model.atoms.push(atom("CA", "C", "GLY", 1, 1.0));
+        model.atoms.push(atom("HA2", "H", "GLY", 1, 1.5));
+        model.atoms.push(atom("CB", "C", "ALA", 2, 3.0));
+        model.atoms.push(atom("1HB", "", "ALA", 2, 3.5));
+        let mut water = atom("O", "O", "HOH", 101, 10.0);
+        water.hetero = true;
+        water.chain_id = "W".to_string();
+        water.serial = 50;
+        model.atoms.push(water);
+        let mut ligand = atom("P", "P", "ATP", 201, 20.0);
+        ligand.hetero = true;
+        ligand.alt_loc = Some('A');
+        model.atoms.push(ligand);
+        model.atoms.push(atom("P", "P", "DA", 301, 30.0));
+        model
+    }
+
+    fn select(text: &str) -> Vec<usize> {
+        Selection::parse(text).unwrap().evaluate(&sample())
+    }
+
+    #[test]
+    fn test_predicates() {
+        assert_eq!(vec![0, 1, 2, 3, 4, 5, 6, 7], select("all"));
+        assert!(select("none").is_empty());
+        assert_eq!(vec![5], select("chain W"));
+        assert_eq!(vec![3, 4, 5], select("resid 2 100-200"));
+        assert_eq!(vec![6], select("resname ATP"));
+        assert_eq!(vec![1, 3], select("name CA CB"));
+        assert_eq!(vec![1, 3], select("element C"));
+        assert_eq!(vec![2, 4], select("element h"));
+        assert_eq!(vec![6, 7], select("element P X"));
+        assert_eq!(vec![6], select("altloc A"));
+        assert_eq!(vec![5], select("serial 10:60"));
+        assert_eq!(vec![0, 1, 2, 3, 4], select("protein"));
+        assert_eq!(vec![7], select("nucleic"));
+        assert_eq!(vec![0, 1], select("backbone"));
+        assert_eq!(vec![2, 3, 4], select("sidechain"));
+        assert_eq!(vec![2, 4], select("hydrogen"));
+        assert_eq!(vec![5], select("water"));
+        assert_eq!(vec![5, 6], select("hetero"));
+    }
+
+    #[test]
+    fn test_operators() {
+        assert_eq!(vec![0, 1, 3], select("protein and not hydrogen"));
+        assert_eq!(vec![1, 5], select("name CA or water"));
+        assert_eq!(vec![5, 6], select("not (protein or nucleic)"));
+        assert_eq!(vec![0, 1, 2, 3, 4, 6, 7], select("not water"));
+
+        let selection = Selection::Protein & !Selection::Hydrogen | Selection::Water;
+        assert_eq!(vec![0, 1, 3, 5], selection.evaluate(&sample()));
+    }
+
+    #[test]
+    fn test_within() {
+        assert_eq!(vec![0, 1, 2, 3], select("within 1.5 of name HA2"));
+        assert_eq!(
+            vec![0, 1, 3],
+            select("within 1.5 of name HA2 and not hydrogen")
+        );
+        assert!(select("within 5 of none").is_empty());
+        assert_eq!(vec![2], select("within 0 of name HA2"));
+        assert_eq!(vec![0, 1, 2, 3, 4, 5, 6, 7], select("within 100 of name N"));
+
+        let mut model = sample();
+        model.atoms[0].position.x = f32::NAN;
+        let selection = Selection::parse("within 1.5 of resname GLY").unwrap();
+        assert_eq!(vec![1, 2, 3], selection.evaluate(&model));
+    }
+
+    #[test]
+    fn test_display_error() {
+        let err = Selection::parse("name CA and").unwrap_err();
+        assert_eq!("column 12: unexpected end of selection", err.to_string());
+    }
+}
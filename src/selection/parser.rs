@@ -0,0 +1,366 @@
+use std::iter::Peekable;
+use std::str::FromStr;
+use std::vec::IntoIter;
+
+use super::{Error, ErrorKind, Selection};
+
+const KEYWORDS: [&str; 23] = [
+    "and",
+    "or",
+    "not",
+    "all",
+    "none",
+    "within",
+    "of",
+    "chain",
+    "resid",
+    "resname",
+    "name",
+    "element",
+    "altloc",
+    "serial",
+    "protein",
+    "nucleic",
+    "backbone",
+    "sidechain",
+    "hydrogen",
+    "water",
+    "hetero",
+    "(",
+    ")",
+];
+
+type Tokens<'a> = Peekable<IntoIter<(usize, &'a str)>>;
+
+/// Splits a selection into words, quoted values and parentheses paired
+/// with their byte offsets. Quoted values keep their quotation marks.
+fn tokenize(text: &str) -> Result<Vec<(usize, &str)>, Error> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    while let Some(offset) = text[start..].find(|c: char| !c.is_whitespace()) {
+        let i = start + offset;
+        let c = text[i..].chars().next().unwrap_or(' ');
+        let end = match c {
+            '(' | ')' => i + 1,
+            '"' | '\'' => match text[i + 1..].find(c) {
+                Some(length) => i + 1 + length + 1,
+                None => return Err(Error::new(i, ErrorKind::UnterminatedQuote)),
+            },
+            _ => text[i..]
+                .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                .map_or(text.len(), |length| i + length),
+        };
+        tokens.push((i, &text[i..end]));
+        start = end;
+    }
+    Ok(tokens)
+}
+
+/// Removes the quotation marks around a quoted value.
+fn unquote(token: &str) -> &str {
+    let quoted = token.len() >= 2
+        && (token.starts_with('"') && token.ends_with('"')
+            || token.starts_with('\'') && token.ends_with('\''));
+    if quoted {
+        &token[1..token.len() - 1]
+    } else {
+        token
+    }
+}
+
+fn is_keyword(word: &str) -> bool {
+    KEYWORDS
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(word))
+}
+
+pub fn parse(text: &str) -> Result<Selection, Error> {
+    let mut tokens = tokenize(text)?.into_iter().peekable();
+    let selection = parse_or(&mut tokens, text.len())?;
+    match tokens.next() {
+        None => Ok(selection),
+        Some((position, token)) => Err(Error::new(
+            position,
+            ErrorKind::UnexpectedToken(token.to_string()),
+        )),
+    }
+}
+
+fn next_is(tokens: &mut Tokens, keyword: &str) -> bool {
+    match tokens.peek() {
+        Some(&(_, token)) if token.eq_ignore_ascii_case(keyword) => {
+            tokens.next();
+            true
+        }
+        _ => false,
+    }
+}
+
+fn parse_or(tokens: &mut Tokens, end: usize) -> Result<Selection, Error> {
+    let mut selection = parse_and(tokens, end)?;
+    while next_is(tokens, "or") {
+        selection = Selection::Or(Box::new(selection), Box::new(parse_and(tokens, end)?));
+    }
+    Ok(selection)
+}
+
+fn parse_and(tokens: &mut Tokens, end: usize) -> Result<Selection, Error> {
+    let mut selection = parse_not(tokens, end)?;
+    while next_is(tokens, "and") {
+        selection = Selection::And(Box::new(selection), Box::new(parse_not(tokens, end)?));
+    }
+    Ok(selection)
+}
+
+fn parse_not(tokens: &mut Tokens, end: usize) -> Result<Selection, Error> {
+    if next_is(tokens, "not") {
+        return Ok(Selection::Not(Box::new(parse_not(tokens, end)?)));
+    }
+    parse_primary(tokens, end)
+}
+
+fn parse_primary(tokens: &mut Tokens, end: usize) -> Result<Selection, Error> {
+    let (position, token) = tokens
+        .next()
+        .ok_or_else(|| Error::new(end, ErrorKind::UnexpectedEnd))?;
+    let keyword = token.to_ascii_lowercase();
+    let selection = match keyword.as_str() {
+        "(" => {
+            let selection = parse_or(tokens, end)?;
+            if !next_is(tokens, ")") {
+                return Err(Error::new(position, ErrorKind::UnclosedParenthesis));
+            }
+            selection
+        }
+        "all" => Selection::All,
+        "none" => Selection::None,
+        "protein" => Selection::Protein,
+        "nucleic" => Selection::Nucleic,
+        "backbone" => Selection::Backbone,
+        "sidechain" => Selection::Sidechain,
+        "hydrogen" => Selection::Hydrogen,
+        "water" => Selection::Water,
+        "hetero" => Selection::Hetero,
+        "chain" => Selection::Chain(values(tokens, position, token)?),
+        "resname" => Selection::ResName(values(tokens, position, token)?),
+        "name" => Selection::Name(values(tokens, position, token)?),
+        "element" => Selection::Element(values(tokens, position, token)?),
+        "altloc" => {
+            let mut alt_locs = Vec::new();
+            for (i, value) in words(tokens, position, token)? {
+                let value = unquote(value);
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => alt_locs.push(c),
+                    _ => {
+                        return Err(Error::new(i, ErrorKind::InvalidValue(value.to_string())));
+                    }
+                }
+            }
+            Selection::AltLoc(alt_locs)
+        }
+        "resid" => Selection::ResId(ranges(tokens, position, token)?),
+        "serial" => Selection::Serial(ranges(tokens, position, token)?),
+        "within" => {
+            let (i, value) = tokens
+                .next()
+                .ok_or_else(|| Error::new(end, ErrorKind::UnexpectedEnd))?;
+            let invalid = || Error::new(i, ErrorKind::InvalidValue(value.to_string()));
+            let cutoff: f32 = unquote(value).parse().map_err(|_| invalid())?;
+            if !(cutoff >= 0.0 && cutoff.is_finite()) {
+                return Err(invalid());
+            }
+            if !next_is(tokens, "of") {
+                let (i, token) = tokens.next().unwrap_or((end, ""));
+                return Err(Error::new(i, ErrorKind::UnexpectedToken(token.to_string())));
+            }
+            Selection::Within(cutoff, Box::new(parse_not(tokens, end)?))
+        }
+        _ => {
+            return Err(Error::new(
+                position,
+                ErrorKind::UnexpectedToken(token.to_string()),
+            ))
+        }
+    };
+    Ok(selection)
+}
+
+/// Returns the words following a keyword up to the next keyword.
+fn words<'a>(
+    tokens: &mut Tokens<'a>,
+    position: usize,
+    keyword: &str,
+) -> Result<Vec<(usize, &'a str)>, Error> {
+    let mut words = Vec::new();
+    while let Some(&(i, token)) = tokens.peek() {
+        if is_keyword(token) {
+            break;
+        }
+        words.push((i, token));
+        tokens.next();
+    }
+    if words.is_empty() {
+        return Err(Error::new(
+            position,
+            ErrorKind::MissingValue(keyword.to_string()),
+        ));
+    }
+    Ok(words)
+}
+
+fn values(tokens: &mut Tokens, position: usize, keyword: &str) -> Result<Vec<String>, Error> {
+    Ok(words(tokens, position, keyword)?
+        .into_iter()
+        .map(|(_, word)| unquote(word).to_string())
+        .collect())
+}
+
+/// Parses values such as `5`, `10-50` or `-3:4` into inclusive ranges.
+fn ranges<T: FromStr + Copy>(
+    tokens: &mut Tokens,
+    position: usize,
+    keyword: &str,
+) -> Result<Vec<(T, T)>, Error> {
+    let mut ranges = Vec::new();
+    for (i, word) in words(tokens, position, keyword)? {
+        let invalid = || Error::new(i, ErrorKind::InvalidValue(word.to_string()));
+        let word = unquote(word);
+        // A leading minus is the sign of the first number.
+        let separator = word
+            .char_indices()
+            .skip(1)
+            .find(|&(_, c)| c == '-' || c == ':')
+            .map(|(s, _)| s);
+        let range = match separator {
+            Some(s) => {
+                let first = word[..s].parse().map_err(|_| invalid())?;
+                let last = word[s + 1..].parse().map_err(|_| invalid())?;
+                (first, last)
+            }
+            None => {
+                let value = word.parse().map_err(|_| invalid())?;
+                (value, value)
+            }
+        };
+        ranges.push(range);
+    }
+    Ok(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            vec![(0, "not"), (4, "("), (5, "name"), (10, "CA"), (12, ")")],
+            tokenize("not (name CA)").unwrap()
+        );
+        assert_eq!(
+            vec![(0, "name"), (5, "\"C1 A\""), (12, "'OR'"), (17, "O5'")],
+            tokenize("name \"C1 A\" 'OR' O5'").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_quoted_values() {
+        let selection = parse("chain \"OR\" 'of' and resname \"ALL\" and altloc 'A'").unwrap();
+        let expected = Selection::And(
+            Box::new(Selection::And(
+                Box::new(Selection::Chain(strings(&["OR", "of"]))),
+                Box::new(Selection::ResName(strings(&["ALL"]))),
+            )),
+            Box::new(Selection::AltLoc(vec!['A'])),
+        );
+        assert_eq!(expected, selection);
+        assert_eq!(
+            Selection::Name(strings(&["C1'", ""])),
+            parse("name \"C1'\" ''").unwrap()
+        );
+
+        let err = parse("name \"CA").unwrap_err();
+        assert_eq!(5, err.position());
+        assert_eq!(&ErrorKind::UnterminatedQuote, err.kind());
+        // A quoted keyword is a value, not a predicate.
+        let err = parse("\"all\"").unwrap_err();
+        assert_eq!(
+            &ErrorKind::UnexpectedToken("\"all\"".to_string()),
+            err.kind()
+        );
+    }
+
+    #[test]
+    fn test_precedence() {
+        let selection = parse("chain A and resid 10-50 -3 or not name CA CB").unwrap();
+        let expected = Selection::Or(
+            Box::new(Selection::And(
+                Box::new(Selection::Chain(strings(&["A"]))),
+                Box::new(Selection::ResId(vec![(10, 50), (-3, -3)])),
+            )),
+            Box::new(Selection::Not(Box::new(Selection::Name(strings(&[
+                "CA", "CB",
+            ]))))),
+        );
+        assert_eq!(expected, selection);
+    }
+
+    #[test]
+    fn test_within() {
+        let selection = parse("WITHIN 5.0 of resname HEM and (protein or water)").unwrap();
+        let expected = Selection::And(
+            Box::new(Selection::Within(
+                5.0,
+                Box::new(Selection::ResName(strings(&["HEM"]))),
+            )),
+            Box::new(Selection::Or(
+                Box::new(Selection::Protein),
+                Box::new(Selection::Water),
+            )),
+        );
+        assert_eq!(expected, selection);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = parse("name").unwrap_err();
+        assert_eq!(0, err.position());
+        assert_eq!(&ErrorKind::MissingValue("name".to_string()), err.kind());
+
+        let err = parse("chain A and").unwrap_err();
+        assert_eq!(11, err.position());
+        assert_eq!(&ErrorKind::UnexpectedEnd, err.kind());
+
+        let err = parse("(chain A").unwrap_err();
+        assert_eq!(&ErrorKind::UnclosedParenthesis, err.kind());
+
+        let err = parse("resid 1-x").unwrap_err();
+        assert_eq!(6, err.position());
+        assert_eq!(&ErrorKind::InvalidValue("1-x".to_string()), err.kind());
+
+        let err = parse("protein backbone").unwrap_err();
+        assert_eq!(8, err.position());
+        assert_eq!(
+            &ErrorKind::UnexpectedToken("backbone".to_string()),
+            err.kind()
+        );
+
+        let err = parse("within 5 resname HEM").unwrap_err();
+        assert_eq!(9, err.position());
+
+        for cutoff in ["-5", "NaN", "inf", "-0.5"] {
+            let err = parse(&format!("within {} of name CA", cutoff)).unwrap_err();
+            assert_eq!(7, err.position());
+            assert_eq!(&ErrorKind::InvalidValue(cutoff.to_string()), err.kind());
+        }
+        assert!(parse("within 0 of name CA").is_ok());
+
+        let err = parse("altloc AB").unwrap_err();
+        assert_eq!(&ErrorKind::InvalidValue("AB".to_string()), err.kind());
+    }
+}
@@ -0,0 +1,360 @@
+//! Superposition-based similarity scores of protein structures.
+//!
+//! The scores compare CA atoms of a model to those of a reference, e.g. the
+//! native structure, where the i-th position of the model is aligned to
+//! the i-th of the reference. Unlike the RMSD, they are insensitive to a
+//! few large deviations, since each is computed at the superposition
+//! maximizing it, which is searched as in the TM-score program:
+//! superposition of every fragment of length `L`, `L/2`, `L/4`, ... and 4
+//! is iteratively refined onto the residues close after the superposition.
+//!
+//! All scores are normalized by a given length, usually that of the whole
+//! reference, which may exceed the number of aligned residues. They return
+//! `None` if the model and the reference differ in length, are empty, or
+//! the length is zero.
+
+use float::Float;
+use superposition::kabsch;
+use transform::Transform;
+use Vector3d;
+
+/// The maximum number of refinements of a superposition.
+const MAX_ITERATIONS: usize = 20;
+
+/// The minimum length of fragments to seed a superposition.
+const MIN_FRAGMENT: usize = 4;
+
+/// The cutoffs in Angstroms of GDT_TS.
+const GDT_TS_CUTOFFS: [f32; 4] = [1.0, 2.0, 4.0, 8.0];
+
+/// The cutoffs in Angstroms of GDT_HA.
+const GDT_HA_CUTOFFS: [f32; 4] = [0.5, 1.0, 2.0, 4.0];
+
+/// The TM-score of a model and the superposition giving it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TmScore<T: Float = f32> {
+    pub score: T,
+    /// The distance scale in Angstroms.
+    pub d0: T,
+    /// The transformation superposing the model onto the reference.
+    pub transform: Transform<T>,
+    /// The distance between each aligned pair after superposition.
+    pub distances: Vec<T>,
+}
+
+/// Returns the distance scale `d0` of the TM-score normalized by given
+/// length, `1.24 * (length - 15)^(1/3) - 1.8`, which is at least 0.5.
+///
+/// # Example
+///
+/// ```
+/// use biost::similarity::tm_score_d0;
+/// assert!((tm_score_d0::<f64>(100) - 3.6521).abs() < 1e-4);
+/// assert_eq!(0.5, tm_score_d0::<f32>(10));
+/// ```
+pub fn tm_score_d0<T: Float>(length: usize) -> T {
+    if length <= 21 {
+        return T::from_f32(0.5);
+    }
+    let d0 = 1.24 * ((length - 15) as f64).cbrt() - 1.8;
+    T::from_f64(d0.max(0.5))
+}
+
+/// Returns the TM-score of a model normalized by given length.
+///
+/// # Example
+///
+/// ```
+/// use biost::similarity::tm_score;
+/// use biost::{Rotation, Vector3d};
+/// let reference: Vec<Vector3d> = (0..30)
+///     .map(|i| {
+///         let t = i as f32 * 0.5;
+///         Vector3d::new(2.3 * t.cos(), 2.3 * t.sin(), 1.5 * t)
+///     })
+///     .collect();
+/// let rotation = Rotation::from_axis_angle(&Vector3d::new(0.0, 1.0, 0.0), 1.0).unwrap();
+/// let mut model: Vec<Vector3d> = reference.iter().map(|v| rotation.rotate(v)).collect();
+/// model[29].x += 20.0;
+///
+/// let result = tm_score(&model, &reference, reference.len()).unwrap();
+/// assert!(result.score > 0.96 && result.score < 0.97);
+/// assert!(result.distances[0] < 1e-3);
+/// assert!(result.distances[29] > 15.0);
+/// ```
+pub fn tm_score<T: Float>(
+    model: &[Vector3d<T>],
+    reference: &[Vector3d<T>],
+    length: usize,
+) -> Option<TmScore<T>> {
+    tm_score_with_d0(model, reference, length, tm_score_d0(length))
+}
+
+/// Returns the TM-score of a model normalized by given length with a
+/// distance scale `d0` other than the standard one.
+pub fn tm_score_with_d0<T: Float>(
+    model: &[Vector3d<T>],
+    reference: &[Vector3d<T>],
+    length: usize,
+    d0: T,
+) -> Option<TmScore<T>> {
+    let (score, transform) = tm_score_search(model, reference, length, d0, 1)?;
+    Some(TmScore {
+        score,
+        d0,
+        transform,
+        distances: distances(model, reference, &transform),
+    })
+}
+
+/// Returns the maximum TM-score and the superposition giving it, trying
+/// only every `step`-th fragment as a seed to save time.
+pub(crate) fn tm_score_search<T: Float>(
+    model: &[Vector3d<T>],
+    reference: &[Vector3d<T>],
+    length: usize,
+    d0: T,
+    step: usize,
+) -> Option<(T, Transform<T>)> {
+    if length == 0 {
+        return None;
+    }
+    let d0_squared = d0 * d0;
+    let normalization = T::from_f64(length as f64);
+    let score = |distances: &[T]| -> T {
+        let sum: T = distances
+            .iter()
+            .map(|&d| T::one() / (T::one() + d * d / d0_squared))
+            .sum();
+        sum / normalization
+    };
+    let cutoff = d0.max(T::from_f32(4.5)).min(T::from_f32(8.0));
+    search(model, reference, cutoff, step, score)
+}
+
+/// Returns the global distance test score, the mean over the cutoffs of the
+/// fraction of residues within each cutoff from the reference.
+///
+/// Each fraction is maximized over superpositions independently. The score
+/// ranges from 0 to 1, instead of the percentage used in CASP.
+pub fn gdt<T: Float>(
+    model: &[Vector3d<T>],
+    reference: &[Vector3d<T>],
+    cutoffs: &[T],
+    length: usize,
+) -> Option<T> {
+    if length == 0 || cutoffs.is_empty() {
+        return None;
+    }
+    let normalization = T::from_f64(length as f64);
+    let mut sum = T::zero();
+    for &cutoff in cutoffs {
+        let count = |distances: &[T]| -> T {
+            let count = distances.iter().filter(|&&d| d <= cutoff).count();
+            T::from_f64(count as f64) / normalization
+        };
+        sum += search(model, reference, cutoff, 1, count)?.0;
+    }
+    Some(sum / T::from_f64(cutoffs.len() as f64))
+}
+
+/// Returns GDT_TS, the global distance test score with cutoffs of 1, 2, 4
+/// and 8 Angstroms.
+///
+/// # Example
+///
+/// ```
+/// use biost::similarity::{gdt_ha, gdt_ts};
+/// use biost::Vector3d;
+/// let reference: Vec<Vector3d> = (0..10).map(|i| Vector3d::new(3.8 * i as f32, 0.0, 0.0)).collect();
+/// let mut model = reference.clone();
+/// model[9].y += 30.0;
+/// assert_eq!(Some(0.9), gdt_ts(&model, &reference, 10));
+/// assert_eq!(Some(0.45), gdt_ha(&model, &reference, 20));
+/// ```
+pub fn gdt_ts<T: Float>(
+    model: &[Vector3d<T>],
+    reference: &[Vector3d<T>],
+    length: usize,
+) -> Option<T> {
+    let cutoffs: Vec<T> = GDT_TS_CUTOFFS.iter().map(|&c| T::from_f32(c)).collect();
+    gdt(model, reference, &cutoffs, length)
+}
+
+/// Returns GDT_HA, the high accuracy global distance test score with
+/// cutoffs of 0.5, 1, 2 and 4 Angstroms.
+pub fn gdt_ha<T: Float>(
+    model: &[Vector3d<T>],
+    reference: &[Vector3d<T>],
+    length: usize,
+) -> Option<T> {
+    let cutoffs: Vec<T> = GDT_HA_CUTOFFS.iter().map(|&c| T::from_f32(c)).collect();
+    gdt(model, reference, &cutoffs, length)
+}
+
+/// Returns the distances between the aligned pairs after transforming the
+/// model.
+fn distances<T: Float>(
+    model: &[Vector3d<T>],
+    reference: &[Vector3d<T>],
+    transform: &Transform<T>,
+) -> Vec<T> {
+    model
+        .iter()
+        .zip(reference)
+        .map(|(p, q)| transform.apply(p).distance(q))
+        .collect()
+}
+
+/// Returns the maximum score over superpositions and the superposition
+/// giving it.
+///
+/// Each superposition seeded with a fragment is refined by superposing the
+/// pairs within `cutoff`, or the three closest pairs if there are fewer.
+/// Only every `step`-th fragment of each length is tried.
+fn search<T: Float, F: Fn(&[T]) -> T>(
+    model: &[Vector3d<T>],
+    reference: &[Vector3d<T>],
+    cutoff: T,
+    step: usize,
+    score: F,
+) -> Option<(T, Transform<T>)> {
+    let len = model.len();
+    if len != reference.len() || len == 0 {
+        return None;
+    }
+    let min_fragment = MIN_FRAGMENT.min(len);
+    let min_selected = 3.min(len);
+
+    let mut best: Option<(T, Transform<T>)> = None;
+    let mut weights = vec![T::zero(); len];
+    let mut fragment = len;
+    loop {
+        for start in (0..=(len - fragment)).step_by(step.max(1)) {
+            for (i, weight) in weights.iter_mut().enumerate() {
+                *weight = if start <= i && i < start + fragment {
+                    T::one()
+                } else {
+                    T::zero()
+                };
+            }
+            for _ in 0..MAX_ITERATIONS {
+                let transform = kabsch(model, reference, Some(&weights))?;
+                let distances = distances(model, reference, &transform);
+                let value = score(&distances);
+                if best.as_ref().is_none_or(|&(max, _)| value > max) {
+                    best = Some((value, transform));
+                }
+
+                let mut selected: Vec<usize> =
+                    (0..len).filter(|&i| distances[i] < cutoff).collect();
+                if selected.len() < min_selected {
+                    selected = (0..len).collect();
+                    selected.sort_by(|&i, &j| {
+                        distances[i]
+                            .partial_cmp(&distances[j])
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    });
+                    selected.truncate(min_selected);
+                }
+                let mut next = vec![T::zero(); len];
+                for i in selected {
+                    next[i] = T::one();
+                }
+                if next == weights {
+                    break;
+                }
+                weights = next;
+            }
+        }
+        if fragment == min_fragment {
+            break;
+        }
+        fragment = (fragment / 2).max(min_fragment);
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rotation::{EulerConvention, Rotation};
+
+    fn helix(len: usize) -> Vec<Vector3d<f64>> {
+        (0..len)
+            .map(|i| {
+                let t = i as f64 * 100f64.to_radians();
+                Vector3d::new(2.3 * t.cos(), 2.3 * t.sin(), 1.5 * i as f64)
+            })
+            .collect()
+    }
+
+    fn moved(positions: &[Vector3d<f64>]) -> Vec<Vector3d<f64>> {
+        let transform = Transform::new(
+            Rotation::from_euler(EulerConvention::ZYZ, 0.7, -1.1, 2.0),
+            Vector3d::new(4.0, -8.0, 1.0),
+        );
+        positions.iter().map(|v| transform.apply(v)).collect()
+    }
+
+    #[test]
+    fn test_tm_score_identical() {
+        let reference = helix(40);
+        let model = moved(&reference);
+        let result = tm_score(&model, &reference, 40).unwrap();
+        assert!((result.score - 1.0).abs() < 1e-9);
+        assert!(result.distances.iter().all(|&d| d < 1e-6));
+
+        // Normalized by the length of the whole reference
+        let result = tm_score(&model[..20], &reference[..20], 40).unwrap();
+        assert!((result.score - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tm_score_outliers() {
+        let reference = helix(40);
+        let mut model = moved(&reference);
+        // Displace the last ten residues by a hinge motion.
+        let hinge = Transform::new(
+            Rotation::from_axis_angle(&Vector3d::new(1.0, 0.0, 0.0), 1.0).unwrap(),
+            Vector3d::new(0.0, 0.0, 0.0),
+        );
+        for position in model.iter_mut().skip(30) {
+            *position = hinge.apply(position);
+        }
+        let result = tm_score(&model, &reference, 40).unwrap();
+        // The superposition is found on the thirty unmoved residues.
+        assert!(result.distances[..30].iter().all(|&d| d < 1e-6));
+        assert!(result.score > 0.75);
+        assert_eq!(
+            result.score,
+            tm_score_with_d0(&model, &reference, 40, result.d0)
+                .unwrap()
+                .score
+        );
+    }
+
+    #[test]
+    fn test_gdt() {
+        let reference = helix(20);
+        let mut model = moved(&reference);
+        assert_eq!(Some(1.0), gdt_ts(&model, &reference, 20));
+        assert_eq!(Some(1.0), gdt_ha(&model, &reference, 20));
+
+        // Move five residues far away and normalize by the double length.
+        for position in model.iter_mut().take(5) {
+            position.z += 50.0;
+        }
+        assert_eq!(Some(0.375), gdt_ts(&model, &reference, 40));
+        assert_eq!(Some(0.375), gdt(&model, &reference, &[3.0], 40));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let reference = helix(10);
+        assert!(tm_score(&reference, &reference[1..], 10).is_none());
+        assert!(tm_score(&reference, &reference, 0).is_none());
+        assert!(gdt_ts::<f64>(&[], &[], 10).is_none());
+        assert!(gdt(&reference, &reference, &[], 10).is_none());
+    }
+}
@@ -0,0 +1,573 @@
+//! The hierarchy of a molecular structure.
+//!
+//! A `Structure` consists of `Model`s, each of which owns its atoms. Chains
+//! and residues are not stored separately but are views into contiguous
+//! runs of the atoms of a model:
+//!
+//! * a chain is a maximal run of atoms sharing the chain identifier, which
+//!   is further split at chain ends (`TER` records), and
+//! * a residue is a maximal run of atoms of a chain sharing the residue
+//!   name, sequence number and insertion code.
+//!
+//! Hence a model may contain several chains with the same identifier, e.g.
+//! a polymer chain and the waters listed after its `TER` record.
+
+use std::fmt;
+use std::iter;
+use std::ops::Range;
+use std::slice;
+
+use atom::Atom;
+use transform::Transform;
+
+/// A molecular structure, which consists of one or more models.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Structure {
+    pub models: Vec<Model>,
+    /// Pairs of serial numbers of explicitly bonded atoms.
+    pub bonds: Vec<(u32, u32)>,
+}
+
+impl Structure {
+    /// Returns the model with given serial number.
+    pub fn model(&self, serial: i32) -> Option<&Model> {
+        self.models.iter().find(|model| model.serial == serial)
+    }
+
+    /// Returns an iterator over the chains of all models.
+    pub fn chains<'a>(&'a self) -> impl Iterator<Item = Chain<'a>> + 'a {
+        self.models.iter().flat_map(|model| model.chains())
+    }
+
+    /// Returns an iterator over the residues of all models.
+    pub fn residues<'a>(&'a self) -> impl Iterator<Item = Residue<'a>> + 'a {
+        self.models.iter().flat_map(|model| model.residues())
+    }
+
+    /// Returns an iterator over the atoms of all models.
+    pub fn atoms<'a>(&'a self) -> impl Iterator<Item = &'a Atom> + 'a {
+        self.models.iter().flat_map(|model| model.atoms.iter())
+    }
+
+    /// Moves the atoms of all models in place by a transformation.
+    pub fn transform(&mut self, transform: &Transform) {
+        for model in self.models.iter_mut() {
+            model.transform(transform);
+        }
+    }
+}
+
+/// A set of coordinates of a structure, e.g. one NMR conformer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Model {
+    /// The model serial number.
+    pub serial: i32,
+    pub atoms: Vec<Atom>,
+    /// The number of atoms preceding each chain terminus (`TER`).
+    pub chain_ends: Vec<usize>,
+}
+
+impl Model {
+    /// Returns an empty model with given serial number.
+    pub fn new(serial: i32) -> Self {
+        Model {
+            serial,
+            atoms: Vec::new(),
+            chain_ends: Vec::new(),
+        }
+    }
+
+    /// Returns an iterator over the chains.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let text = "\
+    /// ATOM      1  CA  GLY A   1       0.000   0.000   0.000  1.00  0.00           C
+    /// ATOM      2  CA  GLY A   2       3.800   0.000   0.000  1.00  0.00           C
+    /// ATOM      3  CA  GLY B   1       0.000   5.000   0.000  1.00  0.00           C
+    /// ";
+    /// let structure = biost::pdb::read(text.as_bytes()).unwrap();
+    /// let ids: Vec<&str> = structure.models[0].chains().map(|chain| chain.id()).collect();
+    /// assert_eq!(vec!["A", "B"], ids);
+    /// ```
+    pub fn chains(&self) -> Chains<'_> {
+        Chains {
+            model: self,
+            start: 0,
+        }
+    }
+
+    /// Returns an iterator over the residues of all chains.
+    pub fn residues<'a>(&'a self) -> impl Iterator<Item = Residue<'a>> + 'a {
+        self.chains().flat_map(|chain| chain.residues())
+    }
+
+    /// Returns the first chain with given identifier.
+    pub fn chain(&self, id: &str) -> Option<Chain<'_>> {
+        self.chains().find(|chain| chain.id() == id)
+    }
+
+    /// Returns the first residue with given chain identifier, sequence
+    /// number and insertion code.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let text = "\
+    /// ATOM      1  CA  GLY A   1       0.000   0.000   0.000  1.00  0.00           C
+    /// ATOM      2  CA  SER A   1A      3.800   0.000   0.000  1.00  0.00           C
+    /// TER       3      SER A   1A
+    /// HETATM    4  O   HOH A 101       0.000   5.000   0.000  1.00  0.00           O
+    /// ";
+    /// let structure = biost::pdb::read(text.as_bytes()).unwrap();
+    /// let model = &structure.models[0];
+    /// assert_eq!("SER", model.residue("A", 1, Some('A')).unwrap().name());
+    /// assert_eq!("HOH", model.residue("A", 101, None).unwrap().name());
+    /// assert!(model.residue("A", 2, None).is_none());
+    /// ```
+    pub fn residue(
+        &self,
+        chain_id: &str,
+        res_seq: i32,
+        i_code: Option<char>,
+    ) -> Option<Residue<'_>> {
+        self.chains()
+            .filter(|chain| chain.id() == chain_id)
+            .filter_map(|chain| chain.residue(res_seq, i_code))
+            .next()
+    }
+
+    /// Returns the residue containing the atom at given index.
+    pub fn residue_of(&self, index: usize) -> Option<Residue<'_>> {
+        if index >= self.atoms.len() {
+            return None;
+        }
+        self.chains()
+            .find(|chain| chain.range.end > index)
+            .and_then(|chain| chain.residues().find(|residue| residue.range.end > index))
+    }
+
+    /// Removes the atoms at alternate locations other than `alt_loc`.
+    /// Atoms without an alternate location indicator are kept.
+    pub fn retain_alt_loc(&mut self, alt_loc: char) {
+        let mut kept = Vec::with_capacity(self.atoms.len());
+        let mut removed = 0;
+        let mut chain_ends = self.chain_ends.iter().peekable();
+        let mut new_chain_ends = Vec::with_capacity(self.chain_ends.len());
+        for (i, atom) in self.atoms.drain(..).enumerate() {
+            while chain_ends.peek().is_some_and(|&&end| end <= i) {
+                new_chain_ends.push(chain_ends.next().unwrap() - removed);
+            }
+            if atom.alt_loc.is_none_or(|c| c == alt_loc) {
+                kept.push(atom);
+            } else {
+                removed += 1;
+            }
+        }
+        for end in chain_ends {
+            new_chain_ends.push(end - removed);
+        }
+        self.atoms = kept;
+        self.chain_ends = new_chain_ends;
+    }
+
+    /// Moves the atoms in place by a transformation.
+    ///
+    /// # Example
+    ///
+    /// Superposing a model onto another by their CA atoms:
+    ///
+    /// ```
+    /// use biost::superposition::kabsch;
+    /// use biost::{Rotation, Transform, Vector3d};
+    /// let text = "\
+    /// ATOM      1  CA  GLY A   1       0.000   0.000   0.000  1.00  0.00           C
+    /// ATOM      2  CA  GLY A   2       3.800   0.000   0.000  1.00  0.00           C
+    /// ATOM      3  CA  GLY A   3       5.000   3.600   0.000  1.00  0.00           C
+    /// ";
+    /// let reference = biost::pdb::read(text.as_bytes()).unwrap().models.remove(0);
+    /// let mut model = reference.clone();
+    /// let axis = Vector3d::new(1.0, 1.0, 0.0);
+    /// let rotation = Rotation::from_axis_angle(&axis, 1.0).unwrap();
+    /// model.transform(&Transform::new(rotation, Vector3d::new(0.0, 0.0, 7.0)));
+    ///
+    /// let positions = |model: &biost::Model| -> Vec<Vector3d> {
+    ///     model.atoms.iter().map(|atom| atom.position).collect()
+    /// };
+    /// let transform = kabsch(&positions(&model), &positions(&reference), None).unwrap();
+    /// model.transform(&transform);
+    /// for (a, b) in model.atoms.iter().zip(reference.atoms.iter()) {
+    ///     assert!((a.position - b.position).norm() < 1e-4);
+    /// }
+    /// ```
+    pub fn transform(&mut self, transform: &Transform) {
+        for atom in self.atoms.iter_mut() {
+            atom.position = transform.apply(&atom.position);
+        }
+    }
+
+    /// Returns the index past the last atom of the chain starting at
+    /// `start`.
+    fn chain_end(&self, start: usize) -> usize {
+        let id = &self.atoms[start].chain_id;
+        let limit = self
+            .chain_ends
+            .iter()
+            .cloned()
+            .filter(|&end| end > start)
+            .min()
+            .unwrap_or(self.atoms.len())
+            .min(self.atoms.len());
+        self.atoms[start..limit]
+            .iter()
+            .position(|atom| atom.chain_id != *id)
+            .map_or(limit, |offset| start + offset)
+    }
+}
+
+/// An iterator over the chains of a model.
+pub struct Chains<'a> {
+    model: &'a Model,
+    start: usize,
+}
+
+impl<'a> Iterator for Chains<'a> {
+    type Item = Chain<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start >= self.model.atoms.len() {
+            return None;
+        }
+        let end = self.model.chain_end(self.start);
+        let chain = Chain {
+            model: self.model,
+            range: self.start..end,
+        };
+        self.start = end;
+        Some(chain)
+    }
+}
+
+/// A chain of a model.
+#[derive(Clone)]
+pub struct Chain<'a> {
+    model: &'a Model,
+    range: Range<usize>,
+}
+
+impl<'a> Chain<'a> {
+    /// Returns the chain identifier.
+    pub fn id(&self) -> &'a str {
+        &self.model.atoms[self.range.start].chain_id
+    }
+
+    /// Returns the model to which the chain belongs.
+    pub fn model(&self) -> &'a Model {
+        self.model
+    }
+
+    /// Returns the indices of the atoms in the model.
+    pub fn atom_indices(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    pub fn atoms(&self) -> slice::Iter<'a, Atom> {
+        self.model.atoms[self.range.clone()].iter()
+    }
+
+    pub fn residues(&self) -> Residues<'a> {
+        Residues {
+            chain: self.clone(),
+            start: self.range.start,
+        }
+    }
+
+    /// Returns the residue with given sequence number and insertion code.
+    pub fn residue(&self, res_seq: i32, i_code: Option<char>) -> Option<Residue<'a>> {
+        self.residues()
+            .find(|residue| residue.res_seq() == res_seq && residue.i_code() == i_code)
+    }
+}
+
+impl<'a> fmt::Debug for Chain<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Chain")
+            .field("id", &self.id())
+            .field("atoms", &self.range)
+            .finish()
+    }
+}
+
+/// An iterator over the residues of a chain.
+pub struct Residues<'a> {
+    chain: Chain<'a>,
+    start: usize,
+}
+
+impl<'a> Iterator for Residues<'a> {
+    type Item = Residue<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chain_end = self.chain.range.end;
+        if self.start >= chain_end {
+            return None;
+        }
+        let atoms = &self.chain.model.atoms;
+        let first = &atoms[self.start];
+        let end = atoms[self.start..chain_end]
+            .iter()
+            .position(|atom| {
+                atom.res_seq != first.res_seq
+                    || atom.i_code != first.i_code
+                    || atom.res_name != first.res_name
+            })
+            .map_or(chain_end, |offset| self.start + offset);
+        let residue = Residue {
+            chain: self.chain.clone(),
+            range: self.start..end,
+        };
+        self.start = end;
+        Some(residue)
+    }
+}
+
+/// A residue of a chain, e.g. an amino acid, a ligand or a water.
+#[derive(Clone)]
+pub struct Residue<'a> {
+    chain: Chain<'a>,
+    range: Range<usize>,
+}
+
+impl<'a> Residue<'a> {
+    fn first(&self) -> &'a Atom {
+        &self.chain.model.atoms[self.range.start]
+    }
+
+    /// Returns the residue name, e.g. `ALA`.
+    pub fn name(&self) -> &'a str {
+        self.first().res_name.as_str()
+    }
+
+    pub fn res_seq(&self) -> i32 {
+        self.first().res_seq
+    }
+
+    pub fn i_code(&self) -> Option<char> {
+        self.first().i_code
+    }
+
+    /// Returns whether the residue comes from `HETATM` records.
+    pub fn is_hetero(&self) -> bool {
+        self.first().hetero
+    }
+
+    /// Returns the chain to which the residue belongs.
+    pub fn chain(&self) -> Chain<'a> {
+        self.chain.clone()
+    }
+
+    /// Returns the model to which the residue belongs.
+    pub fn model(&self) -> &'a Model {
+        self.chain.model
+    }
+
+    /// Returns the indices of the atoms in the model.
+    pub fn atom_indices(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    pub fn atoms(&self) -> slice::Iter<'a, Atom> {
+        self.chain.model.atoms[self.range.clone()].iter()
+    }
+
+    /// Returns the atom with given name. If the atom has alternate
+    /// locations, the one with the highest occupancy is returned, the first
+    /// one on ties.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let text = "\
+    /// ATOM      1  CA  SER A   1       0.000   0.000   0.000  1.00  0.00           C
+    /// ATOM      2  OG ASER A   1       1.000   0.000   0.000  0.40  0.00           O
+    /// ATOM      3  OG BSER A   1       2.000   0.000   0.000  0.60  0.00           O
+    /// ";
+    /// let structure = biost::pdb::read(text.as_bytes()).unwrap();
+    /// let residue = structure.models[0].residues().next().unwrap();
+    /// assert_eq!(Some('B'), residue.atom("OG").unwrap().alt_loc);
+    /// assert_eq!(vec!['A', 'B'], residue.alt_locs());
+    /// assert!(residue.atom("CB").is_none());
+    /// ```
+    pub fn atom(&self, name: &str) -> Option<&'a Atom> {
+        self.atoms()
+            .filter(|atom| atom.name == name)
+            .fold(None, |best: Option<&'a Atom>, atom| match best {
+                Some(best) if best.occupancy >= atom.occupancy => Some(best),
+                _ => Some(atom),
+            })
+    }
+
+    /// Returns the alternate location indicators used in the residue.
+    pub fn alt_locs(&self) -> Vec<char> {
+        let mut alt_locs: Vec<char> = self.atoms().filter_map(|atom| atom.alt_loc).collect();
+        alt_locs.sort();
+        alt_locs.dedup();
+        alt_locs
+    }
+}
+
+impl<'a> fmt::Debug for Residue<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Residue")
+            .field("name", &self.name())
+            .field("res_seq", &self.res_seq())
+            .field("i_code", &self.i_code())
+            .field("atoms", &self.range)
+            .finish()
+    }
+}
+
+impl<'a> iter::FusedIterator for Chains<'a> {}
+impl<'a> iter::FusedIterator for Residues<'a> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Vector3d;
+
+    fn atom(name: &str, chain_id: &str, res_name: &str, res_seq: i32) -> Atom {
+        let mut atom = Atom::new(name, Vector3d::zero());
+        atom.chain_id = chain_id.to_string();
+        atom.res_name = res_name.to_string();
+        atom.res_seq = res_seq;
+        atom
+    }
+
+    fn sample() -> Model {
+        let mut model = Model::new(1);
+        model.atoms.push(atom("N", "A", "GLY", 1));
+        model.atoms.push(atom("CA", "A", "GLY", 1));
+        model.atoms.push(atom("N", "A", "SER", 2));
+        let mut inserted = atom("N", "A", "ALA", 2);
+        inserted.i_code = Some('A');
+        model.atoms.push(inserted);
+        model.atoms.push(atom("N", "B", "GLY", 1));
+        model.atoms.push(atom("CA", "B", "GLY", 1));
+        model.chain_ends.push(6);
+        model.atoms.push(atom("O", "B", "HOH", 101));
+        model.atoms.push(atom("O", "B", "HOH", 102));
+        model
+    }
+
+    #[test]
+    fn test_chains() {
+        let model = sample();
+        let chains: Vec<(&str, Range<usize>)> = model
+            .chains()
+            .map(|chain| (chain.id(), chain.atom_indices()))
+            .collect();
+        assert_eq!(vec![("A", 0..4), ("B", 4..6), ("B", 6..8)], chains);
+        assert_eq!(4..6, model.chain("B").unwrap().atom_indices());
+        assert!(model.chain("C").is_none());
+        assert_eq!(0, Model::new(1).chains().count());
+    }
+
+    #[test]
+    fn test_residues() {
+        let model = sample();
+        let residues: Vec<(&str, i32, Option<char>)> = model
+            .residues()
+            .map(|residue| (residue.name(), residue.res_seq(), residue.i_code()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("GLY", 1, None),
+                ("SER", 2, None),
+                ("ALA", 2, Some('A')),
+                ("GLY", 1, None),
+                ("HOH", 101, None),
+                ("HOH", 102, None),
+            ],
+            residues
+        );
+
+        let chain = model.chain("A").unwrap();
+        let residue = chain.residue(2, Some('A')).unwrap();
+        assert_eq!("ALA", residue.name());
+        assert_eq!(3..4, residue.atom_indices());
+        assert_eq!("A", residue.chain().id());
+        assert_eq!(1, residue.model().serial);
+
+        let names: Vec<&str> = chain
+            .residue(1, None)
+            .unwrap()
+            .atoms()
+            .map(|atom| atom.name.as_str())
+            .collect();
+        assert_eq!(vec!["N", "CA"], names);
+        assert_eq!("HOH", model.residue("B", 102, None).unwrap().name());
+    }
+
+    #[test]
+    fn test_residue_of() {
+        let model = sample();
+        assert_eq!("SER", model.residue_of(2).unwrap().name());
+        assert_eq!(101, model.residue_of(6).unwrap().res_seq());
+        assert!(model.residue_of(8).is_none());
+    }
+
+    #[test]
+    fn test_structure_iterators() {
+        let mut model2 = sample();
+        model2.serial = 2;
+        let structure = Structure {
+            models: vec![sample(), model2],
+            bonds: vec![],
+        };
+        assert_eq!(16, structure.atoms().count());
+        assert_eq!(12, structure.residues().count());
+        assert_eq!(6, structure.chains().count());
+        assert_eq!(2, structure.model(2).unwrap().serial);
+        assert!(structure.model(3).is_none());
+    }
+
+    #[test]
+    fn test_alt_locs() {
+        let mut model = Model::new(1);
+        model.atoms.push(atom("CA", "A", "SER", 1));
+        for &(alt_loc, occupancy) in [('A', 0.5), ('B', 0.5)].iter() {
+            let mut og = atom("OG", "A", "SER", 1);
+            og.alt_loc = Some(alt_loc);
+            og.occupancy = occupancy;
+            model.atoms.push(og);
+        }
+        model.chain_ends.push(3);
+        model.atoms.push(atom("O", "A", "HOH", 101));
+
+        {
+            let residue = model.residues().next().unwrap();
+            assert_eq!(vec!['A', 'B'], residue.alt_locs());
+            assert_eq!(Some('A'), residue.atom("OG").unwrap().alt_loc);
+        }
+
+        model.retain_alt_loc('B');
+        assert_eq!(3, model.atoms.len());
+        assert_eq!(Some('B'), model.atoms[1].alt_loc);
+        assert_eq!(vec![2], model.chain_ends);
+        assert_eq!(2, model.chains().count());
+    }
+
+    #[test]
+    fn test_transform() {
+        let mut structure = Structure {
+            models: vec![sample(), sample()],
+            bonds: vec![],
+        };
+        let translation = Vector3d::new(1.0, -2.0, 3.0);
+        structure.transform(&Transform::from_translation(translation));
+        for (atom, original) in structure.atoms().zip(sample().atoms.iter().cycle()) {
+            assert_eq!(original.position + translation, atom.position);
+        }
+    }
+}
@@ -0,0 +1,507 @@
+//! Optimal superposition of one set of coordinates onto another.
+
+use std::cmp::Ordering;
+
+use float::Float;
+use inertia::centroid;
+use matrix::Matrix3;
+use quaternion::Quaternion;
+use rotation::Rotation;
+use transform::Transform;
+use Vector3d;
+
+/// Returns the rigid body transformation minimizing the (weighted) RMSD of
+/// `mobile` from `reference` by the Kabsch algorithm.
+///
+/// The i-th position of `mobile` corresponds to the i-th of `reference`.
+/// If `weights` are given, the i-th pair contributes to the deviation in
+/// proportion to the i-th weight, e.g. the atomic mass. A proper rotation
+/// is always returned, even when the best fit would be a reflection.
+///
+/// Returns `None` if the slices differ in length, are empty, or the weights
+/// do not sum up to a positive value.
+///
+/// # Example
+///
+/// ```
+/// use biost::{EulerConvention, Rotation, Transform, Vector3d};
+/// use biost::superposition::kabsch;
+/// let reference = vec![
+///     Vector3d::new(0.0, 0.0, 0.0),
+///     Vector3d::new(1.5, 0.0, 0.0),
+///     Vector3d::new(1.5, 1.5, 0.0),
+///     Vector3d::new(2.0, 1.5, 1.0),
+/// ];
+/// let moved = Transform::new(
+///     Rotation::from_euler(EulerConvention::ZYZ, 0.3, 1.2, -0.5),
+///     Vector3d::new(10.0, -2.0, 5.0),
+/// );
+/// let mut mobile = reference.clone();
+/// moved.apply_all(&mut mobile);
+///
+/// let transform = kabsch(&mobile, &reference, None).unwrap();
+/// transform.apply_all(&mut mobile);
+/// for (a, b) in mobile.iter().zip(reference.iter()) {
+///     assert!((*a - *b).norm() < 1e-4);
+/// }
+/// ```
+pub fn kabsch<T: Float>(
+    mobile: &[Vector3d<T>],
+    reference: &[Vector3d<T>],
+    weights: Option<&[T]>,
+) -> Option<Transform<T>> {
+    if mobile.len() != reference.len() || weights.is_some_and(|w| w.len() != mobile.len()) {
+        return None;
+    }
+    let weight = |i: usize| weights.map_or(T::one(), |w| w[i]);
+    let mobile_center = centroid(mobile, weights)?;
+    let reference_center = centroid(reference, weights)?;
+
+    let mut covariance = Matrix3::zero();
+    for (i, (p, q)) in mobile.iter().zip(reference.iter()).enumerate() {
+        covariance = covariance
+            + Matrix3::outer(&(*p - mobile_center), &(*q - reference_center)) * weight(i);
+    }
+    let (u, _, v) = covariance.svd();
+    // Flip the axis of the smallest singular value to avoid a reflection.
+    let sign = if (v * u.transpose()).determinant() < T::zero() {
+        -T::one()
+    } else {
+        T::one()
+    };
+    let matrix = v * Matrix3::diagonal(T::one(), T::one(), sign) * u.transpose();
+    let rotation = Rotation::from_matrix_unchecked(matrix);
+    let translation = reference_center - rotation.rotate(&mobile_center);
+    Some(Transform::new(rotation, translation))
+}
+
+/// The maximum number of Newton-Raphson iterations of QCP.
+const MAX_QCP_ITERATIONS: usize = 50;
+
+/// Returns the (weighted) RMSD of `mobile` from `reference` after optimal
+/// superposition by Theobald's quaternion-based characteristic polynomial
+/// (QCP) method.
+///
+/// This gives the same value as `fitted_rmsd` without superposing the
+/// coordinates: it only finds the largest root of a quartic polynomial by
+/// the Newton-Raphson method. `examples/superposition.rs` compares the
+/// speed of both.
+/// The arguments and `None` cases are the same as for `kabsch`.
+///
+/// # Example
+///
+/// ```
+/// use biost::superposition::qcp_rmsd;
+/// use biost::{fitted_rmsd, Vector3d};
+/// let reference: [Vector3d; 4] = [
+///     Vector3d::new(0.0, 0.0, 0.0),
+///     Vector3d::new(1.5, 0.0, 0.0),
+///     Vector3d::new(1.5, 1.5, 0.0),
+///     Vector3d::new(2.0, 1.5, 1.0),
+/// ];
+/// let mobile = [
+///     Vector3d::new(5.0, 0.2, 0.0),
+///     Vector3d::new(5.1, -1.5, 0.0),
+///     Vector3d::new(6.5, -1.4, 0.1),
+///     Vector3d::new(6.4, -2.0, 1.0),
+/// ];
+/// let rmsd = qcp_rmsd(&mobile, &reference, None).unwrap();
+/// assert!((rmsd - fitted_rmsd(&mobile, &reference, None).unwrap()).abs() < 1e-4);
+/// ```
+pub fn qcp_rmsd<T: Float>(
+    mobile: &[Vector3d<T>],
+    reference: &[Vector3d<T>],
+    weights: Option<&[T]>,
+) -> Option<T> {
+    Qcp::new(mobile, reference, weights).map(|qcp| qcp.rmsd())
+}
+
+/// Returns the RMSD of `mobile` from `reference` after optimal
+/// superposition together with the transformation superposing `mobile`
+/// onto `reference`, by the QCP method.
+///
+/// The rotation is obtained from the eigenvector of the largest eigenvalue.
+/// If it is ill-determined, the rotation is computed by `kabsch` instead.
+///
+/// # Example
+///
+/// ```
+/// use biost::superposition::qcp;
+/// use biost::{Rotation, Vector3d};
+/// let reference = [
+///     Vector3d::new(0.0, 0.0, 0.0),
+///     Vector3d::new(1.5, 0.0, 0.0),
+///     Vector3d::new(1.5, 1.5, 0.0),
+/// ];
+/// let rotation = Rotation::from_axis_angle(&Vector3d::new(1.0, 2.0, 3.0), 2.0).unwrap();
+/// let mobile: Vec<Vector3d> = reference.iter().map(|v| rotation.rotate(v)).collect();
+/// let (rmsd, transform) = qcp(&mobile, &reference, None).unwrap();
+/// assert!(rmsd < 1e-3);
+/// assert!((transform.apply(&mobile[2]) - reference[2]).norm() < 1e-4);
+/// ```
+pub fn qcp<T: Float>(
+    mobile: &[Vector3d<T>],
+    reference: &[Vector3d<T>],
+    weights: Option<&[T]>,
+) -> Option<(T, Transform<T>)> {
+    let qcp = Qcp::new(mobile, reference, weights)?;
+    let rotation = match qcp.rotation() {
+        Some(rotation) => rotation,
+        None => kabsch(mobile, reference, weights)?.rotation,
+    };
+    let translation = qcp.reference_center - rotation.rotate(&qcp.mobile_center);
+    Some((qcp.rmsd(), Transform::new(rotation, translation)))
+}
+
+/// The largest eigenvalue of the key matrix of QCP.
+struct Qcp<T: Float> {
+    key: [[T; 4]; 4],
+    eigenvalue: T,
+    /// The half of the weighted sum of the squared norms of the centered
+    /// positions, which is an upper bound of the eigenvalue.
+    inner_product: T,
+    total_weight: T,
+    mobile_center: Vector3d<T>,
+    reference_center: Vector3d<T>,
+}
+
+impl<T: Float> Qcp<T> {
+    fn new(
+        mobile: &[Vector3d<T>],
+        reference: &[Vector3d<T>],
+        weights: Option<&[T]>,
+    ) -> Option<Self> {
+        if mobile.len() != reference.len() || weights.is_some_and(|w| w.len() != mobile.len()) {
+            return None;
+        }
+        // The sums are accumulated in a single pass, as in the reference
+        // implementation of QCP, relative to the first pair of positions
+        // so that they do not lose precision far from the origin, and are
+        // then centered.
+        let (&p0, &q0) = (mobile.first()?, reference.first()?);
+        let mut total_weight = T::zero();
+        let mut mobile_sum = Vector3d::zero();
+        let mut reference_sum = Vector3d::zero();
+        let mut inner_product = T::zero();
+        let [mut sxx, mut sxy, mut sxz] = [T::zero(); 3];
+        let [mut syx, mut syy, mut syz] = [T::zero(); 3];
+        let [mut szx, mut szy, mut szz] = [T::zero(); 3];
+        for (i, (p, q)) in mobile.iter().zip(reference.iter()).enumerate() {
+            let weight = weights.map_or(T::one(), |w| w[i]);
+            let a = *p - p0;
+            let b = *q - q0;
+            let wa = a * weight;
+            total_weight += weight;
+            mobile_sum += wa;
+            reference_sum += b * weight;
+            inner_product += wa.dot(&a) + b.norm_squared() * weight;
+            sxx += wa.x * b.x;
+            sxy += wa.x * b.y;
+            sxz += wa.x * b.z;
+            syx += wa.y * b.x;
+            syy += wa.y * b.y;
+            syz += wa.y * b.z;
+            szx += wa.z * b.x;
+            szy += wa.z * b.y;
+            szz += wa.z * b.z;
+        }
+        if total_weight.partial_cmp(&T::zero()) != Some(Ordering::Greater) {
+            return None;
+        }
+        let a = mobile_sum / total_weight;
+        let b = reference_sum / total_weight;
+        let mobile_center = p0 + a;
+        let reference_center = q0 + b;
+        let s = Matrix3::new([
+            [
+                sxx - mobile_sum.x * b.x,
+                sxy - mobile_sum.x * b.y,
+                sxz - mobile_sum.x * b.z,
+            ],
+            [
+                syx - mobile_sum.y * b.x,
+                syy - mobile_sum.y * b.y,
+                syz - mobile_sum.y * b.z,
+            ],
+            [
+                szx - mobile_sum.z * b.x,
+                szy - mobile_sum.z * b.y,
+                szz - mobile_sum.z * b.z,
+            ],
+        ]);
+        let inner_product =
+            (inner_product - mobile_sum.dot(&a) - reference_sum.dot(&b)) / T::from_f32(2.0);
+
+        let (sxx, sxy, sxz) = (s[(0, 0)], s[(0, 1)], s[(0, 2)]);
+        let (syx, syy, syz) = (s[(1, 0)], s[(1, 1)], s[(1, 2)]);
+        let (szx, szy, szz) = (s[(2, 0)], s[(2, 1)], s[(2, 2)]);
+        let key = [
+            [sxx + syy + szz, syz - szy, szx - sxz, sxy - syx],
+            [syz - szy, sxx - syy - szz, sxy + syx, szx + sxz],
+            [szx - sxz, sxy + syx, -sxx + syy - szz, syz + szy],
+            [sxy - syx, szx + sxz, syz + szy, -sxx - syy + szz],
+        ];
+
+        // The characteristic polynomial of the key matrix is
+        // x^4 + c2 x^2 + c1 x + c0.
+        let mut c2 = T::zero();
+        for i in 0..3 {
+            c2 += s.row(i).norm_squared();
+        }
+        let c2 = -T::from_f32(2.0) * c2;
+        let c1 = -T::from_f32(8.0) * s.determinant();
+        let c0 = determinant4(&key);
+
+        let mut eigenvalue = inner_product;
+        for _ in 0..MAX_QCP_ITERATIONS {
+            let previous = eigenvalue;
+            let square = eigenvalue * eigenvalue;
+            let value = (square + c2) * square + c1 * eigenvalue + c0;
+            let derivative =
+                T::from_f32(4.0) * square * eigenvalue + T::from_f32(2.0) * c2 * eigenvalue + c1;
+            if derivative == T::zero() {
+                break;
+            }
+            eigenvalue -= value / derivative;
+            if (eigenvalue - previous).abs() <= T::epsilon() * eigenvalue.abs() {
+                break;
+            }
+        }
+
+        Some(Qcp {
+            key,
+            eigenvalue,
+            inner_product,
+            total_weight,
+            mobile_center,
+            reference_center,
+        })
+    }
+
+    fn rmsd(&self) -> T {
+        let deviation = T::from_f32(2.0) * (self.inner_product - self.eigenvalue);
+        (deviation / self.total_weight).max(T::zero()).sqrt()
+    }
+
+    /// Returns the rotation of the eigenvector, which is taken from the
+    /// largest column of the adjugate of `key - eigenvalue * I`, or `None`
+    /// if all the columns are too small.
+    fn rotation(&self) -> Option<Rotation<T>> {
+        let mut matrix = self.key;
+        for (i, row) in matrix.iter_mut().enumerate() {
+            row[i] -= self.eigenvalue;
+        }
+        let scale: T = matrix
+            .iter()
+            .flat_map(|row| row.iter())
+            .map(|element| element.abs())
+            .fold(T::zero(), T::max);
+        let mut best: Option<(T, Quaternion<T>)> = None;
+        for j in 0..4 {
+            let mut column = [T::zero(); 4];
+            for (i, element) in column.iter_mut().enumerate() {
+                let cofactor = minor4(&matrix, j, i);
+                *element = if (i + j) % 2 == 0 {
+                    cofactor
+                } else {
+                    -cofactor
+                };
+            }
+            let quaternion = Quaternion::new(column[0], column[1], column[2], column[3]);
+            let norm_squared = quaternion.norm_squared();
+            if best.is_none_or(|(best_norm, _)| norm_squared > best_norm) {
+                best = Some((norm_squared, quaternion));
+            }
+        }
+        let (norm_squared, quaternion) = best?;
+        // The cofactors are cubic in the elements, so compare the norm with
+        // the sixth power of the scale.
+        let cube = scale * scale * scale;
+        let threshold = T::epsilon() * T::from_f32(1e3) * cube * cube;
+        if norm_squared > threshold && norm_squared.is_finite() {
+            quaternion.to_rotation()
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the determinant of the 3x3 submatrix of a 4x4 matrix without
+/// given row and column.
+fn minor4<T: Float>(matrix: &[[T; 4]; 4], row: usize, column: usize) -> T {
+    let mut elements = [[T::zero(); 3]; 3];
+    for (i, source) in (0..4).filter(|&i| i != row).enumerate() {
+        for (j, k) in (0..4).filter(|&k| k != column).enumerate() {
+            elements[i][j] = matrix[source][k];
+        }
+    }
+    Matrix3::new(elements).determinant()
+}
+
+fn determinant4<T: Float>(matrix: &[[T; 4]; 4]) -> T {
+    let mut determinant = T::zero();
+    for j in 0..4 {
+        let term = matrix[0][j] * minor4(matrix, 0, j);
+        if j % 2 == 0 {
+            determinant += term;
+        } else {
+            determinant -= term;
+        }
+    }
+    determinant
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rotation::EulerConvention;
+
+    fn sample() -> Vec<Vector3d<f64>> {
+        vec![
+            Vector3d::new(-1.2, 0.3, 2.0),
+            Vector3d::new(0.5, 1.1, -0.7),
+            Vector3d::new(2.2, -0.4, 0.1),
+            Vector3d::new(0.0, 2.5, 1.4),
+            Vector3d::new(-0.8, -1.9, -1.0),
+        ]
+    }
+
+    fn moved() -> Transform<f64> {
+        Transform::new(
+            Rotation::from_euler(EulerConvention::XYZ, 2.5, -0.4, 1.9),
+            Vector3d::new(-3.0, 4.0, 0.5),
+        )
+    }
+
+    #[test]
+    fn test_kabsch() {
+        let reference = sample();
+        let mut mobile = reference.clone();
+        moved().apply_all(&mut mobile);
+
+        let transform = kabsch(&mobile, &reference, None).unwrap();
+        let expected = moved().inverse();
+        assert!((transform.translation - expected.translation).norm() < 1e-9);
+        for i in 0..3 {
+            let row = transform.rotation.matrix().row(i) - expected.rotation.matrix().row(i);
+            assert!(row.norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_weights() {
+        let reference = sample();
+        let mut mobile = reference.clone();
+        moved().apply_all(&mut mobile);
+        // Perturb the last position, which is ignored by a zero weight.
+        mobile[4] += Vector3d::new(0.5, -1.0, 2.0);
+
+        let weights = [1.0, 2.0, 0.5, 1.0, 0.0];
+        let transform = kabsch(&mobile, &reference, Some(&weights)).unwrap();
+        for (p, q) in mobile.iter().zip(reference.iter()).take(4) {
+            assert!((transform.apply(p) - *q).norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_reflection() {
+        let reference = sample();
+        let mirrored: Vec<_> = reference
+            .iter()
+            .map(|v| Vector3d::new(-v.x, v.y, v.z))
+            .collect();
+        let transform = kabsch(&mirrored, &reference, None).unwrap();
+        assert!((transform.rotation.matrix().determinant() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_degenerate() {
+        let reference: Vec<Vector3d<f64>> =
+            (0..4).map(|i| Vector3d::new(i as f64, 0.0, 0.0)).collect();
+        let mut mobile = reference.clone();
+        moved().apply_all(&mut mobile);
+        let transform = kabsch(&mobile, &reference, None).unwrap();
+        for (p, q) in mobile.iter().zip(reference.iter()) {
+            assert!((transform.apply(p) - *q).norm() < 1e-9);
+        }
+
+        let single = [Vector3d::new(1.0, 2.0, 3.0)];
+        let transform = kabsch(&single, &[Vector3d::zero()], None).unwrap();
+        assert!(transform.apply(&single[0]).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_qcp() {
+        let reference = sample();
+        let mut mobile = reference.clone();
+        moved().apply_all(&mut mobile);
+        mobile[0].x += 0.3;
+        mobile[3].y -= 0.5;
+
+        let weights = [1.0, 2.0, 0.5, 1.0, 3.0];
+        for &weights in [None, Some(&weights[..])].iter() {
+            let (rmsd, transform) = qcp(&mobile, &reference, weights).unwrap();
+            let expected = kabsch(&mobile, &reference, weights).unwrap();
+            assert!((transform.translation - expected.translation).norm() < 1e-9);
+            for i in 0..3 {
+                let row = transform.rotation.matrix().row(i) - expected.rotation.matrix().row(i);
+                assert!(row.norm() < 1e-9);
+            }
+
+            let fitted: Vec<_> = mobile.iter().map(|v| expected.apply(v)).collect();
+            let total: f64 = weights.map_or(5.0, |w| w.iter().sum());
+            let sum: f64 = fitted
+                .iter()
+                .zip(reference.iter())
+                .enumerate()
+                .map(|(i, (p, q))| p.distance_squared(q) * weights.map_or(1.0, |w| w[i]))
+                .sum();
+            let expected_rmsd = (sum / total).sqrt();
+            assert!((rmsd - expected_rmsd).abs() < 1e-9);
+            assert_eq!(Some(rmsd), qcp_rmsd(&mobile, &reference, weights));
+        }
+
+        // The sums are accumulated without losing precision far from the
+        // origin.
+        let far = Vector3d::new(1e6, -2e6, 3e6);
+        let shifted: Vec<_> = mobile.iter().map(|v| *v + far).collect();
+        let expected = qcp_rmsd(&mobile, &reference, None).unwrap();
+        let rmsd = qcp_rmsd(&shifted, &reference, None).unwrap();
+        assert!((rmsd - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_qcp_degenerate() {
+        // Identical positions give an ill-determined eigenvector.
+        let reference = sample();
+        let (rmsd, transform) = qcp(&reference, &reference, None).unwrap();
+        assert!(rmsd < 1e-6);
+        for p in reference.iter() {
+            assert!((transform.apply(p) - *p).norm() < 1e-9);
+        }
+
+        let reference: Vec<Vector3d<f64>> =
+            (0..4).map(|i| Vector3d::new(i as f64, 0.0, 0.0)).collect();
+        let mut mobile = reference.clone();
+        moved().apply_all(&mut mobile);
+        let (rmsd, transform) = qcp(&mobile, &reference, None).unwrap();
+        assert!(rmsd < 1e-6);
+        for (p, q) in mobile.iter().zip(reference.iter()) {
+            assert!((transform.apply(p) - *q).norm() < 1e-9);
+        }
+
+        assert!(qcp_rmsd(&mobile, &reference[1..], None).is_none());
+        assert!(qcp_rmsd::<f64>(&[], &[], None).is_none());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let positions = sample();
+        assert!(kabsch(&positions, &positions[1..], None).is_none());
+        assert!(kabsch::<f64>(&[], &[], None).is_none());
+        assert!(kabsch(&positions, &positions, Some(&[0.0; 5])).is_none());
+        assert!(kabsch(&positions, &positions, Some(&[1.0; 4])).is_none());
+        assert!(qcp(&positions, &positions, Some(&[0.0; 5])).is_none());
+        assert!(qcp(&positions, &positions, Some(&[1.0; 4])).is_none());
+    }
+}
@@ -0,0 +1,344 @@
+//! Torsion angles of protein backbones and side chains.
+
+use float::Float;
+use structure::{Chain, Residue};
+use Vector3d;
+
+/// The maximum length in Angstroms of a peptide bond between the C atom of
+/// a residue and the N atom of the next; longer ones are chain breaks.
+const MAX_PEPTIDE_BOND: f32 = 2.0;
+
+/// The atoms defining the side-chain torsion angles chi1 to chi4 of each
+/// amino acid.
+const CHI_ATOMS: [(&str, &[[&str; 4]]); 20] = [
+    (
+        "ARG",
+        &[
+            ["N", "CA", "CB", "CG"],
+            ["CA", "CB", "CG", "CD"],
+            ["CB", "CG", "CD", "NE"],
+            ["CG", "CD", "NE", "CZ"],
+        ],
+    ),
+    ("ASN", &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "OD1"]]),
+    ("ASP", &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "OD1"]]),
+    ("CYS", &[["N", "CA", "CB", "SG"]]),
+    (
+        "GLN",
+        &[
+            ["N", "CA", "CB", "CG"],
+            ["CA", "CB", "CG", "CD"],
+            ["CB", "CG", "CD", "OE1"],
+        ],
+    ),
+    (
+        "GLU",
+        &[
+            ["N", "CA", "CB", "CG"],
+            ["CA", "CB", "CG", "CD"],
+            ["CB", "CG", "CD", "OE1"],
+        ],
+    ),
+    ("HIS", &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "ND1"]]),
+    (
+        "ILE",
+        &[["N", "CA", "CB", "CG1"], ["CA", "CB", "CG1", "CD1"]],
+    ),
+    ("LEU", &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "CD1"]]),
+    (
+        "LYS",
+        &[
+            ["N", "CA", "CB", "CG"],
+            ["CA", "CB", "CG", "CD"],
+            ["CB", "CG", "CD", "CE"],
+            ["CG", "CD", "CE", "NZ"],
+        ],
+    ),
+    (
+        "MET",
+        &[
+            ["N", "CA", "CB", "CG"],
+            ["CA", "CB", "CG", "SD"],
+            ["CB", "CG", "SD", "CE"],
+        ],
+    ),
+    (
+        "MSE",
+        &[
+            ["N", "CA", "CB", "CG"],
+            ["CA", "CB", "CG", "SE"],
+            ["CB", "CG", "SE", "CE"],
+        ],
+    ),
+    ("PHE", &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "CD1"]]),
+    ("PRO", &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "CD"]]),
+    ("SER", &[["N", "CA", "CB", "OG"]]),
+    ("THR", &[["N", "CA", "CB", "OG1"]]),
+    ("TRP", &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "CD1"]]),
+    ("TYR", &[["N", "CA", "CB", "CG"], ["CA", "CB", "CG", "CD1"]]),
+    ("VAL", &[["N", "CA", "CB", "CG1"]]),
+    ("SEC", &[["N", "CA", "CB", "SE"]]),
+];
+
+/// Returns the torsion angle in radians, in `(-pi, pi]`, of four points
+/// about the axis from `b` to `c`.
+///
+/// The angle is positive if `d` is rotated clockwise from `a` when viewed
+/// from `b` toward `c`, following the IUPAC convention.
+///
+/// # Example
+///
+/// ```
+/// use biost::{dihedral, Vector3d};
+/// let a = Vector3d::new(1.0, 0.0, 0.0);
+/// let b = Vector3d::new(0.0, 0.0, 0.0);
+/// let c = Vector3d::new(0.0, 0.0, 1.0);
+/// let d = Vector3d::new(0.0, 1.0, 1.0);
+/// assert_eq!(std::f32::consts::FRAC_PI_2, dihedral(&a, &b, &c, &d));
+/// let e = Vector3d::new(0.0, -1.0, 1.0);
+/// assert_eq!(-std::f32::consts::FRAC_PI_2, dihedral(&a, &b, &c, &e));
+/// ```
+pub fn dihedral<T: Float>(a: &Vector3d<T>, b: &Vector3d<T>, c: &Vector3d<T>, d: &Vector3d<T>) -> T {
+    let b1 = *b - *a;
+    let b2 = *c - *b;
+    let b3 = *d - *c;
+    let n1 = b1.cross(&b2);
+    let n2 = b2.cross(&b3);
+    (b1.dot(&n2) * b2.norm()).atan2(n1.dot(&n2))
+}
+
+/// The torsion angles of a residue in radians. Each angle is `None` if an
+/// atom defining it is missing or the chain is broken.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Torsions {
+    /// The angle of C of the previous residue, N, CA and C.
+    pub phi: Option<f32>,
+    /// The angle of N, CA, C and N of the next residue.
+    pub psi: Option<f32>,
+    /// The angle of CA and C of the previous residue, N and CA, i.e. that of
+    /// the peptide bond preceding the residue.
+    pub omega: Option<f32>,
+    /// The side-chain angles chi1 to chi4, which are `None` also beyond
+    /// those of the amino acid.
+    pub chi: [Option<f32>; 4],
+}
+
+/// Returns the torsion angles of each residue of a chain.
+///
+/// Consecutive residues are regarded as bonded if the C atom of the former
+/// is within 2 Angstroms of the N atom of the latter.
+///
+/// # Example
+///
+/// ```
+/// let text = "\
+/// ATOM      1  N   ALA A   1      -0.500  -1.400   0.000  1.00  0.00           N
+/// ATOM      2  CA  ALA A   1       0.000   0.000   0.000  1.00  0.00           C
+/// ATOM      3  C   ALA A   1       1.500   0.000   0.000  1.00  0.00           C
+/// ATOM      4  N   ALA A   2       2.100   1.200   0.000  1.00  0.00           N
+/// ATOM      5  CA  ALA A   2       3.550   1.300   0.000  1.00  0.00           C
+/// ATOM      6  C   ALA A   2       4.100   2.700   0.000  1.00  0.00           C
+/// ";
+/// let structure = biost::pdb::read(text.as_bytes()).unwrap();
+/// let chain = structure.models[0].chains().next().unwrap();
+/// let torsions = biost::torsion::torsions(&chain);
+/// // All atoms are in a plane, so that the angles are 180 degrees.
+/// let degrees = |angle: Option<f32>| angle.unwrap().to_degrees().abs();
+/// assert_eq!(None, torsions[0].phi);
+/// assert!((degrees(torsions[0].psi) - 180.0).abs() < 1e-3);
+/// assert!((degrees(torsions[1].phi) - 180.0).abs() < 1e-3);
+/// assert!((degrees(torsions[1].omega) - 180.0).abs() < 1e-3);
+/// assert_eq!(None, torsions[1].psi);
+/// ```
+pub fn torsions(chain: &Chain) -> Vec<Torsions> {
+    let residues: Vec<Residue> = chain.residues().collect();
+    let position = |residue: &Residue, name: &str| residue.atom(name).map(|atom| atom.position);
+    let bonded =
+        |former: &Residue, latter: &Residue| match (position(former, "C"), position(latter, "N")) {
+            (Some(c), Some(n)) => c.distance(&n) <= MAX_PEPTIDE_BOND,
+            _ => false,
+        };
+    let angle = |atoms: [Option<Vector3d>; 4]| match atoms {
+        [Some(a), Some(b), Some(c), Some(d)] => Some(dihedral(&a, &b, &c, &d)),
+        _ => None,
+    };
+
+    (0..residues.len())
+        .map(|i| {
+            let residue = &residues[i];
+            let previous = residues[..i]
+                .last()
+                .filter(|previous| bonded(previous, residue));
+            let next = residues.get(i + 1).filter(|next| bonded(residue, next));
+            let (n, ca, c) = (
+                position(residue, "N"),
+                position(residue, "CA"),
+                position(residue, "C"),
+            );
+            let phi = previous.and_then(|previous| angle([position(previous, "C"), n, ca, c]));
+            let psi = next.and_then(|next| angle([n, ca, c, position(next, "N")]));
+            let omega = previous.and_then(|previous| {
+                angle([position(previous, "CA"), position(previous, "C"), n, ca])
+            });
+
+            let mut chi = [None; 4];
+            if let Some(&(_, definitions)) =
+                CHI_ATOMS.iter().find(|&&(name, _)| name == residue.name())
+            {
+                for (chi, names) in chi.iter_mut().zip(definitions.iter()) {
+                    *chi = angle([
+                        position(residue, names[0]),
+                        position(residue, names[1]),
+                        position(residue, names[2]),
+                        position(residue, names[3]),
+                    ]);
+                }
+            }
+            Torsions {
+                phi,
+                psi,
+                omega,
+                chi,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use atom::Atom;
+    use structure::Model;
+    use zmatrix::place;
+
+    fn atom(name: &str, res_name: &str, res_seq: i32, position: Vector3d) -> Atom {
+        let mut atom = Atom::new(name, position);
+        atom.res_name = res_name.to_string();
+        atom.res_seq = res_seq;
+        atom.chain_id = "A".to_string();
+        atom
+    }
+
+    /// Builds a peptide of SER residues with given backbone angles and
+    /// chi1 of 60 degrees.
+    fn peptide(phi: f32, psi: f32, omega: f32, len: i32) -> Model {
+        let mut model = Model::new(1);
+        let mut n = Vector3d::new(-0.7, -1.2, -0.5);
+        let mut ca = Vector3d::zero();
+        let mut c = place(
+            &Vector3d::new(0.0, -1.0, 1.0),
+            &n,
+            &ca,
+            1.52,
+            111.0f32.to_radians(),
+            phi.to_radians(),
+        );
+        for res_seq in 1..=len {
+            let cb = place(
+                &c,
+                &n,
+                &ca,
+                1.53,
+                110.5f32.to_radians(),
+                -122.5f32.to_radians(),
+            );
+            let og = place(
+                &n,
+                &ca,
+                &cb,
+                1.42,
+                111.0f32.to_radians(),
+                60.0f32.to_radians(),
+            );
+            for &(name, position) in [("N", n), ("CA", ca), ("C", c), ("CB", cb), ("OG", og)].iter()
+            {
+                model.atoms.push(atom(name, "SER", res_seq, position));
+            }
+            let next_n = place(&n, &ca, &c, 1.33, 116.2f32.to_radians(), psi.to_radians());
+            let next_ca = place(
+                &ca,
+                &c,
+                &next_n,
+                1.46,
+                121.7f32.to_radians(),
+                omega.to_radians(),
+            );
+            let next_c = place(
+                &c,
+                &next_n,
+                &next_ca,
+                1.52,
+                111.2f32.to_radians(),
+                phi.to_radians(),
+            );
+            n = next_n;
+            ca = next_ca;
+            c = next_c;
+        }
+        model
+    }
+
+    fn degrees(angle: Option<f32>) -> f32 {
+        angle.unwrap().to_degrees()
+    }
+
+    #[test]
+    fn test_dihedral() {
+        let a = Vector3d::new(1.0, 0.0, 0.0);
+        let b = Vector3d::zero();
+        let c = Vector3d::new(0.0, 0.0, 1.5);
+        for &angle in [-179.0f32, -120.0, -60.0, 0.0, 45.0, 135.0, 179.5].iter() {
+            let d = place(&a, &b, &c, 1.0, 109.5f32.to_radians(), angle.to_radians());
+            assert!((dihedral(&a, &b, &c, &d).to_degrees() - angle).abs() < 1e-3);
+        }
+        let x = Vector3d::new(1.0f64, 0.0, 0.0);
+        let y = Vector3d::new(0.0, 1.0, 0.0);
+        assert_eq!(
+            std::f64::consts::PI,
+            dihedral(&x, &Vector3d::zero(), &y, &-x)
+        );
+    }
+
+    #[test]
+    fn test_torsions() {
+        let model = peptide(-60.0, -45.0, 180.0, 3);
+        let chain = model.chains().next().unwrap();
+        let torsions = torsions(&chain);
+        assert_eq!(3, torsions.len());
+
+        assert_eq!(None, torsions[0].phi);
+        assert_eq!(None, torsions[0].omega);
+        assert!((degrees(torsions[0].psi) + 45.0).abs() < 1e-3);
+        for torsion in torsions[1..].iter() {
+            assert!((degrees(torsion.phi) + 60.0).abs() < 1e-3);
+            assert!((degrees(torsion.omega).abs() - 180.0).abs() < 1e-3);
+        }
+        assert_eq!(None, torsions[2].psi);
+        for torsion in torsions.iter() {
+            assert!((degrees(torsion.chi[0]) - 60.0).abs() < 1e-3);
+            assert_eq!([None; 3], torsion.chi[1..]);
+        }
+    }
+
+    #[test]
+    fn test_chain_break_and_missing_atoms() {
+        let mut model = peptide(-120.0, 130.0, 0.0, 3);
+        // Move the third residue away and remove OG of the first.
+        for atom in model.atoms.iter_mut().filter(|atom| atom.res_seq == 3) {
+            atom.position.x += 5.0;
+        }
+        model
+            .atoms
+            .retain(|atom| !(atom.res_seq == 1 && atom.name == "OG"));
+        let chain = model.chains().next().unwrap();
+        let torsions = torsions(&chain);
+
+        assert!(degrees(torsions[1].omega).abs() < 1e-3);
+        assert!((degrees(torsions[1].phi) + 120.0).abs() < 1e-3);
+        assert_eq!(None, torsions[1].psi);
+        assert_eq!(None, torsions[2].phi);
+        assert_eq!(None, torsions[2].omega);
+        assert_eq!(None, torsions[0].chi[0]);
+        assert!((degrees(torsions[1].chi[0]) - 60.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,143 @@
+use std::ops;
+
+use float::Float;
+use rotation::Rotation;
+use Vector3d;
+
+/// A rigid body transformation, which rotates a vector and then
+/// translates it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform<T: Float = f32> {
+    pub rotation: Rotation<T>,
+    pub translation: Vector3d<T>,
+}
+
+impl<T: Float> Transform<T> {
+    /// Returns a transformation with given rotation and translation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{Rotation, Transform, Vector3d};
+    /// let rotation = Rotation::from_axis_angle(&Vector3d::new(0.0, 0.0, 1.0), std::f32::consts::PI).unwrap();
+    /// let transform = Transform::new(rotation, Vector3d::new(1.0, 0.0, 0.0));
+    /// let moved = transform * Vector3d::new(1.0, 1.0, 0.0);
+    /// assert!((moved - Vector3d::new(0.0, -1.0, 0.0)).norm() < 1e-6);
+    /// ```
+    pub fn new(rotation: Rotation<T>, translation: Vector3d<T>) -> Self {
+        Transform {
+            rotation,
+            translation,
+        }
+    }
+
+    /// Returns the transformation which does nothing.
+    pub fn identity() -> Self {
+        Self::new(Rotation::identity(), Vector3d::zero())
+    }
+
+    /// Returns a pure translation.
+    pub fn from_translation(translation: Vector3d<T>) -> Self {
+        Self::new(Rotation::identity(), translation)
+    }
+
+    /// Returns a pure rotation about the origin.
+    pub fn from_rotation(rotation: Rotation<T>) -> Self {
+        Self::new(rotation, Vector3d::zero())
+    }
+
+    /// Returns the inverse transformation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::{EulerConvention, Rotation, Transform, Vector3d};
+    /// let rotation = Rotation::from_euler(EulerConvention::ZYZ, 0.1, 0.2, 0.3);
+    /// let transform = Transform::new(rotation, Vector3d::new(1.0, 2.0, 3.0));
+    /// let vector = Vector3d::new(-2.0, 0.5, 4.0);
+    /// let back = transform.inverse() * (transform * vector);
+    /// assert!((back - vector).norm() < 1e-6);
+    /// ```
+    pub fn inverse(&self) -> Self {
+        let rotation = self.rotation.inverse();
+        Self::new(rotation, -rotation.rotate(&self.translation))
+    }
+
+    /// Transforms a position.
+    pub fn apply(&self, vector: &Vector3d<T>) -> Vector3d<T> {
+        self.rotation.rotate(vector) + self.translation
+    }
+
+    /// Transforms all positions in place.
+    pub fn apply_all(&self, vectors: &mut [Vector3d<T>]) {
+        for vector in vectors.iter_mut() {
+            *vector = self.apply(vector);
+        }
+    }
+}
+
+impl<T: Float> ops::Mul for Transform<T> {
+    type Output = Self;
+
+    /// Composes two transformations; `a * b` applies `b` first, then `a`.
+    fn mul(self, transform: Self) -> Self::Output {
+        Self::new(
+            self.rotation * transform.rotation,
+            self.apply(&transform.translation),
+        )
+    }
+}
+
+impl<T: Float> ops::Mul<Vector3d<T>> for Transform<T> {
+    type Output = Vector3d<T>;
+    fn mul(self, vector: Vector3d<T>) -> Self::Output {
+        self.apply(&vector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rotation::EulerConvention;
+
+    #[test]
+    fn test_apply() {
+        let rotation = Rotation::from_euler(EulerConvention::ZYZ, 0.0, 0.0, 0.0);
+        let transform = Transform::new(rotation, Vector3d::new(1.0, 2.0, 3.0));
+        assert_eq!(
+            Vector3d::new(2.0, 2.0, 3.0),
+            transform * Vector3d::new(1.0, 0.0, 0.0)
+        );
+
+        let transform: Transform = Transform::identity();
+        let vector = Vector3d::new(1.0, 2.0, 3.0);
+        assert_eq!(vector, transform * vector);
+    }
+
+    #[test]
+    fn test_compose() {
+        let a = Transform::new(
+            Rotation::from_euler(EulerConvention::XYZ, 0.4, -0.2, 1.0),
+            Vector3d::new(1.0, -1.0, 2.0),
+        );
+        let b = Transform::new(
+            Rotation::from_euler(EulerConvention::ZXZ, -0.7, 0.3, 0.1),
+            Vector3d::new(-3.0, 0.5, 0.0),
+        );
+        let vector: Vector3d<f64> = Vector3d::new(0.3, 2.0, -1.5);
+        let expected = a * (b * vector);
+        assert!(((a * b) * vector - expected).norm() < 1e-12);
+
+        let identity = a * a.inverse();
+        assert!((identity * vector - vector).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_apply_all() {
+        let transform = Transform::from_translation(Vector3d::new(1.0, 1.0, 1.0));
+        let mut vectors = vec![Vector3d::zero(), Vector3d::new(1.0, 2.0, 3.0)];
+        transform.apply_all(&mut vectors);
+        assert_eq!(Vector3d::new(1.0, 1.0, 1.0), vectors[0]);
+        assert_eq!(Vector3d::new(2.0, 3.0, 4.0), vectors[1]);
+    }
+}
@@ -0,0 +1,399 @@
+//! Internal coordinates, i.e. a Z-matrix, and their conversion from and to
+//! Cartesian coordinates.
+//!
+//! Each atom is positioned by its distance to a bonded atom, the angle with
+//! a second atom and the torsion angle with a third, all of which precede
+//! it. Positions are reconstructed by the Natural Extension Reference Frame
+//! (NeRF) method. Angles are in radians.
+
+use float::Float;
+use torsion::dihedral;
+use Vector3d;
+
+/// Returns the position of `d` at given distance from `c`, angle `b-c-d`
+/// and torsion angle `a-b-c-d`.
+///
+/// The result is not finite if `a`, `b` and `c` are collinear.
+///
+/// # Example
+///
+/// ```
+/// use biost::zmatrix::place;
+/// use biost::{dihedral, Vector3d};
+/// let a = Vector3d::new(1.0, 0.0, 0.0);
+/// let b = Vector3d::new(0.0, 0.0, 0.0);
+/// let c = Vector3d::new(0.0, 0.0, 1.0);
+/// let d = place(&a, &b, &c, 1.0, std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2);
+/// assert!((d - Vector3d::new(0.0, 1.0, 1.0)).norm() < 1e-6);
+/// assert!((dihedral(&a, &b, &c, &d) - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+/// ```
+pub fn place<T: Float>(
+    a: &Vector3d<T>,
+    b: &Vector3d<T>,
+    c: &Vector3d<T>,
+    length: T,
+    angle: T,
+    torsion: T,
+) -> Vector3d<T> {
+    let bc = (*c - *b).normalize();
+    let n = (*b - *a).cross(&bc).normalize();
+    let m = n.cross(&bc);
+    let (sin, cos) = (angle.sin(), angle.cos());
+    *c + bc * (-length * cos)
+        + m * (length * sin * torsion.cos())
+        + n * (length * sin * torsion.sin())
+}
+
+/// The internal coordinates of an atom.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Entry<T: Float = f32> {
+    /// The indices of the bonded atom, the atom forming the angle and the
+    /// atom forming the torsion angle, in this order.
+    ///
+    /// The first atom has none of them, the second only the bonded atom
+    /// and the third no torsion atom; all others have all of them.
+    pub references: [Option<usize>; 3],
+    /// The distance to the bonded atom.
+    pub length: T,
+    /// The angle of the atom, the bonded atom and the angle atom.
+    pub angle: T,
+    /// The torsion angle of the atom, the bonded atom, the angle atom and
+    /// the torsion atom.
+    pub torsion: T,
+}
+
+/// A list of atoms positioned by internal coordinates.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ZMatrix<T: Float = f32> {
+    pub entries: Vec<Entry<T>>,
+}
+
+impl<T: Float> ZMatrix<T> {
+    /// Returns the internal coordinates of `positions` with given reference
+    /// atoms for each of them.
+    ///
+    /// Returns `None` if the numbers differ or the references are invalid,
+    /// i.e. they do not precede the atom, coincide, or do not match the
+    /// pattern described in `Entry::references`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::zmatrix::ZMatrix;
+    /// use biost::Vector3d;
+    /// let positions = [
+    ///     Vector3d::new(0.0, 0.0, 0.0),
+    ///     Vector3d::new(0.0, 0.0, 2.0),
+    ///     Vector3d::new(0.0, 1.0, 2.0),
+    /// ];
+    /// let references = [[None, None, None], [Some(0), None, None], [Some(1), Some(0), None]];
+    /// let zmatrix = ZMatrix::from_positions(&positions, &references).unwrap();
+    /// assert_eq!(2.0, zmatrix.entries[1].length);
+    /// assert_eq!(std::f32::consts::FRAC_PI_2, zmatrix.entries[2].angle);
+    /// ```
+    pub fn from_positions(
+        positions: &[Vector3d<T>],
+        references: &[[Option<usize>; 3]],
+    ) -> Option<Self> {
+        if positions.len() != references.len() {
+            return None;
+        }
+        let entries = references
+            .iter()
+            .enumerate()
+            .map(|(i, &references)| {
+                validate(i, &references)?;
+                let position = &positions[i];
+                let mut entry = Entry {
+                    references,
+                    length: T::zero(),
+                    angle: T::zero(),
+                    torsion: T::zero(),
+                };
+                if let Some(a) = references[0] {
+                    entry.length = position.distance(&positions[a]);
+                    if let Some(b) = references[1] {
+                        entry.angle =
+                            (*position - positions[a]).angle(&(positions[b] - positions[a]));
+                        if let Some(c) = references[2] {
+                            entry.torsion =
+                                dihedral(&positions[c], &positions[b], &positions[a], position);
+                        }
+                    }
+                }
+                Some(entry)
+            })
+            .collect::<Option<_>>()?;
+        Some(ZMatrix { entries })
+    }
+
+    /// Returns the internal coordinates of `positions` of atoms forming a
+    /// tree, in which each atom but the first is bonded to a preceding
+    /// parent.
+    ///
+    /// The angle and torsion atoms are the parent's ancestors where
+    /// possible and otherwise the first preceding atom bonded to one of the
+    /// references chosen so far. Returns `None` if the numbers differ, the
+    /// first atom has a parent, or another atom has none or one that does
+    /// not precede it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::zmatrix::ZMatrix;
+    /// use biost::Vector3d;
+    /// // N, CA, C and CB of an amino acid.
+    /// let positions = [
+    ///     Vector3d::new(-0.5, -1.4, 0.0),
+    ///     Vector3d::new(0.0, 0.0, 0.0),
+    ///     Vector3d::new(1.5, 0.0, 0.0),
+    ///     Vector3d::new(-0.5, 0.8, 1.2),
+    /// ];
+    /// let parents = [None, Some(0), Some(1), Some(1)];
+    /// let zmatrix = ZMatrix::from_tree(&positions, &parents).unwrap();
+    /// assert_eq!([Some(1), Some(0), Some(2)], zmatrix.entries[3].references);
+    /// let rebuilt: Vec<Vector3d> = zmatrix.to_positions().unwrap();
+    /// assert!((rebuilt[3].distance(&rebuilt[2]) - positions[3].distance(&positions[2])).abs() < 1e-5);
+    /// ```
+    pub fn from_tree(positions: &[Vector3d<T>], parents: &[Option<usize>]) -> Option<Self> {
+        if positions.len() != parents.len() {
+            return None;
+        }
+        let mut references = Vec::with_capacity(parents.len());
+        for (i, &parent) in parents.iter().enumerate() {
+            let mut chosen = [None; 3];
+            match parent {
+                None if i == 0 => {}
+                Some(parent) if parent < i => {
+                    chosen[0] = Some(parent);
+                    for k in 1..i.min(3) {
+                        let ancestor = chosen[k - 1]
+                            .and_then(|previous: usize| parents[previous])
+                            .filter(|ancestor| !chosen[..k].contains(&Some(*ancestor)));
+                        chosen[k] = ancestor.or_else(|| {
+                            (0..i).find(|&j| {
+                                !chosen[..k].contains(&Some(j))
+                                    && chosen[..k].iter().flatten().any(|&reference| {
+                                        parents[j] == Some(reference)
+                                            || parents[reference] == Some(j)
+                                    })
+                            })
+                        });
+                    }
+                }
+                _ => return None,
+            }
+            references.push(chosen);
+        }
+        Self::from_positions(positions, &references)
+    }
+
+    /// Reconstructs the Cartesian positions.
+    ///
+    /// The first atom is placed at the origin, the second on the x axis
+    /// and the third in the xy plane. Returns `None` if the references are
+    /// invalid as described in `from_positions`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use biost::zmatrix::{Entry, ZMatrix};
+    /// let entry = |references, length, angle: f32, torsion: f32| Entry {
+    ///     references,
+    ///     length,
+    ///     angle: angle.to_radians(),
+    ///     torsion: torsion.to_radians(),
+    /// };
+    /// // A trans (anti) conformation of butane carbons.
+    /// let zmatrix = ZMatrix {
+    ///     entries: vec![
+    ///         entry([None, None, None], 0.0, 0.0, 0.0),
+    ///         entry([Some(0), None, None], 1.53, 0.0, 0.0),
+    ///         entry([Some(1), Some(0), None], 1.53, 109.5, 0.0),
+    ///         entry([Some(2), Some(1), Some(0)], 1.53, 109.5, 180.0),
+    ///     ],
+    /// };
+    /// let positions = zmatrix.to_positions().unwrap();
+    /// assert!((positions[0].distance(&positions[3]) - 3.85).abs() < 1e-2);
+    /// ```
+    pub fn to_positions(&self) -> Option<Vec<Vector3d<T>>> {
+        let mut positions: Vec<Vector3d<T>> = Vec::with_capacity(self.entries.len());
+        for (i, entry) in self.entries.iter().enumerate() {
+            validate(i, &entry.references)?;
+            let position = match entry.references {
+                [None, None, None] => Vector3d::zero(),
+                [Some(a), None, None] => {
+                    positions[a] + Vector3d::new(entry.length, T::zero(), T::zero())
+                }
+                [Some(a), Some(b), None] => {
+                    let u = (positions[b] - positions[a])
+                        .try_normalize()
+                        .unwrap_or_else(|| Vector3d::new(T::one(), T::zero(), T::zero()));
+                    let z = Vector3d::new(T::zero(), T::zero(), T::one());
+                    let v = z
+                        .cross(&u)
+                        .try_normalize()
+                        .unwrap_or_else(|| Vector3d::new(T::zero(), T::one(), T::zero()));
+                    positions[a] + (u * entry.angle.cos() + v * entry.angle.sin()) * entry.length
+                }
+                [Some(a), Some(b), Some(c)] => place(
+                    &positions[c],
+                    &positions[b],
+                    &positions[a],
+                    entry.length,
+                    entry.angle,
+                    entry.torsion,
+                ),
+                _ => return None,
+            };
+            positions.push(position);
+        }
+        Some(positions)
+    }
+}
+
+/// Checks that the references of the `index`-th atom precede it, are
+/// distinct and are as many as required.
+fn validate(index: usize, references: &[Option<usize>; 3]) -> Option<()> {
+    let count = index.min(3);
+    let valid = references
+        .iter()
+        .enumerate()
+        .all(|(k, reference)| match reference {
+            Some(reference) => {
+                k < count && *reference < index && !references[..k].contains(&Some(*reference))
+            }
+            None => k >= count,
+        });
+    if valid {
+        Some(())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    fn sample() -> Vec<Vector3d<f64>> {
+        vec![
+            Vector3d::new(1.2, -0.3, 0.5),
+            Vector3d::new(2.1, 0.8, 0.2),
+            Vector3d::new(3.5, 0.4, -0.6),
+            Vector3d::new(3.9, 1.7, -1.4),
+            Vector3d::new(4.2, -0.9, -0.1),
+            Vector3d::new(5.3, 2.2, -0.8),
+        ]
+    }
+
+    #[test]
+    fn test_place() {
+        let a = Vector3d::new(0.3, -1.0, 0.2);
+        let b = Vector3d::new(0.0, 0.0, 0.0);
+        let c = Vector3d::new(0.1, 0.2, 1.5);
+        for &torsion in [-170.0f64, -60.0, 0.0, 75.0, 180.0].iter() {
+            let d = place(
+                &a,
+                &b,
+                &c,
+                1.33,
+                116.0f64.to_radians(),
+                torsion.to_radians(),
+            );
+            assert!((d.distance(&c) - 1.33).abs() < 1e-9);
+            assert!(((d - c).angle(&(b - c)).to_degrees() - 116.0).abs() < 1e-9);
+            let angle = dihedral(&a, &b, &c, &d).to_degrees();
+            assert!(((angle - torsion + 180.0).rem_euclid(360.0) - 180.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let positions = sample();
+        let parents = [None, Some(0), Some(1), Some(2), Some(2), Some(3)];
+        let zmatrix = ZMatrix::from_tree(&positions, &parents).unwrap();
+        assert_eq!([Some(2), Some(1), Some(0)], zmatrix.entries[4].references);
+        assert_eq!([Some(3), Some(2), Some(1)], zmatrix.entries[5].references);
+
+        // The reconstruction is the original up to a rigid motion, which
+        // preserves all the distances.
+        let rebuilt = zmatrix.to_positions().unwrap();
+        for i in 0..positions.len() {
+            for j in 0..i {
+                let expected = positions[i].distance(&positions[j]);
+                assert!((rebuilt[i].distance(&rebuilt[j]) - expected).abs() < 1e-9);
+            }
+        }
+        assert_eq!(Vector3d::zero(), rebuilt[0]);
+        assert_eq!(0.0, rebuilt[1].y);
+        assert_eq!(0.0, rebuilt[2].z);
+        let again = ZMatrix::from_tree(&rebuilt, &parents).unwrap();
+        for (a, b) in zmatrix.entries.iter().zip(again.entries.iter()) {
+            assert!((a.length - b.length).abs() < 1e-9);
+            assert!((a.angle - b.angle).abs() < 1e-9);
+            assert!((a.torsion - b.torsion).abs() < 1e-9);
+        }
+
+        // A branch at the root, such as a carbon with three substituents,
+        // takes its references from the siblings.
+        let parents = [None, Some(0), Some(0), Some(0), Some(1), Some(2)];
+        let zmatrix = ZMatrix::from_tree(&positions, &parents).unwrap();
+        assert_eq!([Some(0), Some(1), Some(2)], zmatrix.entries[3].references);
+        let rebuilt = zmatrix.to_positions().unwrap();
+        for i in 0..positions.len() {
+            for j in 0..i {
+                let expected = positions[i].distance(&positions[j]);
+                assert!((rebuilt[i].distance(&rebuilt[j]) - expected).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotate_torsion() {
+        let positions = sample();
+        let parents = [None, Some(0), Some(1), Some(2), Some(3), Some(4)];
+        let mut zmatrix = ZMatrix::from_tree(&positions, &parents).unwrap();
+        zmatrix.entries[3].torsion += 1.0;
+        let rebuilt = zmatrix.to_positions().unwrap();
+        let angle = dihedral(&rebuilt[0], &rebuilt[1], &rebuilt[2], &rebuilt[3]);
+        let original = dihedral(&positions[0], &positions[1], &positions[2], &positions[3]);
+        let difference = (angle - original - 1.0 + PI).rem_euclid(2.0 * PI) - PI;
+        assert!(difference.abs() < 1e-9);
+        // The atoms beyond keep their internal coordinates.
+        let angle = dihedral(&rebuilt[1], &rebuilt[2], &rebuilt[3], &rebuilt[4]);
+        let original = dihedral(&positions[1], &positions[2], &positions[3], &positions[4]);
+        assert!((angle - original).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let positions = sample();
+        assert_eq!(None, ZMatrix::from_tree(&positions, &[None; 6]));
+        assert_eq!(
+            None,
+            ZMatrix::from_tree(
+                &positions,
+                &[None, Some(0), Some(1), Some(4), Some(2), Some(3)]
+            )
+        );
+        assert_eq!(None, ZMatrix::from_tree(&positions[..2], &[None]));
+        let references = [
+            [None, None, None],
+            [Some(0), None, None],
+            [Some(1), Some(1), None],
+        ];
+        assert_eq!(None, ZMatrix::from_positions(&positions[..3], &references));
+        let references = [
+            [None, None, None],
+            [Some(0), None, None],
+            [Some(1), None, None],
+        ];
+        assert_eq!(None, ZMatrix::from_positions(&positions[..3], &references));
+
+        let mut zmatrix = ZMatrix::from_positions(&positions[..2], &references[..2]).unwrap();
+        zmatrix.entries[1].references = [Some(1), None, None];
+        assert_eq!(None, zmatrix.to_positions());
+    }
+}